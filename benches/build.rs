@@ -0,0 +1,97 @@
+//! In-process benchmarks for `TorrentBuilder`.
+//!
+//! Run with `cargo bench`. Unlike `run_benchmarks`, these don't spawn the CLI, so
+//! the per-phase numbers from `build_timed` are free of process start-up noise.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::time::Duration;
+use torrite::{BuildTimings, Mode, Torrent, TorrentBuilder, TorrentOptions};
+
+const THREADS: usize = 4;
+const PIECE_EXP: u32 = 18;
+
+/// A mix of one large file and a batch of small ones, filled with cheap pseudo-random bytes
+fn generate_fixture(dir: &Path) -> u64 {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next_chunk = |len: usize| {
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            data.extend_from_slice(&state.to_le_bytes());
+        }
+        data.truncate(len);
+        data
+    };
+
+    let mut total = 0u64;
+    let mut write = |path: &Path, len: usize| {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, next_chunk(len)).unwrap();
+        total += len as u64;
+    };
+
+    write(&dir.join("large.bin"), 16 * 1024 * 1024);
+    for i in 0..32 {
+        write(&dir.join(format!("small/{i:02}.bin")), 100_000 + i * 4_099);
+    }
+    total
+}
+
+fn build(source: &Path, mode: Mode) -> (Torrent, BuildTimings) {
+    let options = TorrentOptions {
+        mode,
+        piece_length: Some(PIECE_EXP),
+        no_date: true,
+        ..Default::default()
+    };
+    TorrentBuilder::new(source.to_path_buf(), options)
+        .with_threads(THREADS)
+        .build_timed()
+        .unwrap()
+}
+
+fn bench_build(c: &mut Criterion) {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let source = tmp_dir.path().join("fixture");
+    let total_size = generate_fixture(&source);
+
+    let modes = [("v1", Mode::V1), ("v2", Mode::V2), ("hybrid", Mode::Hybrid)];
+
+    let mut group = c.benchmark_group("build");
+    group.throughput(Throughput::Bytes(total_size));
+    for (name, mode) in modes {
+        group.bench_function(name, |b| b.iter(|| black_box(build(&source, mode))));
+    }
+    group.finish();
+
+    // Only the hashing phase, as measured by the builder itself
+    let mut group = c.benchmark_group("hash");
+    group.throughput(Throughput::Bytes(total_size));
+    group.bench_function("v1", |b| {
+        b.iter_custom(|iters| {
+            (0..iters)
+                .map(|_| build(&source, Mode::V1).1.hash_v1)
+                .sum::<Duration>()
+        })
+    });
+    group.bench_function("v2", |b| {
+        b.iter_custom(|iters| {
+            (0..iters)
+                .map(|_| build(&source, Mode::V2).1.hash_v2)
+                .sum::<Duration>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_build
+}
+criterion_main!(benches);
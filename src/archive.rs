@@ -0,0 +1,382 @@
+//! Reading torrent content straight out of zip/tar archives.
+//!
+//! Entries of a plain tar or a zip of stored entries are hashed in place: each one
+//! becomes a [`FileInfo`] pointing at the archive with a `data_offset`. Deflated zip
+//! entries and gzipped tarballs can't be read at an offset, so they are decompressed
+//! front to back with [`read_entries`] while hashing.
+
+use anyhow::{Context, Result, anyhow, bail};
+use flate2::read::GzDecoder;
+use log::{debug, info};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use crate::models::FileInfo;
+use crate::scanner::{FileFilter, is_hidden};
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Zip,
+    Tar,
+    /// A gzip-compressed tarball (`.tar.gz`, `.tgz`)
+    TarGz,
+}
+
+/// An archive entry found while scanning
+struct Entry {
+    path: PathBuf,
+    /// Where the entry's data starts in the archive file; only meaningful when the
+    /// archive isn't compressed
+    data_offset: u64,
+    len: u64,
+}
+
+/// Scans an archive and collects its entries as virtual files
+pub fn scan_archive(
+    archive: &Path,
+    exclude_patterns: &[String],
+    include_patterns: &[String],
+    include_hidden: bool,
+) -> Result<(Vec<FileInfo>, u64)> {
+    let archive = archive
+        .canonicalize()
+        .context("Failed to resolve archive path")?;
+
+    let entries = match detect_format(&archive)? {
+        ArchiveFormat::Zip => zip_entries(&archive)?,
+        ArchiveFormat::Tar => tar_entries(open(&archive)?)?,
+        ArchiveFormat::TarGz => tar_entries(GzDecoder::new(open(&archive)?))?,
+    };
+
+    // The same rules as a directory source, hidden files included
+    let filter = FileFilter::new(None, exclude_patterns, include_patterns, include_hidden);
+    let mut files = Vec::new();
+    let mut total_size = 0u64;
+
+    for Entry {
+        path,
+        data_offset,
+        len,
+    } in entries
+    {
+        if !filter.keep(&path, &path, false, is_hidden(&path)) {
+            continue;
+        }
+
+        debug!("  {} ({} bytes)", path.display(), len);
+
+        total_size += len;
+        files.push(FileInfo {
+            path,
+            full_path: archive.clone(),
+            len,
+            start_offset: 0,
+            is_padding: false,
+            data_offset,
+        });
+    }
+
+    info!(
+        "Found {} archive entries, total size: {} bytes",
+        files.len(),
+        total_size
+    );
+
+    // Filtered and ordered like a directory scan, so the info hash matches the extracted tree
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut current_offset = 0u64;
+    for file in &mut files {
+        file.start_offset = current_offset;
+        current_offset += file.len;
+    }
+
+    Ok((files, total_size))
+}
+
+/// Whether the archive's entries have to be decompressed, so they can only be read
+/// front to back with [`read_entries`] instead of in place
+pub fn is_compressed(archive: &Path) -> Result<bool> {
+    match detect_format(archive)? {
+        ArchiveFormat::Tar => Ok(false),
+        ArchiveFormat::TarGz => Ok(true),
+        ArchiveFormat::Zip => {
+            let mut zip = open_zip(archive)?;
+            for i in 0..zip.len() {
+                if zip.by_index_raw(i)?.compression() != zip::CompressionMethod::Stored {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Call `visit` with each of `files` and a reader of its decompressed contents, in
+/// order. Padding files read as zeros.
+///
+/// Zip entries are opened directly. A tarball is read in a single pass as long as
+/// `files` follows the archive's order, and reopened whenever a file comes earlier
+/// in the archive than the one before it.
+pub fn read_entries(
+    archive: &Path,
+    files: &[FileInfo],
+    mut visit: impl FnMut(&FileInfo, &mut dyn Read) -> Result<()>,
+) -> Result<()> {
+    let format = detect_format(archive)?;
+    if format == ArchiveFormat::Zip {
+        let mut zip = open_zip(archive)?;
+        let mut indices = HashMap::new();
+        for i in 0..zip.len() {
+            let entry = zip.by_index_raw(i)?;
+            if !entry.is_dir()
+                && let Some(path) = entry.enclosed_name()
+            {
+                indices.insert(entry_path(&path)?, i);
+            }
+        }
+        for file in files {
+            if file.is_padding {
+                visit(file, &mut std::io::repeat(0).take(file.len))?;
+                continue;
+            }
+            let index = *indices
+                .get(&file.path)
+                .ok_or_else(|| anyhow!("{} is missing from the archive", file.path.display()))?;
+            let mut entry = zip
+                .by_index(index)
+                .with_context(|| format!("Failed to read zip entry {}", file.path.display()))?;
+            visit(file, &mut entry)?;
+        }
+        return Ok(());
+    }
+
+    let open_tar = || -> Result<tar::Archive<Box<dyn Read>>> {
+        let reader: Box<dyn Read> = match format {
+            ArchiveFormat::TarGz => Box::new(GzDecoder::new(open(archive)?)),
+            _ => Box::new(open(archive)?),
+        };
+        Ok(tar::Archive::new(reader))
+    };
+
+    // Where each file sits in the archive, counting every entry
+    let mut positions = HashMap::new();
+    for (i, entry) in open_tar()?.entries()?.enumerate() {
+        let entry = entry.context("Failed to read tar entry")?;
+        if entry.header().entry_type().is_file() {
+            positions.insert(entry_path(&entry.path()?)?, i);
+        }
+    }
+
+    let mut remaining = files.iter().peekable();
+    while remaining.peek().is_some() {
+        let mut tar = open_tar()?;
+        let mut entries = tar.entries()?.enumerate();
+        let mut next_position = 0;
+        while let Some(file) = remaining.peek() {
+            if file.is_padding {
+                visit(file, &mut std::io::repeat(0).take(file.len))?;
+                remaining.next();
+                continue;
+            }
+            let position = *positions
+                .get(&file.path)
+                .ok_or_else(|| anyhow!("{} is missing from the archive", file.path.display()))?;
+            if position < next_position {
+                debug!("Reopening the archive for {}", file.path.display());
+                break;
+            }
+            for (i, entry) in entries.by_ref() {
+                let mut entry = entry.context("Failed to read tar entry")?;
+                next_position = i + 1;
+                if i == position {
+                    visit(file, &mut entry)?;
+                    break;
+                }
+            }
+            remaining.next();
+        }
+    }
+    Ok(())
+}
+
+/// Default torrent name for an archive: its filename without the archive extension
+pub fn archive_stem(archive: &Path) -> Option<String> {
+    let name = archive.file_name()?.to_str()?;
+    let lower = name.to_ascii_lowercase();
+    for ext in [".zip", ".tar.gz", ".tgz", ".tar"] {
+        if lower.ends_with(ext) && name.len() > ext.len() {
+            return Some(name[..name.len() - ext.len()].to_string());
+        }
+    }
+    Some(name.to_string())
+}
+
+fn detect_format(archive: &Path) -> Result<ArchiveFormat> {
+    let mut header = [0u8; 512];
+    let mut file = File::open(archive)
+        .with_context(|| format!("Failed to open archive: {}", archive.display()))?;
+    let mut read = 0;
+    while read < header.len() {
+        match file.read(&mut header[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+
+    if header[..read].starts_with(b"PK\x03\x04") || header[..read].starts_with(b"PK\x05\x06") {
+        Ok(ArchiveFormat::Zip)
+    } else if read >= 262 && &header[257..262] == b"ustar" {
+        Ok(ArchiveFormat::Tar)
+    } else if header[..read].starts_with(&[0x1F, 0x8B]) {
+        Ok(ArchiveFormat::TarGz)
+    } else if let Some(compression) = unsupported_compression(&header[..read]) {
+        bail!(
+            "{} is {}-compressed, which isn't supported; use a gzip (.tar.gz) or plain \
+             tarball, or a zip",
+            archive.display(),
+            compression
+        )
+    } else {
+        bail!("{} is not a zip or tar archive", archive.display())
+    }
+}
+
+/// The compression of a file that torrite can't read archives out of
+fn unsupported_compression(header: &[u8]) -> Option<&'static str> {
+    if header.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+        Some("zstd")
+    } else if header.starts_with(b"BZh") {
+        Some("bzip2")
+    } else if header.starts_with(&[0xFD, b'7', b'z', b'X', b'Z']) {
+        Some("xz")
+    } else {
+        None
+    }
+}
+
+fn open(archive: &Path) -> Result<BufReader<File>> {
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open archive: {}", archive.display()))?;
+    Ok(BufReader::new(file))
+}
+
+fn open_zip(archive: &Path) -> Result<zip::ZipArchive<BufReader<File>>> {
+    zip::ZipArchive::new(open(archive)?)
+        .with_context(|| format!("Failed to read zip archive: {}", archive.display()))
+}
+
+/// Turn an archive-internal path into a safe relative path
+fn entry_path(raw: &Path) -> Result<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in raw.components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => bail!("Unsafe path in archive: {}", raw.display()),
+        }
+    }
+    if path.as_os_str().is_empty() {
+        bail!("Empty path in archive");
+    }
+    Ok(path)
+}
+
+fn zip_entries(archive: &Path) -> Result<Vec<Entry>> {
+    let mut zip = open_zip(archive)?;
+
+    let mut entries = Vec::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        let entry = zip.by_index_raw(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = entry.name().unwrap_or_default().to_string();
+        if entry.encrypted() {
+            bail!("Zip entry '{}' is encrypted", name);
+        }
+        let stored = match entry.compression() {
+            zip::CompressionMethod::Stored => true,
+            zip::CompressionMethod::Deflated => false,
+            method => bail!(
+                "Zip entry '{}' uses {} compression, which isn't supported; only stored \
+                 and deflated entries are",
+                name,
+                method
+            ),
+        };
+
+        let path = entry
+            .enclosed_name()
+            .ok_or_else(|| anyhow!("Unsafe path in archive: {}", name))
+            .and_then(|p| entry_path(&p))?;
+        let data_offset = if stored {
+            entry
+                .data_start()
+                .ok_or_else(|| anyhow!("Failed to locate data for zip entry '{}'", name))?
+        } else {
+            0
+        };
+
+        entries.push(Entry {
+            path,
+            data_offset,
+            len: entry.size(),
+        });
+    }
+    Ok(entries)
+}
+
+fn tar_entries(reader: impl Read) -> Result<Vec<Entry>> {
+    let mut tar = tar::Archive::new(reader);
+
+    let mut entries = Vec::new();
+    for entry in tar.entries().context("Failed to read tar archive")? {
+        let entry = entry.context("Failed to read tar entry")?;
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_gnu_sparse() {
+            bail!(
+                "Tar entry '{}' is a sparse file and can't be hashed in place",
+                entry.path()?.display()
+            );
+        }
+        if !entry_type.is_file() {
+            continue;
+        }
+
+        entries.push(Entry {
+            path: entry_path(&entry.path()?)?,
+            data_offset: entry.raw_file_position(),
+            len: entry.size(),
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_stem() {
+        assert_eq!(archive_stem(Path::new("/x/Album.zip")).unwrap(), "Album");
+        assert_eq!(archive_stem(Path::new("Album.TAR")).unwrap(), "Album");
+        assert_eq!(archive_stem(Path::new("Album.tar.gz")).unwrap(), "Album");
+        assert_eq!(archive_stem(Path::new("Album.tgz")).unwrap(), "Album");
+        assert_eq!(archive_stem(Path::new("Album.v1")).unwrap(), "Album.v1");
+    }
+
+    #[test]
+    fn test_entry_path_rejects_traversal() {
+        assert_eq!(
+            entry_path(Path::new("./a/b.txt")).unwrap(),
+            PathBuf::from("a/b.txt")
+        );
+        assert!(entry_path(Path::new("../etc/passwd")).is_err());
+        assert!(entry_path(Path::new("/abs")).is_err());
+    }
+}
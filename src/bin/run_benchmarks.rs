@@ -38,11 +38,31 @@ struct HyperfineOutput {
 #[derive(Deserialize, Debug)]
 struct HyperfineRun {
     command: String,
+    #[serde(flatten)]
+    stats: RunStats,
+}
+
+/// Parse the file hyperfine writes with `--export-json`
+fn parse_hyperfine_output(json: &str) -> HyperfineOutput {
+    serde_json::from_str(json).expect("Failed to parse hyperfine json")
+}
+
+/// Timing statistics for one tool in one scenario, in seconds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct RunStats {
     mean: f64,
+    /// Hyperfine reports `null` when only a single run was made
+    stddev: Option<f64>,
+    min: f64,
+    max: f64,
 }
 
+/// Version of the `--json` output layout; bump on any incompatible change
+const SCHEMA_VERSION: u32 = 2;
+
 #[derive(Serialize, Debug)]
 struct BenchmarkResults {
+    schema_version: u32,
     tools: Vec<ToolResult>,
 }
 
@@ -56,7 +76,11 @@ struct ToolResult {
 #[derive(Serialize, Debug)]
 struct ScenarioResult {
     scenario: String,
+    /// Mean time, kept under its original name for existing consumers
     time: Option<f64>,
+    stddev: Option<f64>,
+    min: Option<f64>,
+    max: Option<f64>,
     error: Option<String>,
 }
 
@@ -92,6 +116,7 @@ fn main() {
         println!("🚀 Torrent Benchmark Runner");
         println!("   Tools being compared:");
         println!("     - torrite:      {} (V1, V2, Hybrid)", torrite);
+        println!("       hash backend: {}", hash_backend(&torrite));
         if !only_torrite {
             println!("     - mktorrent:    {} (V1 baseline)", mktorrent);
             println!("     - mkbrr:        {} (V1)", mkbrr);
@@ -167,8 +192,11 @@ fn main() {
     }
 
     let mut aggregated_results: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    // Full statistics per scenario, in the same order, for the JSON output
+    let mut run_stats: BTreeMap<String, Vec<Option<RunStats>>> = BTreeMap::new();
     for tool in &tool_names {
         aggregated_results.insert(tool.to_string(), Vec::new());
+        run_stats.insert(tool.to_string(), Vec::new());
     }
 
     // 3. Run Hyperfine for each scenario
@@ -190,6 +218,7 @@ fn main() {
                     "N/A".to_string()
                 };
                 aggregated_results.get_mut(*tool).unwrap().push(na_str);
+                run_stats.get_mut(*tool).unwrap().push(None);
             }
             continue;
         }
@@ -325,23 +354,27 @@ fn main() {
                     "Err".to_string()
                 };
                 aggregated_results.get_mut(*tool).unwrap().push(err_str);
+                run_stats.get_mut(*tool).unwrap().push(None);
             }
         } else {
             // Read JSON results
             let json_content =
                 fs::read_to_string(&json_output_path).expect("Failed to read hyperfine json");
-            let output: HyperfineOutput =
-                serde_json::from_str(&json_content).expect("Failed to parse hyperfine json");
+            let output = parse_hyperfine_output(&json_content);
 
             // Create a map for this run to easily loop by name
-            let mut run_map: BTreeMap<String, f64> = BTreeMap::new();
+            let mut run_map: BTreeMap<String, RunStats> = BTreeMap::new();
             for res in output.results {
-                run_map.insert(res.command, res.mean);
+                run_map.insert(res.command, res.stats);
             }
 
             // Populate aggregated results preserving order
             for tool in &tool_names {
-                if let Some(mean) = run_map.get(*tool) {
+                run_stats
+                    .get_mut(*tool)
+                    .unwrap()
+                    .push(run_map.get(*tool).copied());
+                if let Some(RunStats { mean, .. }) = run_map.get(*tool) {
                     let time_str = if tool.contains("torrite") {
                         format!("**{:.3}s**", mean)
                     } else {
@@ -409,6 +442,7 @@ fn main() {
     if json_output {
         // --- Generate JSON Output ---
         let mut json_results = BenchmarkResults {
+            schema_version: SCHEMA_VERSION,
             tools: Vec::new(),
         };
 
@@ -438,9 +472,13 @@ fn main() {
                         (None, Some(time_str.clone()))
                     };
 
+                    let stats = run_stats[tool.as_str()][i];
                     scenario_results.push(ScenarioResult {
                         scenario: scenario_name.to_string(),
                         time,
+                        stddev: stats.and_then(|s| s.stddev),
+                        min: stats.map(|s| s.min),
+                        max: stats.map(|s| s.max),
                         error,
                     });
                 }
@@ -513,6 +551,18 @@ fn main() {
     }
 }
 
+/// The SHA-1/SHA-256 implementation the torrite binary reports, so results from
+/// `asm` and default builds can be told apart
+fn hash_backend(torrite: &str) -> String {
+    Command::new(torrite)
+        .arg("--print-backend")
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
 // Helper to check if a binary is runnable
 fn check_binary_exists(bin: &str) {
     // Simple check: try running with --help or --version
@@ -559,3 +609,66 @@ fn sanitize_filename(name: &str) -> String {
         .replace(")", "")
         .to_lowercase()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hyperfine_statistics() {
+        let json = r#"{
+          "results": [
+            {
+              "command": "torrite (V1)",
+              "mean": 1.25,
+              "stddev": 0.05,
+              "median": 1.24,
+              "user": 3.1,
+              "system": 0.4,
+              "min": 1.19,
+              "max": 1.33,
+              "times": [1.19, 1.24, 1.33],
+              "exit_codes": [0, 0, 0]
+            },
+            {
+              "command": "mktorrent (V1)",
+              "mean": 2.5,
+              "stddev": null,
+              "median": 2.5,
+              "user": 2.4,
+              "system": 0.1,
+              "min": 2.5,
+              "max": 2.5,
+              "times": [2.5],
+              "exit_codes": [0]
+            }
+          ]
+        }"#;
+
+        let output = parse_hyperfine_output(json);
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.results[0].command, "torrite (V1)");
+        assert_eq!(
+            output.results[0].stats,
+            RunStats {
+                mean: 1.25,
+                stddev: Some(0.05),
+                min: 1.19,
+                max: 1.33,
+            }
+        );
+        assert_eq!(output.results[1].stats.stddev, None);
+
+        let scenario = ScenarioResult {
+            scenario: "Single".to_string(),
+            time: Some(output.results[0].stats.mean),
+            stddev: output.results[0].stats.stddev,
+            min: Some(output.results[0].stats.min),
+            max: Some(output.results[0].stats.max),
+            error: None,
+        };
+        let value = serde_json::to_value(&scenario).unwrap();
+        assert_eq!(value["stddev"], 0.05);
+        assert_eq!(value["max"], 1.33);
+    }
+}
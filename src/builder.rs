@@ -1,12 +1,234 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use log::{debug, info, warn};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use serde_bytes::ByteBuf;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::{MAX_PIECE_EXP, check_piece_exp};
+use crate::hashing::{
+    Cancellation, ChunkSize, HashCache, HashError, MAX_V2_CHUNK_BLOCKS, MIN_V2_CHUNK_BLOCKS,
+    SINGLE_FILE_CHUNKS_PER_THREAD, hash_backend, hash_v1_pieces, hash_v1_pieces_cancellable,
+};
+use crate::models::{
+    FileEntry, Info, Mode, Node, PaddingReport, Torrent, TorrentOptions, validate_torrent_name,
+};
+use crate::piece::{apply_min_pieces, calculate_num_pieces, calculate_piece_length};
+use crate::scanner::{
+    Scanner, WalkOptions, add_padding_files, find_case_collisions, generate_cross_seed_id,
+    glob_base, is_glob_source, resolve_source, scan_glob,
+};
+use crate::tree::hybrid_layout_mismatch;
+
+/// Output of the hashing phase, consumed by `build_torrent`
+struct HashedContent {
+    pieces_bytes: Vec<u8>,
+    file_tree: Option<BTreeMap<String, Node>>,
+    piece_layers: Option<BTreeMap<ByteBuf, ByteBuf>>,
+    meta_version: Option<u8>,
+}
+
+/// Wall-clock time spent in each phase of [`TorrentBuilder::build_timed`], and
+/// how much data was hashed
+///
+/// Phases that don't run for the selected mode (e.g. V1 hashing for a V2-only
+/// torrent) are reported as zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildTimings {
+    /// Walking the source and collecting file metadata
+    pub scan: Duration,
+    /// V1 piece hashing
+    pub hash_v1: Duration,
+    /// V2 merkle tree hashing (including hash cache load/save)
+    pub hash_v2: Duration,
+    /// The whole build, from scan to the finished `Torrent`
+    pub total: Duration,
+    /// Bytes fed through the hashers. A hybrid build counts both passes, and V2
+    /// files served from the hash cache count as hashed.
+    pub bytes_hashed: u64,
+}
+
+impl BuildTimings {
+    /// Hashing throughput over the whole build, in MiB/s
+    pub fn mib_per_sec(&self) -> f64 {
+        let secs = self.total.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes_hashed as f64 / (1024.0 * 1024.0) / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// What a build would produce, computed without hashing (see [`TorrentBuilder::dry_run_report`])
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DryRunReport {
+    /// Files that would be included, in torrent order
+    pub files: Vec<FileEntryView>,
+    pub total_size: u64,
+    pub piece_length: u64,
+    /// `piece_length` as a power of two
+    pub piece_exp: u32,
+    pub num_pieces: u64,
+    pub mode: Mode,
+}
+
+/// A file in a [`DryRunReport`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileEntryView {
+    /// Path relative to the torrent root
+    pub path: PathBuf,
+    pub length: u64,
+}
+
+/// Torrent name used when none is given: the source's basename (archive extension
+/// stripped, or the last path segment of a URL), the `--relative-to` directory's
+/// basename or a glob's base directory. Names that depend on the content, as with
+/// `--flatten-single` and `--strip-root`, come from [`TorrentBuilder::torrent_name`].
+pub fn default_torrent_name(source: &Path, options: &TorrentOptions) -> String {
+    #[cfg(feature = "http")]
+    if options.from_url
+        && let Some(name) = crate::remote::url_file_name(&source.to_string_lossy())
+    {
+        return name;
+    }
+    #[cfg(feature = "archive")]
+    if options.from_archive
+        && let Some(stem) = crate::archive::archive_stem(source)
+    {
+        return stem;
+    }
+    // A glob source is named after the directory its matches are stored under
+    let base = options
+        .relative_to
+        .clone()
+        .or_else(|| is_glob_source(source).then(|| glob_base(source)))
+        .map(|base| base.canonicalize().unwrap_or(base));
+    base.as_deref()
+        .unwrap_or(source)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("output")
+        .to_string()
+}
+
+/// How to walk the source for `options`
+fn walk_options(options: &TorrentOptions) -> WalkOptions {
+    WalkOptions {
+        keep_empty_dirs: options.keep_empty_dirs,
+        no_recursion: options.no_recursion,
+        skip_empty_files: options.skip_empty_files,
+        include_hidden: options.include_hidden,
+    }
+}
+
+/// Seed used for random choices when [`TorrentOptions::deterministic`] is set
+pub const DETERMINISTIC_SEED: u64 = 0x746f_7272_6974_6521;
+
+/// Shuffle the URLs within each tier, leaving the tiers themselves in order
+pub fn shuffle_tiers(tiers: &mut [Vec<String>], rng: &mut impl Rng) {
+    for tier in tiers {
+        tier.shuffle(rng);
+    }
+}
+
+/// A file's modification time in seconds since the Unix epoch, for `--store-mtime`.
+/// `None` where there's nothing on disk to read it from, such as a
+/// `--keep-empty-dirs` placeholder.
+fn file_mtime(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(chrono::DateTime::<chrono::Utc>::from(modified).timestamp())
+}
+
+/// Read a written `.torrent` back and check that the info dictionary on disk hashes
+/// to the same info hashes as `torrent`, for `--rehash-verify`
+///
+/// This catches what goes wrong between serializing and the bytes on disk: a
+/// truncated write, a bit flip in storage. It can't catch a bug in serializing
+/// [`Info`] itself, because the expected hashes come from that same serialization.
+pub fn check_written_torrent(path: &Path, torrent: &Torrent) -> Result<()> {
+    use serde_bencode::value::Value;
+    use sha1::{Digest, Sha1};
+    use sha2::Sha256;
+
+    let data =
+        std::fs::read(path).with_context(|| format!("Failed to read back {}", path.display()))?;
+    let Value::Dict(mut root) = serde_bencode::from_bytes::<Value>(&data)
+        .with_context(|| format!("{} is not valid bencode", path.display()))?
+    else {
+        anyhow::bail!("{} is not a bencoded dictionary", path.display());
+    };
+    let info = root
+        .remove(b"info".as_slice())
+        .with_context(|| format!("{} has no info dictionary", path.display()))?;
+    let info_bytes = serde_bencode::to_bytes(&info).context("Failed to re-encode info")?;
+
+    if let Some(expected) = torrent.info_hash_v1() {
+        let actual: [u8; 20] = Sha1::digest(&info_bytes).into();
+        if actual != expected {
+            anyhow::bail!(
+                "Self-check failed: {} has V1 info hash {}, expected {}",
+                path.display(),
+                hex::encode(actual),
+                hex::encode(expected)
+            );
+        }
+    }
+    if let Some(expected) = torrent.info_hash_v2() {
+        let actual: [u8; 32] = Sha256::digest(&info_bytes).into();
+        if actual != expected {
+            anyhow::bail!(
+                "Self-check failed: {} has V2 info hash {}, expected {}",
+                path.display(),
+                hex::encode(actual),
+                hex::encode(expected)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Drop the one directory every file sits under from the stored paths, for `--strip-root`
+fn strip_root_dir(files: Vec<crate::models::FileInfo>) -> Result<Vec<crate::models::FileInfo>> {
+    let Some(root) = root_dir(&files) else {
+        anyhow::bail!("--strip-root needs every file to be inside a single top-level directory");
+    };
+    debug!("Stripping top-level directory: {}", root.display());
+
+    Ok(files
+        .into_iter()
+        .map(|mut file| {
+            file.path = file
+                .path
+                .strip_prefix(&root)
+                .map(Path::to_path_buf)
+                .unwrap_or(file.path);
+            file
+        })
+        .collect())
+}
+
+/// The one directory every file sits under, if there is one
+fn root_dir(files: &[crate::models::FileInfo]) -> Option<PathBuf> {
+    let root = files
+        .first()
+        .and_then(|file| file.path.components().next())
+        .map(|root| PathBuf::from(root.as_os_str()))?;
+    files
+        .iter()
+        .all(|file| file.path.starts_with(&root) && file.path != root)
+        .then_some(root)
+}
 
-use crate::hashing::{hash_v1_pieces, hash_v2_files};
-use crate::models::{FileEntry, Info, Mode, Torrent, TorrentOptions};
-use crate::piece::{calculate_num_pieces, calculate_piece_length};
-use crate::scanner::{add_padding_files, generate_cross_seed_id, scan_files};
+/// Upper bound on hashing threads; beyond this extra threads only add contention
+pub const MAX_THREADS: usize = 512;
 
 /// Builder for creating torrent files
 pub struct TorrentBuilder {
@@ -15,7 +237,16 @@ pub struct TorrentBuilder {
     options: TorrentOptions,
     verbose: bool,
     show_progress: bool,
+    progress_bar: Option<ProgressBar>,
     num_threads: usize,
+    hash_cache: Option<PathBuf>,
+    cache_compress: bool,
+    v2_chunk_blocks: Option<usize>,
+    single_file_chunks: usize,
+    max_memory: Option<u64>,
+    cancel: Option<Cancellation>,
+    /// Walk of a directory source, shared by naming, reports and the build
+    walked: OnceLock<Scanner>,
 }
 
 impl TorrentBuilder {
@@ -27,7 +258,15 @@ impl TorrentBuilder {
             options,
             verbose: false,
             show_progress: false,
+            progress_bar: None,
             num_threads: num_cpus::get(),
+            hash_cache: None,
+            cache_compress: false,
+            v2_chunk_blocks: None,
+            single_file_chunks: SINGLE_FILE_CHUNKS_PER_THREAD,
+            max_memory: None,
+            cancel: None,
+            walked: OnceLock::new(),
         }
     }
 
@@ -37,7 +276,7 @@ impl TorrentBuilder {
         self
     }
 
-    /// Enable verbose output
+    /// Log the version and build settings at info level before building
     pub fn with_verbose(mut self, verbose: bool) -> Self {
         self.verbose = verbose;
         self
@@ -49,14 +288,259 @@ impl TorrentBuilder {
         self
     }
 
-    /// Set the number of threads for hashing
+    /// Report hashing progress on `pb` instead of drawing a bar on stderr, e.g. a
+    /// hidden bar polled by a GUI. Its length is set to the total bytes to hash,
+    /// which covers both passes of a hybrid build.
+    pub fn with_progress_bar(mut self, pb: ProgressBar) -> Self {
+        self.progress_bar = Some(pb);
+        self
+    }
+
+    /// Set the number of threads for hashing. `0` means one per CPU core, and
+    /// values above [`MAX_THREADS`] are capped.
     pub fn with_threads(mut self, threads: usize) -> Self {
-        self.num_threads = threads;
+        self.num_threads = match threads {
+            0 => num_cpus::get(),
+            n if n > MAX_THREADS => {
+                warn!("{} threads requested, capping to {}", n, MAX_THREADS);
+                MAX_THREADS
+            }
+            n => n,
+        };
         self
     }
 
-    /// Resolve tracker configuration based on announce URLs
+    /// Reuse V2 block hashes from a cache file, updating it after hashing
+    pub fn with_hash_cache(mut self, path: PathBuf, compress: bool) -> Self {
+        self.hash_cache = Some(path);
+        self.cache_compress = compress;
+        self
+    }
+
+    /// Set how many 16 KiB blocks each parallel V2 work item reads (default
+    /// [`DEFAULT_V2_CHUNK_BLOCKS`](crate::hashing::DEFAULT_V2_CHUNK_BLOCKS)). Larger
+    /// chunks suit fast drives, smaller ones spread slow reads across threads. Values outside
+    /// [`MIN_V2_CHUNK_BLOCKS`]..=[`MAX_V2_CHUNK_BLOCKS`] are clamped; the hashes
+    /// don't depend on it. Without this, a lone file is split more finely
+    /// ([`with_single_file_chunks_per_thread`](Self::with_single_file_chunks_per_thread));
+    /// with it, the size is used as given.
+    pub fn with_v2_chunk_blocks(mut self, blocks: usize) -> Self {
+        let clamped = blocks.clamp(MIN_V2_CHUNK_BLOCKS, MAX_V2_CHUNK_BLOCKS);
+        if clamped != blocks {
+            warn!(
+                "V2 chunk size of {} blocks is out of range, using {}",
+                blocks, clamped
+            );
+        }
+        self.v2_chunk_blocks = Some(clamped);
+        self
+    }
+
+    /// Set how many V2 work items per thread a lone file is split into (default
+    /// [`SINGLE_FILE_CHUNKS_PER_THREAD`]). More items even out slow reads across
+    /// threads; fewer mean larger, more sequential reads. `0` is treated as 1. It
+    /// has no effect with [`with_v2_chunk_blocks`](Self::with_v2_chunk_blocks), and
+    /// the hashes don't depend on it.
+    pub fn with_single_file_chunks_per_thread(mut self, chunks: usize) -> Self {
+        self.single_file_chunks = chunks.max(1);
+        self
+    }
+
+    /// Cap the piece buffers held at once during V1 hashing to roughly `bytes`.
+    /// Each hashing thread holds one piece, so fewer threads are used when
+    /// `threads * piece_length` would exceed the cap, but never fewer than one.
+    pub fn with_max_memory(mut self, bytes: u64) -> Self {
+        self.max_memory = Some(bytes);
+        self
+    }
+
+    /// Stop hashing once `cancel` is triggered, e.g. from a Ctrl-C handler. The build
+    /// then fails with [`HashError::Cancelled`].
+    pub fn with_cancellation(mut self, cancel: Cancellation) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Threads to hash with once the piece length is known, honouring `--max-memory`
+    fn hashing_threads(&self, piece_length: u64) -> usize {
+        match self.max_memory {
+            Some(max_memory) => {
+                let fits = (max_memory / piece_length).max(1);
+                self.num_threads
+                    .min(usize::try_from(fits).unwrap_or(usize::MAX))
+            }
+            None => self.num_threads,
+        }
+    }
+
+    /// Collect the files to hash, either from the filesystem or from an archive
+    fn scan(&self) -> Result<(Vec<crate::models::FileInfo>, u64)> {
+        if self.options.from_url {
+            anyhow::bail!("A URL source is hashed while it downloads and can't be scanned");
+        }
+        if let Some(length) = self.options.length {
+            // Taken on trust here; the build checks it against what can actually be read
+            let file = crate::models::FileInfo {
+                path: PathBuf::from(self.default_name()),
+                full_path: self.source.clone(),
+                len: length,
+                start_offset: 0,
+                is_padding: false,
+                data_offset: 0,
+            };
+            return Ok((vec![file], length));
+        }
+        if self.options.from_archive {
+            #[cfg(feature = "archive")]
+            return crate::archive::scan_archive(
+                &self.source,
+                &self.options.exclude,
+                &self.options.include_only,
+                self.options.include_hidden,
+            )
+            .map(|(mut files, total_size)| {
+                if self.options.skip_empty_files {
+                    files.retain(|f| f.len > 0);
+                }
+                (files, total_size)
+            });
+            #[cfg(not(feature = "archive"))]
+            anyhow::bail!("This build of torrite was compiled without archive support");
+        }
+        let (files, total_size) = if is_glob_source(&self.source) {
+            scan_glob(
+                &self.source,
+                self.output_file.as_deref(),
+                &self.options.exclude,
+                &self.options.include_only,
+                walk_options(&self.options),
+            )?
+        } else {
+            self.walked()?.files(
+                self.output_file.as_deref(),
+                &self.options.exclude,
+                &self.options.include_only,
+            )?
+        };
+        let files = if self.options.strip_root {
+            strip_root_dir(files)?
+        } else {
+            files
+        };
+        match &self.options.relative_to {
+            Some(base) => Ok((self.rebase_paths(files, base)?, total_size)),
+            None => Ok((files, total_size)),
+        }
+    }
+
+    /// Whether the scanned files make a single-file torrent. `--relative-to` always
+    /// produces a multi-file layout, even for a single file.
+    fn is_single_file(&self, files: &[crate::models::FileInfo]) -> bool {
+        !self.options.from_archive
+            && self.options.relative_to.is_none()
+            && (self.source.is_file() || self.flattens(files))
+    }
+
+    /// Whether `--flatten-single` turns this directory source into a single-file torrent
+    fn flattens(&self, files: &[crate::models::FileInfo]) -> bool {
+        self.options.flatten_single
+            && self.source.is_dir()
+            && matches!(files, [file] if file.path.components().count() == 1)
+    }
+
+    /// Store paths relative to `base` instead of the source, for `--relative-to`
+    fn rebase_paths(
+        &self,
+        files: Vec<crate::models::FileInfo>,
+        base: &Path,
+    ) -> Result<Vec<crate::models::FileInfo>> {
+        let base = base
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve --relative-to path: {}", base.display()))?;
+        let source = resolve_source(&self.source_root())?;
+        let prefix = source
+            .strip_prefix(&base)
+            .ok()
+            .filter(|p| !p.as_os_str().is_empty())
+            .with_context(|| {
+                format!(
+                    "Source {} is not inside --relative-to directory {}",
+                    source.display(),
+                    base.display()
+                )
+            })?;
+
+        // A single file's scanned path is its basename, which `prefix` already ends with
+        let prefix = if source.is_file() {
+            prefix.parent().unwrap_or(Path::new(""))
+        } else {
+            prefix
+        };
+        debug!("Storing paths under: {}", prefix.display());
+
+        Ok(files
+            .into_iter()
+            .map(|mut file| {
+                file.path = prefix.join(&file.path);
+                file
+            })
+            .collect())
+    }
+
+    /// The source path, or a glob source's base directory
+    fn source_root(&self) -> PathBuf {
+        if is_glob_source(&self.source) {
+            glob_base(&self.source)
+        } else {
+            self.source.clone()
+        }
+    }
+
+    /// The source walked once for the builder's options; later scans re-apply the
+    /// filters to it without touching the disk
+    fn walked(&self) -> Result<&Scanner> {
+        if let Some(scanner) = self.walked.get() {
+            return Ok(scanner);
+        }
+        let scanner = Scanner::new(&self.source, walk_options(&self.options))?;
+        Ok(self.walked.get_or_init(|| scanner))
+    }
+
+    fn default_name(&self) -> String {
+        default_torrent_name(&self.source, &self.options)
+    }
+
+    /// Name the torrent will get: `--name`, else with `--strip-root` the directory
+    /// that is stripped, else with `--flatten-single` the one file kept, else
+    /// [`default_torrent_name`]. The walk this needs is kept for the build, so callers
+    /// naming the output file don't walk the source twice.
+    pub fn torrent_name(&self) -> Result<String> {
+        if let Some(name) = &self.options.name {
+            return Ok(name.trim().to_string());
+        }
+        if (self.options.strip_root || self.options.flatten_single) && self.source.is_dir() {
+            let (files, _) = self.walked()?.files(
+                self.output_file.as_deref(),
+                &self.options.exclude,
+                &self.options.include_only,
+            )?;
+            let name = if self.options.strip_root {
+                root_dir(&files)
+            } else {
+                self.flattens(&files).then(|| files[0].path.clone())
+            };
+            if let Some(name) = name {
+                return Ok(name.to_string_lossy().into_owned());
+            }
+        }
+        Ok(self.default_name())
+    }
+
+    /// Resolve tracker configuration from the preset, else the announce URLs
     fn resolve_tracker_config(&self) -> Option<&'static crate::trackers::TrackerConfig> {
+        if let Some(preset) = &self.options.tracker_preset {
+            return crate::trackers::find_tracker_preset(preset);
+        }
         if self.options.announce.is_empty() {
             return None;
         }
@@ -71,74 +555,324 @@ impl TorrentBuilder {
         None
     }
 
+    /// Whether the private flag ends up set: -p/--private or a profile asks for it, or
+    /// the tracker requires it and the user didn't explicitly ask for a public torrent
+    fn is_private(&self, config: Option<&crate::trackers::TrackerConfig>) -> bool {
+        self.options.private
+            || (!self.options.public && config.is_some_and(|cfg| cfg.requires_private))
+    }
+
+    /// Warn (or fail with `strict`) when the options break a tracker's upload rules
+    fn check_tracker_requirements(
+        &self,
+        config: Option<&crate::trackers::TrackerConfig>,
+    ) -> Result<()> {
+        let Some(cfg) = config.filter(|cfg| cfg.requires_private && !self.options.private) else {
+            return Ok(());
+        };
+
+        if !self.options.public {
+            warn!(
+                "{} requires the private flag; setting it (pass --public to opt out)",
+                cfg.urls[0]
+            );
+            return Ok(());
+        }
+
+        let msg = format!(
+            "{} requires the private flag, but the torrent is public; the upload may be rejected",
+            cfg.urls[0]
+        );
+        if self.options.strict {
+            anyhow::bail!(msg);
+        }
+        warn!("{}", msg);
+        Ok(())
+    }
+
+    /// Warn when a private torrent announces to open trackers: the private flag keeps
+    /// peers off DHT and PEX, but anyone can join the swarm through a public tracker.
+    /// Failing that, warn when none of its trackers is a known private one, since the
+    /// flag may not be intended.
+    fn check_public_trackers(&self, config: Option<&crate::trackers::TrackerConfig>) {
+        if !self.is_private(config) {
+            return;
+        }
+        let urls: Vec<&str> = self
+            .options
+            .announce
+            .iter()
+            .flat_map(|tier| tier.split(','))
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .collect();
+        let public: Vec<&str> = urls
+            .iter()
+            .copied()
+            .filter(|url| crate::trackers::is_public_tracker(url))
+            .collect();
+        if !public.is_empty() {
+            warn!(
+                "The torrent is private but announces to public tracker(s): {}. \
+                 Anyone can join the swarm through them; drop them or the private flag",
+                public.join(", ")
+            );
+        } else if config.is_none() && !urls.is_empty() {
+            warn!(
+                "The torrent is private but none of its trackers is a known private \
+                 tracker; check the private flag is intended"
+            );
+        }
+    }
+
+    /// Reject piece length exponents that would overflow the piece length
+    fn check_piece_exponents(&self) -> Result<()> {
+        for exp in [
+            self.options.piece_length,
+            self.options.min_piece_exp,
+            self.options.max_piece_exp,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            check_piece_exp(exp).map_err(anyhow::Error::msg)?;
+        }
+        Ok(())
+    }
+
+    /// Warn (or fail with `strict`) about source strings trackers may not match:
+    /// anything outside printable ASCII, including whitespace
+    fn check_source_string(&self) -> Result<()> {
+        let Some(source) = &self.options.source_string else {
+            return Ok(());
+        };
+        if source.chars().all(|c| c.is_ascii_graphic()) {
+            return Ok(());
+        }
+
+        let msg = format!(
+            "Source string {:?} contains non-ASCII or whitespace characters; trackers and cross-seed tools may not match it",
+            source
+        );
+        if self.options.strict {
+            anyhow::bail!(msg);
+        }
+        warn!("{}", msg);
+        Ok(())
+    }
+
+    /// Warn (or fail with `strict`) when paths differ only in case, since they
+    /// clash when the torrent is downloaded to a case-insensitive filesystem
+    fn check_case_collisions(&self, files: &[crate::models::FileInfo]) -> Result<()> {
+        let collisions = find_case_collisions(files);
+        if collisions.is_empty() {
+            return Ok(());
+        }
+
+        let groups: Vec<String> = collisions
+            .iter()
+            .map(|paths| {
+                paths
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+        let msg = format!(
+            "Paths that differ only in case will collide on case-insensitive filesystems: {}",
+            groups.join("; ")
+        );
+        if self.options.strict {
+            anyhow::bail!(msg);
+        }
+        warn!("{}", msg);
+        Ok(())
+    }
+
+    /// Warn about `.torrent` files in the content: they're usually left over from an
+    /// earlier run rather than meant to be shared
+    fn check_torrent_files(&self, files: &[crate::models::FileInfo]) {
+        if self.options.allow_torrent_files {
+            return;
+        }
+        let found: Vec<String> = files
+            .iter()
+            .filter(|f| {
+                f.path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("torrent"))
+            })
+            .map(|f| f.path.display().to_string())
+            .collect();
+        if found.is_empty() {
+            return;
+        }
+        warn!(
+            "The content includes .torrent file(s): {}. Exclude them with -e '*.torrent', \
+             or pass --allow-torrent-files if they're meant to be shared.",
+            found.join(", ")
+        );
+    }
+
     /// Calculate piece length considering tracker configurations
     fn calculate_piece_length_with_config(
         &self,
         total_size: u64,
         config: Option<&crate::trackers::TrackerConfig>,
     ) -> (u64, u32) {
+        let mut power = self.select_piece_length(total_size, config);
+        // An explicit --piece-length is taken as-is
+        if self.options.piece_length.is_none()
+            && let Some(min_pieces) = self.options.min_pieces
+        {
+            let lowered = apply_min_pieces(total_size, power, min_pieces);
+            if lowered != power {
+                info!(
+                    "Lowering piece length to 2^{} for at least {} pieces",
+                    lowered, min_pieces
+                );
+            }
+            power = lowered;
+        }
+        // Exponents are checked before building; this only keeps the shift below from
+        // overflowing when that check is bypassed
+        let clamped = self.clamp_piece_exp(power).min(MAX_PIECE_EXP);
+        if clamped != power {
+            warn!(
+                "Piece length 2^{} is outside the requested bounds. Clamping to 2^{}.",
+                power, clamped
+            );
+        }
+        (1u64 << clamped, clamped)
+    }
+
+    /// Apply the user's --min-piece-length/--max-piece-length bounds (max wins if they conflict)
+    fn clamp_piece_exp(&self, mut power: u32) -> u32 {
+        if let Some(min_exp) = self.options.min_piece_exp
+            && power < min_exp
+        {
+            power = min_exp;
+        }
+        if let Some(max_exp) = self.options.max_piece_exp
+            && power > max_exp
+        {
+            power = max_exp;
+        }
+        power
+    }
+
+    fn select_piece_length(
+        &self,
+        total_size: u64,
+        config: Option<&crate::trackers::TrackerConfig>,
+    ) -> u32 {
         // 1. User override
         if let Some(power) = self.options.piece_length {
             // Check max limit from config
-            if let Some(cfg) = config {
-                if let Some(max_exp) = cfg.max_piece_length {
-                    if power > max_exp {
-                        // Warn and cap
-                        if self.verbose {
-                            eprintln!(
-                                "Warning: Requested piece length 2^{} exceeds tracker limit 2^{}. Capping.",
-                                power, max_exp
-                            );
-                        }
-                        return (1u64 << max_exp, max_exp);
-                    }
-                }
+            if let Some(cfg) = config
+                && let Some(max_exp) = cfg.max_piece_length
+                && power > max_exp
+            {
+                // Warn and cap
+                warn!(
+                    "Requested piece length 2^{} exceeds tracker limit 2^{}. Capping.",
+                    power, max_exp
+                );
+                return max_exp;
             }
-            return (1u64 << power, power);
+            return power;
         }
 
         // 2. Config logic
         if let Some(cfg) = config {
-            // Check ranges
-            if !cfg.piece_size_ranges.is_empty() {
-                for range in cfg.piece_size_ranges {
-                    if total_size <= range.max_size {
-                        let mut power = range.piece_exp;
-                        // Enforce max limit
-                        if let Some(max_exp) = cfg.max_piece_length {
-                            if power > max_exp {
-                                power = max_exp;
-                            }
-                        }
-                        return (1u64 << power, power);
-                    }
-                }
-                // No range match
-                if !cfg.use_default_ranges {
-                    // Use largest defined
-                    let last = cfg.piece_size_ranges.last().unwrap();
-                    let mut power = last.piece_exp;
-                    if let Some(max_exp) = cfg.max_piece_length {
-                        if power > max_exp {
-                            power = max_exp;
-                        }
-                    }
-                    return (1u64 << power, power);
-                }
-            } else if let Some(max_exp) = cfg.max_piece_length {
-                // No ranges, but max limit. Use default calc but cap.
-                let power = calculate_piece_length(total_size);
-                let final_power = std::cmp::min(power, max_exp);
-                return (1u64 << final_power, final_power);
-            }
+            // The tightest range that fits, so a tiny file gets the smallest pieces
+            // even if a config lists its ranges out of order
+            let ranged = cfg
+                .piece_size_ranges
+                .iter()
+                .filter(|range| total_size <= range.max_size)
+                .min_by_key(|range| range.max_size);
+            let power = match ranged {
+                Some(range) => range.piece_exp,
+                // Too large for every range: the largest defined one, unless the
+                // tracker falls back to the default table (or has no ranges at all)
+                None => match cfg.piece_size_ranges.iter().max_by_key(|r| r.max_size) {
+                    Some(largest) if !cfg.use_default_ranges => largest.piece_exp,
+                    _ => calculate_piece_length(total_size),
+                },
+            };
+            // Enforce max limit
+            return match cfg.max_piece_length {
+                Some(max_exp) => power.min(max_exp),
+                None => power,
+            };
         }
 
         // 3. Default
-        let power = calculate_piece_length(total_size);
-        (1u64 << power, power)
+        calculate_piece_length(total_size)
     }
 
-    /// Perform a dry run (scan files, calculate piece size, but don't hash)
+    /// Scan files and calculate the piece size without hashing anything
+    pub fn dry_run_report(&self) -> Result<DryRunReport> {
+        let (files, total_size) = self.scan()?;
+
+        if files.is_empty() {
+            anyhow::bail!("No files found to create torrent from");
+        }
+
+        // Resolve tracker config
+        let tracker_config = self.resolve_tracker_config();
+        self.check_tracker_requirements(tracker_config)?;
+        self.check_public_trackers(tracker_config);
+        self.check_piece_exponents()?;
+        self.check_source_string()?;
+        self.options.check_meta_version()?;
+        self.check_case_collisions(&files)?;
+        self.check_torrent_files(&files);
+
+        // Calculate or use provided piece length
+        let (piece_length, power) =
+            self.calculate_piece_length_with_config(total_size, tracker_config);
+
+        Ok(DryRunReport {
+            files: files
+                .into_iter()
+                .map(|f| FileEntryView {
+                    path: f.path,
+                    length: f.len,
+                })
+                .collect(),
+            total_size,
+            piece_length,
+            piece_exp: power,
+            num_pieces: calculate_num_pieces(total_size, piece_length),
+            mode: self.options.mode,
+        })
+    }
+
+    /// Report the padding a hybrid build would insert, without hashing. Other modes,
+    /// single-file torrents and `--no-padding` have none.
+    pub fn padding_report(&self) -> Result<PaddingReport> {
+        let (files, total_size) = self.scan()?;
+        if files.is_empty() {
+            anyhow::bail!("No files found to create torrent from");
+        }
+
+        let tracker_config = self.resolve_tracker_config();
+        let (piece_length, _) = self.calculate_piece_length_with_config(total_size, tracker_config);
+        let files = if self.options.mode == Mode::Hybrid
+            && !self.options.no_padding
+            && !self.is_single_file(&files)
+        {
+            add_padding_files(files, piece_length)
+        } else {
+            files
+        };
+        Ok(PaddingReport::from_files(&files, piece_length))
+    }
+
+    /// Perform a dry run and print the results to stderr
     pub fn dry_run(&self) -> Result<()> {
         use console::{Emoji, style};
         use indicatif::HumanBytes;
@@ -150,30 +884,12 @@ impl TorrentBuilder {
         if self.verbose {
             eprintln!("torrite {} (Dry Run)", env!("CARGO_PKG_VERSION"));
             eprintln!();
-            self.print_configuration();
+            self.log_configuration();
         } else {
             eprintln!("{} {}", DRY_RUN, style("Dry run: scanning files...").bold());
         }
 
-        let (files, total_size) = scan_files(
-            &self.source,
-            self.output_file.as_deref(),
-            &self.options.exclude,
-            self.verbose,
-        )?;
-
-        if files.is_empty() {
-            anyhow::bail!("No files found to create torrent from");
-        }
-
-        // Resolve tracker config
-        let tracker_config = self.resolve_tracker_config();
-
-        // Calculate or use provided piece length
-        let (piece_length, power) =
-            self.calculate_piece_length_with_config(total_size, tracker_config);
-
-        let num_pieces = calculate_num_pieces(total_size, piece_length);
+        let report = self.dry_run_report()?;
 
         eprintln!();
         eprintln!(
@@ -184,17 +900,17 @@ impl TorrentBuilder {
         eprintln!(
             "{:<15} {}",
             style("Total Size:").bold(),
-            style(HumanBytes(total_size)).green()
+            style(HumanBytes(report.total_size)).green()
         );
-        eprintln!("{:<15} {}", style("File Count:").bold(), files.len());
+        eprintln!("{:<15} {}", style("File Count:").bold(), report.files.len());
         eprintln!(
             "{:<15} {} (2^{})",
             style("Piece Length:").bold(),
-            style(HumanBytes(piece_length)).yellow(),
-            power
+            style(HumanBytes(report.piece_length)).yellow(),
+            report.piece_exp
         );
-        eprintln!("{:<15} {}", style("Piece Count:").bold(), num_pieces);
-        eprintln!("{:<15} {:?}", style("Mode:").bold(), self.options.mode);
+        eprintln!("{:<15} {}", style("Piece Count:").bold(), report.num_pieces);
+        eprintln!("{:<15} {:?}", style("Mode:").bold(), report.mode);
 
         if self.verbose {
             eprintln!(
@@ -202,15 +918,15 @@ impl TorrentBuilder {
                 FILES,
                 style("Files that would be included:").bold()
             );
-            for file in files.iter().take(20) {
+            for file in report.files.iter().take(20) {
                 eprintln!(
                     "  - {:<40} {}",
                     file.path.display(),
-                    style(HumanBytes(file.len)).dim()
+                    style(HumanBytes(file.length)).dim()
                 );
             }
-            if files.len() > 20 {
-                eprintln!("  ... and {} more", style(files.len() - 20).dim());
+            if report.files.len() > 20 {
+                eprintln!("  ... and {} more", style(report.files.len() - 20).dim());
             }
         }
 
@@ -219,23 +935,43 @@ impl TorrentBuilder {
 
     /// Build the torrent metadata
     pub fn build(self) -> Result<Torrent> {
+        self.build_timed().map(|(torrent, _)| torrent)
+    }
+
+    /// Build the torrent and report how long each phase took
+    ///
+    /// Intended for in-process benchmarks; combine with [`with_threads`](Self::with_threads)
+    /// for a fixed hashing thread count.
+    pub fn build_timed(self) -> Result<(Torrent, BuildTimings)> {
+        let started = Instant::now();
+        let mut timings = BuildTimings::default();
+
+        // Through `log` like every other diagnostic, so a library caller without a
+        // logger sees nothing
         if self.verbose {
-            eprintln!("torrite {}", env!("CARGO_PKG_VERSION"));
-            eprintln!();
-            self.print_configuration();
+            info!("torrite {}", env!("CARGO_PKG_VERSION"));
+            info!("");
+            self.log_configuration();
         }
 
-        // Scan files
-        if self.verbose {
-            eprintln!("Scanning files...");
+        if self.options.from_url {
+            #[cfg(feature = "http")]
+            return self.build_from_url(started, timings);
+            #[cfg(not(feature = "http"))]
+            anyhow::bail!(
+                "This build of torrite was compiled without HTTP support; rebuild with `--features http`"
+            );
+        }
+        if let Some(length) = self.options.length {
+            return self.build_fixed_length(length, started, timings);
         }
 
-        let (files, total_size) = scan_files(
-            &self.source,
-            self.output_file.as_deref(),
-            &self.options.exclude,
-            self.verbose,
-        )?;
+        // Scan files
+        info!("Scanning files...");
+
+        let scan_started = Instant::now();
+        let (files, total_size) = self.scan()?;
+        timings.scan = scan_started.elapsed();
 
         if files.is_empty() {
             anyhow::bail!("No files found to create torrent from");
@@ -243,55 +979,216 @@ impl TorrentBuilder {
 
         // Resolve tracker config
         let tracker_config = self.resolve_tracker_config();
+        self.check_tracker_requirements(tracker_config)?;
+        self.check_public_trackers(tracker_config);
+        self.check_piece_exponents()?;
+        self.check_source_string()?;
+        self.options.check_meta_version()?;
+        self.check_case_collisions(&files)?;
+        self.check_torrent_files(&files);
 
         // Calculate or use provided piece length
         let (piece_length, power) =
             self.calculate_piece_length_with_config(total_size, tracker_config);
 
-        if self.verbose {
-            eprintln!("Using piece length: {} bytes (2^{})", piece_length, power);
-        }
+        info!("Using piece length: {} bytes (2^{})", piece_length, power);
 
         let num_pieces = calculate_num_pieces(total_size, piece_length);
-        if self.verbose {
-            eprintln!("Total size: {} bytes", total_size);
-            eprintln!("Number of pieces: {}", num_pieces);
-            eprintln!();
-            eprintln!("Using {} threads for hashing", self.num_threads);
-            eprintln!("Mode: {:?}", self.options.mode);
+        info!("Total size: {} bytes", total_size);
+        info!("Number of pieces: {}", num_pieces);
+        let threads = self.hashing_threads(piece_length);
+        if threads < self.num_threads {
+            info!(
+                "Using {} threads for hashing ({} requested) to stay within the memory limit",
+                threads, self.num_threads
+            );
+        } else {
+            info!("Using {} threads for hashing", threads);
         }
+        info!("Hash backend: {}", hash_backend());
+        info!("Mode: {:?}", self.options.mode);
+
+        let is_single_file = self.is_single_file(&files);
 
-        let is_single_file = self.source.is_file();
+        if self.options.store_mtime && (is_single_file || self.options.mode == Mode::V2) {
+            warn!(
+                "--store-mtime only applies to the file list of multi-file V1 and hybrid \
+                 torrents; no modification times were recorded"
+            );
+        }
 
         // Prepare files (inject padding if Hybrid)
         // V2-only does not use padding. V1 does not use padding (files are continuous).
         let files = if self.options.mode == Mode::Hybrid && !is_single_file {
-            add_padding_files(files, piece_length)
+            if self.options.no_padding {
+                warn!(
+                    "--no-padding: building a hybrid torrent without padding files. \
+                     V1 pieces will span file boundaries, which violates BEP 52 and may \
+                     make some clients reject the torrent or fail to seed it as V2."
+                );
+                files
+            } else {
+                add_padding_files(files, piece_length)
+            }
         } else {
             files
         };
 
         // Hashing
-        let (pieces_bytes, file_tree, piece_layers, meta_version) =
-            self.hash_content(&files, piece_length, is_single_file)?;
+        let hashed = self.hash_content(&files, piece_length, is_single_file, &mut timings)?;
 
-        if self.verbose {
-            eprintln!("Building torrent file...");
-        }
+        info!("Building torrent file...");
 
         // Build the torrent
-        let torrent = self.build_torrent(
-            &files,
-            total_size,
-            piece_length,
-            is_single_file,
-            pieces_bytes,
+        let torrent =
+            self.build_torrent(&files, total_size, piece_length, is_single_file, hashed)?;
+
+        timings.total = started.elapsed();
+        Ok((torrent, timings))
+    }
+
+    /// Hash the file behind a URL in one pass as it downloads and seed it from there
+    #[cfg(feature = "http")]
+    fn build_from_url(
+        &self,
+        started: Instant,
+        timings: BuildTimings,
+    ) -> Result<(Torrent, BuildTimings)> {
+        let url = self.source.to_string_lossy().into_owned();
+        info!("Fetching {}...", url);
+        let (body, total_size) = crate::remote::open_url(&url)?;
+        if total_size == 0 {
+            anyhow::bail!("{} is empty", url);
+        }
+
+        let (mut torrent, timings) = self
+            .build_streamed(body, total_size, started, timings)
+            .with_context(|| format!("Failed to download {}", url))?;
+        let web_seeds = torrent.url_list.get_or_insert_with(Vec::new);
+        if !web_seeds.contains(&url) {
+            web_seeds.push(url);
+        }
+        Ok((torrent, timings))
+    }
+
+    /// Hash exactly `--length` bytes of the source in one pass, for block devices
+    /// and other files whose reported size can't be trusted
+    fn build_fixed_length(
+        &self,
+        length: u64,
+        started: Instant,
+        timings: BuildTimings,
+    ) -> Result<(Torrent, BuildTimings)> {
+        if length == 0 {
+            anyhow::bail!("--length must be greater than 0");
+        }
+        let file = std::fs::File::open(&self.source)
+            .with_context(|| format!("Failed to open {}", self.source.display()))?;
+        let metadata = file.metadata().context("Failed to read source metadata")?;
+        if metadata.is_dir() {
+            anyhow::bail!("--length needs a single file or device as the source, not a directory");
+        }
+        // Devices report a length of 0, so only a regular file can be checked up front
+        if metadata.is_file() && metadata.len() < length {
+            anyhow::bail!(
+                "{} is only {} bytes, less than --length {}",
+                self.source.display(),
+                metadata.len(),
+                length
+            );
+        }
+
+        info!("Reading {} bytes of {}", length, self.source.display());
+        let reader = std::io::BufReader::new(file).take(length);
+        self.build_streamed(reader, length, started, timings)
+            .with_context(|| format!("Failed to read {}", self.source.display()))
+    }
+
+    /// Build a single-file torrent from `total_size` bytes read front to back
+    fn build_streamed(
+        &self,
+        reader: impl std::io::Read,
+        total_size: u64,
+        started: Instant,
+        mut timings: BuildTimings,
+    ) -> Result<(Torrent, BuildTimings)> {
+        let tracker_config = self.resolve_tracker_config();
+        self.check_tracker_requirements(tracker_config)?;
+        self.check_public_trackers(tracker_config);
+        self.check_piece_exponents()?;
+        self.check_source_string()?;
+        self.options.check_meta_version()?;
+
+        let (piece_length, power) =
+            self.calculate_piece_length_with_config(total_size, tracker_config);
+        info!("Using piece length: {} bytes (2^{})", piece_length, power);
+        info!("Total size: {} bytes", total_size);
+        info!("Mode: {:?}", self.options.mode);
+
+        // V1 and V2 share a single read of the body
+        let hash_v1 = self.options.mode != Mode::V2;
+        let hash_v2 = self.options.mode != Mode::V1;
+        let pb = self.progress_bar(total_size)?;
+        if let Some(p) = &pb {
+            p.set_message("Reading and hashing...");
+        }
+        let hash_started = Instant::now();
+        let mut hasher = self.stream_hasher(piece_length, pb.clone());
+        let file = hasher.add_file(reader)?;
+        let streamed = crate::hashing::StreamHashResult {
+            length: file.length,
+            pieces: hasher.finish(),
+            pieces_root: file.pieces_root,
+            piece_layer: file.piece_layer,
+        };
+        if hash_v1 {
+            timings.hash_v1 = hash_started.elapsed();
+        } else {
+            timings.hash_v2 = hash_started.elapsed();
+        }
+        timings.bytes_hashed = streamed.length;
+        if streamed.length != total_size {
+            anyhow::bail!(
+                "Input ended after {} of {} bytes",
+                streamed.length,
+                total_size
+            );
+        }
+        if let Some(p) = pb {
+            p.finish_with_message("Hashing complete");
+        }
+
+        let (file_tree, piece_layers) = match streamed.pieces_root {
+            Some(root) => {
+                let root = ByteBuf::from(root.to_vec());
+                let node = Node::File(crate::models::FileNode {
+                    metadata: crate::models::FileMetadata {
+                        length: total_size,
+                        pieces_root: root.clone(),
+                    },
+                });
+                let layers = streamed
+                    .piece_layer
+                    .map(|layer| (root, ByteBuf::from(layer)))
+                    .into_iter()
+                    .collect();
+                (
+                    Some(BTreeMap::from([(self.default_name(), node)])),
+                    Some(layers),
+                )
+            }
+            None => (None, None),
+        };
+        let hashed = HashedContent {
+            pieces_bytes: streamed.pieces,
             file_tree,
             piece_layers,
-            meta_version,
-        )?;
+            meta_version: hash_v2.then_some(2),
+        };
 
-        Ok(torrent)
+        let torrent = self.build_torrent(&[], total_size, piece_length, true, hashed)?;
+        timings.total = started.elapsed();
+        Ok((torrent, timings))
     }
 
     fn hash_content(
@@ -299,102 +1196,271 @@ impl TorrentBuilder {
         files: &[crate::models::FileInfo],
         piece_length: u64,
         is_single_file: bool,
-    ) -> Result<(
-        Vec<u8>,
-        Option<std::collections::BTreeMap<String, crate::models::Node>>,
-        Option<std::collections::BTreeMap<serde_bytes::ByteBuf, serde_bytes::ByteBuf>>,
-        Option<u8>,
-    )> {
+        timings: &mut BuildTimings,
+    ) -> Result<HashedContent> {
+        #[cfg(feature = "archive")]
+        if self.options.from_archive && crate::archive::is_compressed(&self.source)? {
+            return self.hash_compressed_archive(files, piece_length, timings);
+        }
+
         let total_size: u64 = files.iter().map(|f| f.len).sum();
 
         // Create thread pool once and use it for all hashing
         let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(self.num_threads)
+            .num_threads(self.hashing_threads(piece_length))
             .build()
-            .unwrap();
+            .context("Failed to start hashing thread pool")?;
+
+        // One bar for both passes so a hybrid build doesn't appear to finish twice.
+        // V1 reads padding as zeros; V2 skips it.
+        let hash_v1 = self.options.mode != Mode::V2;
+        let hash_v2 = self.options.mode != Mode::V1;
+        let v1_bytes = if hash_v1 { total_size } else { 0 };
+        let v2_bytes = if hash_v2 {
+            files.iter().filter(|f| !f.is_padding).map(|f| f.len).sum()
+        } else {
+            0
+        };
+        // A hidden bar still counts bytes for the timings
+        let pb = Some(
+            self.progress_bar(v1_bytes + v2_bytes)?
+                .unwrap_or_else(ProgressBar::hidden),
+        );
+        let start_position = pb.as_ref().map_or(0, ProgressBar::position);
+        let (v1_message, v2_message) = if hash_v1 && hash_v2 {
+            ("Hashing V1 (1/2)...", "Hashing V2 (2/2)...")
+        } else {
+            ("Hashing V1...", "Hashing V2...")
+        };
 
-        pool.install(|| {
+        let hashed = pool.install(|| -> Result<HashedContent> {
             // V1 HASHING
-            let pieces_bytes = if self.options.mode != Mode::V2 {
-                let pb = if self.show_progress {
-                    let pb = ProgressBar::new(total_size);
-                    pb.set_draw_target(ProgressDrawTarget::stderr_with_hz(10));
-                    pb.set_style(ProgressStyle::with_template(
-                        "{spinner:.green} [{elapsed_precise}] {bar:40.202/94} {bytes}/{total_bytes} ({eta}) {msg}"
-                    )?
-                    .progress_chars("█▓▒░"));
-                    pb.set_message("Hashing V1...");
-                    Some(pb)
-                } else {
-                    None
-                };
-
-                let res = hash_v1_pieces(files, piece_length, self.verbose, pb.clone())?;
-                if let Some(p) = pb {
-                    p.finish_with_message("V1 Hashing complete");
+            let pieces_bytes = if hash_v1 {
+                let started = Instant::now();
+                if let Some(p) = &pb {
+                    p.set_message(v1_message);
                 }
+                let res = match &self.cancel {
+                    Some(cancel) => {
+                        let hashes =
+                            hash_v1_pieces_cancellable(files, piece_length, pb.clone(), cancel)?;
+                        if cancel.is_cancelled() {
+                            return Err(HashError::Cancelled.into());
+                        }
+                        hashes.into_iter().flatten().flatten().collect()
+                    }
+                    None => hash_v1_pieces(files, piece_length, pb.clone())?,
+                };
+                timings.hash_v1 = started.elapsed();
                 res
             } else {
                 Vec::new()
             };
 
             // V2 HASHING
-            let (file_tree, piece_layers, meta_version) =
-                if self.options.mode == Mode::V2 || self.options.mode == Mode::Hybrid {
-                    let pb = if self.show_progress {
-                        let pb = ProgressBar::new(total_size);
-                        pb.set_draw_target(ProgressDrawTarget::stderr_with_hz(10));
-                        pb.set_style(ProgressStyle::with_template(
-                            "{spinner:.green} [{elapsed_precise}] {bar:40.202/94} {bytes}/{total_bytes} ({eta}) {msg}"
-                        )?
-                        .progress_chars("█▓▒░"));
-                        pb.set_message("Hashing V2...");
-                        Some(pb)
-                    } else {
-                        None
-                    };
-
-                    let result = hash_v2_files(
-                        files,
-                        piece_length,
-                        self.verbose,
-                        is_single_file,
-                        pb.clone(),
-                    )?;
-                    if let Some(p) = pb {
-                        p.finish_with_message("V2 Hashing complete");
-                    }
-                    (Some(result.file_tree), Some(result.piece_layers), Some(2))
-                } else {
-                    (None, None, None)
-                };
+            let (file_tree, piece_layers, meta_version) = if hash_v2 {
+                let started = Instant::now();
+                if let Some(p) = &pb {
+                    p.set_message(v2_message);
+                }
 
-            Ok((pieces_bytes, file_tree, piece_layers, meta_version))
+                let mut cache = self.hash_cache.as_deref().map(|path| {
+                    let cache = HashCache::load(path);
+                    debug!(
+                        "Loaded {} cached file(s) from {}",
+                        cache.len(),
+                        path.display()
+                    );
+                    cache
+                });
+
+                let chunk_size = self
+                    .v2_chunk_blocks
+                    .map_or(ChunkSize::Auto(self.single_file_chunks), ChunkSize::Blocks);
+                let result = crate::hashing::hash_v2(
+                    files,
+                    piece_length,
+                    is_single_file,
+                    pb.clone(),
+                    cache.as_mut(),
+                    self.cancel.as_ref(),
+                    chunk_size,
+                )?;
+
+                // The cache only saves work next time, so failing to write it isn't fatal
+                if let (Some(cache), Some(path)) = (&cache, &self.hash_cache)
+                    && let Err(e) = cache.save(path, self.cache_compress)
+                {
+                    warn!("{:#}; the next run will hash these files again", e);
+                }
+                timings.hash_v2 = started.elapsed();
+                (Some(result.file_tree), Some(result.piece_layers), Some(2))
+            } else {
+                (None, None, None)
+            };
+
+            Ok(HashedContent {
+                pieces_bytes,
+                file_tree,
+                piece_layers,
+                meta_version,
+            })
+        })?;
+
+        if let Some(p) = pb {
+            timings.bytes_hashed = p.position() - start_position;
+            p.finish_with_message("Hashing complete");
+        }
+        Ok(hashed)
+    }
+
+    /// Hash the entries of a compressed archive in a single pass, decompressing
+    /// each one as it's read. V1 and V2 share the read; the hash cache isn't used.
+    #[cfg(feature = "archive")]
+    fn hash_compressed_archive(
+        &self,
+        files: &[crate::models::FileInfo],
+        piece_length: u64,
+        timings: &mut BuildTimings,
+    ) -> Result<HashedContent> {
+        let hash_v1 = self.options.mode != Mode::V2;
+        let hash_v2 = self.options.mode != Mode::V1;
+        let total_size: u64 = files.iter().map(|f| f.len).sum();
+        let pb = self.progress_bar(total_size)?;
+        if let Some(p) = &pb {
+            p.set_message("Decompressing and hashing...");
+        }
+
+        let started = Instant::now();
+        let mut hasher = self.stream_hasher(piece_length, pb.clone());
+        let mut file_tree = BTreeMap::new();
+        let mut piece_layers = BTreeMap::new();
+        crate::archive::read_entries(&self.source, files, |file, reader| {
+            if file.is_padding {
+                hasher.add_padding(file.len);
+                return Ok(());
+            }
+            let streamed = hasher.add_file(reader)?;
+            if streamed.length != file.len {
+                anyhow::bail!(
+                    "{} decompressed to {} bytes, but the archive lists {}",
+                    file.path.display(),
+                    streamed.length,
+                    file.len
+                );
+            }
+            if let Some(root) = streamed.pieces_root {
+                if let Some(layer) = streamed.piece_layer {
+                    piece_layers.insert(ByteBuf::from(root.to_vec()), ByteBuf::from(layer));
+                }
+                crate::tree::insert_into_tree(&mut file_tree, &file.path, file.len, root.to_vec());
+            }
+            Ok(())
+        })?;
+        if hash_v1 {
+            timings.hash_v1 = started.elapsed();
+        } else {
+            timings.hash_v2 = started.elapsed();
+        }
+        timings.bytes_hashed = total_size;
+        if let Some(p) = pb {
+            p.finish_with_message("Hashing complete");
+        }
+
+        Ok(HashedContent {
+            pieces_bytes: hasher.finish(),
+            file_tree: hash_v2.then_some(file_tree),
+            piece_layers: hash_v2.then_some(piece_layers),
+            meta_version: hash_v2.then_some(2),
         })
     }
 
+    /// A hasher for sources read front to back, honouring the mode and cancellation
+    fn stream_hasher(
+        &self,
+        piece_length: u64,
+        pb: Option<ProgressBar>,
+    ) -> crate::hashing::StreamHasher {
+        let hasher = crate::hashing::StreamHasher::new(
+            piece_length,
+            self.options.mode != Mode::V2,
+            self.options.mode != Mode::V1,
+            pb,
+        );
+        match &self.cancel {
+            Some(cancel) => hasher.with_cancellation(cancel.clone()),
+            None => hasher,
+        }
+    }
+
+    /// The bar hashing reports to: the caller's, a fresh one on stderr, or none
+    fn progress_bar(&self, total: u64) -> Result<Option<ProgressBar>> {
+        if let Some(pb) = &self.progress_bar {
+            pb.set_length(total);
+            return Ok(Some(pb.clone()));
+        }
+        if !self.show_progress {
+            return Ok(None);
+        }
+        let pb = ProgressBar::new(total);
+        pb.set_draw_target(ProgressDrawTarget::stderr_with_hz(10));
+        pb.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} [{elapsed_precise}] {bar:40.202/94} {bytes}/{total_bytes} {bytes_per_sec} ({eta}) {msg}",
+            )?
+            .progress_chars("█▓▒░"),
+        );
+        Ok(Some(pb))
+    }
+
     fn build_torrent(
         &self,
         files: &[crate::models::FileInfo],
         total_size: u64,
         piece_length: u64,
         is_single_file: bool,
-        pieces_bytes: Vec<u8>,
-        file_tree: Option<std::collections::BTreeMap<String, crate::models::Node>>,
-        piece_layers: Option<
-            std::collections::BTreeMap<serde_bytes::ByteBuf, serde_bytes::ByteBuf>,
-        >,
-        meta_version: Option<u8>,
+        hashed: HashedContent,
     ) -> Result<Torrent> {
-        // Determine torrent name
-        let torrent_name = self.options.name.clone().unwrap_or_else(|| {
-            self.source
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("output")
-                .to_string()
+        let HashedContent {
+            pieces_bytes,
+            file_tree,
+            piece_layers,
+            meta_version,
+        } = hashed;
+
+        // Determine torrent name; surrounding whitespace in --name is never intended
+        let torrent_name = match &self.options.name {
+            Some(name) => name.trim().to_string(),
+            // A flattened directory is named after its file
+            None if is_single_file && self.source.is_dir() => {
+                files[0].path.to_string_lossy().into_owned()
+            }
+            None => self.torrent_name()?,
+        };
+        validate_torrent_name(&torrent_name)?;
+
+        let meta_version = meta_version.map(|standard| match self.options.meta_version {
+            Some(version) => {
+                warn!(
+                    "--meta-version: writing nonstandard meta version {}; clients may reject this torrent",
+                    version
+                );
+                version
+            }
+            None => standard,
         });
 
+        // The tree is keyed by the file's own name; a custom name replaces it
+        let file_tree = if is_single_file {
+            file_tree.map(|tree| {
+                tree.into_values()
+                    .map(|node| (torrent_name.clone(), node))
+                    .collect()
+            })
+        } else {
+            file_tree
+        };
+
         // Determine files/length fields
         let (files_section, length_section) = if self.options.mode == Mode::V2 {
             // V2 (single or multi) does not use 'files' or 'length' in info dict (uses file tree)
@@ -421,6 +1487,11 @@ impl TorrentBuilder {
                         } else {
                             None
                         },
+                        mtime: if self.options.store_mtime && !f.is_padding {
+                            file_mtime(&f.full_path)
+                        } else {
+                            None
+                        },
                     }
                 })
                 .collect();
@@ -447,7 +1518,11 @@ impl TorrentBuilder {
             piece_length,
             pieces: pieces_section,
             name: torrent_name.clone(),
-            private: if self.options.private { Some(1) } else { None },
+            private: if self.is_private(tracker_config) {
+                Some(1)
+            } else {
+                None
+            },
             files: files_section,
             length: length_section,
             source: source_string,
@@ -458,6 +1533,23 @@ impl TorrentBuilder {
             },
             meta_version,
             file_tree,
+            similar: if self.options.similar.is_empty() {
+                None
+            } else {
+                Some(
+                    self.options
+                        .similar
+                        .iter()
+                        .cloned()
+                        .map(ByteBuf::from)
+                        .collect(),
+                )
+            },
+            collections: if self.options.collections.is_empty() {
+                None
+            } else {
+                Some(self.options.collections.clone())
+            },
         };
 
         // Build announce-list if multiple trackers are provided
@@ -477,6 +1569,15 @@ impl TorrentBuilder {
                 }
             }
 
+            if self.options.announce_tier_shuffle {
+                let mut rng = if self.options.deterministic {
+                    StdRng::seed_from_u64(DETERMINISTIC_SEED)
+                } else {
+                    StdRng::from_os_rng()
+                };
+                shuffle_tiers(&mut list, &mut rng);
+            }
+
             if list.is_empty() {
                 (None, None)
             } else {
@@ -485,7 +1586,7 @@ impl TorrentBuilder {
                 // If we have exactly one tier with one URL, we don't strictly need announce-list
                 let single_tracker = list.len() == 1 && list[0].len() == 1;
 
-                if single_tracker {
+                if single_tracker || self.options.no_announce_list {
                     (Some(first_announce), None)
                 } else {
                     (Some(first_announce), Some(list))
@@ -493,6 +1594,13 @@ impl TorrentBuilder {
             }
         };
 
+        // Both info hashes are only valid if the two layouts agree
+        if (self.options.force_v1_name_compat || cfg!(debug_assertions))
+            && let Some(problem) = hybrid_layout_mismatch(&info)
+        {
+            anyhow::bail!("Hybrid V1 and V2 file layouts diverge: {}", problem);
+        }
+
         // Get creation date
         let creation_date = if self.options.no_date {
             None
@@ -510,7 +1618,11 @@ impl TorrentBuilder {
             announce,
             announce_list,
             comment: self.options.comment.clone(),
-            created_by: format!("torrite {}", env!("CARGO_PKG_VERSION")),
+            created_by: self
+                .options
+                .created_by
+                .clone()
+                .unwrap_or_else(|| format!("torrite {}", env!("CARGO_PKG_VERSION"))),
             creation_date,
             info,
             url_list: if self.options.web_seed.is_empty() {
@@ -519,38 +1631,41 @@ impl TorrentBuilder {
                 Some(self.options.web_seed.clone())
             },
             piece_layers,
+            publisher: self.options.publisher.clone(),
+            publisher_url: self.options.publisher_url.clone(),
         };
 
         Ok(torrent)
     }
 
-    fn print_configuration(&self) {
-        eprintln!("Configuration:");
-        eprintln!("  Source: {}", self.source.display());
+    /// Log the build settings, for `--verbose`
+    fn log_configuration(&self) {
+        info!("Configuration:");
+        info!("  Source: {}", self.source.display());
         if let Some(ref output) = self.output_file {
-            eprintln!("  Output: {}", output.display());
+            info!("  Output: {}", output.display());
         }
         if let Some(ref name) = self.options.name {
-            eprintln!("  Name: {}", name);
+            info!("  Name: {}", name);
         }
         if !self.options.announce.is_empty() {
-            eprintln!("  Announce URLs:");
+            info!("  Announce URLs:");
             for (i, url) in self.options.announce.iter().enumerate() {
-                eprintln!("    {}: {}", i + 1, url);
+                info!("    {}: {}", i + 1, url);
             }
         }
         if let Some(ref comment) = self.options.comment {
-            eprintln!("  Comment: {}", comment);
+            info!("  Comment: {}", comment);
         }
-        eprintln!("  Private: {}", self.options.private);
-        eprintln!("  No date: {}", self.options.no_date);
+        info!("  Private: {}", self.options.private);
+        info!("  No date: {}", self.options.no_date);
         if let Some(ref source) = self.options.source_string {
-            eprintln!("  Source: {}", source);
+            info!("  Source: {}", source);
         }
         if self.options.cross_seed {
-            eprintln!("  Cross-seed: enabled");
+            info!("  Cross-seed: enabled");
         }
-        eprintln!();
+        info!("");
     }
 }
 
@@ -560,10 +1675,373 @@ mod tests {
     use crate::models::TorrentOptions;
     use std::path::PathBuf;
 
+    #[test]
+    fn test_with_threads_auto_and_cap() {
+        let builder = TorrentBuilder::new(PathBuf::from("."), TorrentOptions::default());
+        assert_eq!(builder.with_threads(0).num_threads, num_cpus::get());
+
+        let builder = TorrentBuilder::new(PathBuf::from("."), TorrentOptions::default());
+        assert_eq!(builder.with_threads(1_000_000).num_threads, MAX_THREADS);
+
+        let builder = TorrentBuilder::new(PathBuf::from("."), TorrentOptions::default());
+        assert_eq!(builder.with_threads(3).num_threads, 3);
+    }
+
+    #[test]
+    fn test_max_memory_limits_hashing_threads() {
+        let builder = TorrentBuilder::new(PathBuf::from("."), TorrentOptions::default())
+            .with_threads(16)
+            .with_max_memory(64 << 20);
+        assert_eq!(builder.hashing_threads(1 << 20), 16);
+        assert_eq!(builder.hashing_threads(16 << 20), 4);
+        // A single piece larger than the cap still gets one thread
+        assert_eq!(builder.hashing_threads(128 << 20), 1);
+    }
+
+    #[test]
+    fn test_unwritable_hash_cache_still_builds() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("data.bin");
+        std::fs::write(&source, b"hash cache").unwrap();
+        let options = TorrentOptions {
+            mode: Mode::V2,
+            ..Default::default()
+        };
+
+        let cache = dir.path().join("missing").join("cache.bin");
+        let torrent = TorrentBuilder::new(source, options)
+            .with_hash_cache(cache.clone(), false)
+            .build()
+            .unwrap();
+        assert!(torrent.info.file_tree.is_some());
+        assert!(!cache.exists());
+    }
+
+    #[test]
+    fn test_strip_root_name_reuses_the_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let release = dir.path().join("Release");
+        std::fs::create_dir(&release).unwrap();
+        std::fs::write(release.join("movie.mkv"), b"movie").unwrap();
+        let options = TorrentOptions {
+            strip_root: true,
+            ..Default::default()
+        };
+
+        let builder = TorrentBuilder::new(dir.path().to_path_buf(), options)
+            .with_output_file(dir.path().join("Release.torrent"));
+        assert_eq!(builder.torrent_name().unwrap(), "Release");
+        builder.dry_run_report().unwrap();
+        builder.padding_report().unwrap();
+        assert_eq!(builder.walked.get().unwrap().walk_count(), 1);
+    }
+
+    #[test]
+    fn test_meta_version_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("data.bin");
+        std::fs::write(&source, b"meta version").unwrap();
+        let mut options = TorrentOptions {
+            mode: Mode::Hybrid,
+            meta_version: Some(3),
+            ..Default::default()
+        };
+
+        let torrent = TorrentBuilder::new(source.clone(), options.clone())
+            .build()
+            .unwrap();
+        assert_eq!(torrent.info.meta_version, Some(3));
+
+        // V1 torrents have no meta version to override
+        options.mode = Mode::V1;
+        let err = TorrentBuilder::new(source, options).build().unwrap_err();
+        assert!(err.to_string().contains("--meta-version"), "{err:#}");
+    }
+
+    #[test]
+    fn test_no_announce_list_keeps_first_tracker() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("data.bin");
+        std::fs::write(&source, b"announce list").unwrap();
+        let mut options = TorrentOptions {
+            announce: vec![
+                "http://a.example/announce,http://b.example/announce".to_string(),
+                "http://c.example/announce".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let torrent = TorrentBuilder::new(source.clone(), options.clone())
+            .build()
+            .unwrap();
+        assert_eq!(torrent.announce_list.as_ref().map(Vec::len), Some(2));
+
+        options.no_announce_list = true;
+        let torrent = TorrentBuilder::new(source, options).build().unwrap();
+        assert!(torrent.announce_list.is_none());
+        assert_eq!(
+            torrent.announce.as_deref(),
+            Some("http://a.example/announce")
+        );
+    }
+
+    #[test]
+    fn test_padding_report_matches_padding_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("content");
+        std::fs::create_dir(&source).unwrap();
+        for (name, len) in [("a.bin", 20_000), ("b.bin", 16_384), ("c.bin", 5_000)] {
+            std::fs::write(source.join(name), vec![1u8; len]).unwrap();
+        }
+        std::fs::write(source.join("d.bin"), vec![1u8; 100]).unwrap();
+        let options = TorrentOptions {
+            mode: Mode::Hybrid,
+            piece_length: Some(14),
+            ..Default::default()
+        };
+        let builder = TorrentBuilder::new(source, options);
+
+        let report = builder.padding_report().unwrap();
+        let (files, _) = builder.scan().unwrap();
+        let padded = add_padding_files(files, 1 << 14);
+        let padding: u64 = padded.iter().filter(|f| f.is_padding).map(|f| f.len).sum();
+        assert_eq!(report.padding_size, padding);
+        assert_eq!(report.padding_size, 12_768 + 11_384);
+        assert_eq!(report.content_size, 41_484);
+        assert_eq!(
+            report
+                .files
+                .iter()
+                .map(|f| (f.path.to_str().unwrap(), f.padding))
+                .collect::<Vec<_>>(),
+            vec![("a.bin", 12_768), ("c.bin", 11_384)]
+        );
+        assert!((report.overhead_percent - 24_152.0 * 100.0 / 41_484.0).abs() < 1e-9);
+
+        // The built torrent carries exactly that much padding
+        let torrent = builder.build().unwrap();
+        let built: u64 = torrent
+            .info
+            .files
+            .unwrap()
+            .iter()
+            .filter(|f| f.attr.as_deref() == Some("p"))
+            .map(|f| f.length)
+            .sum();
+        assert_eq!(built, report.padding_size);
+
+        let options = TorrentOptions {
+            piece_length: Some(14),
+            ..Default::default()
+        };
+        let v1 = TorrentBuilder::new(dir.path().join("content"), options);
+        assert_eq!(v1.padding_report().unwrap().padding_size, 0);
+    }
+
+    #[test]
+    fn test_announce_tier_shuffle_keeps_tiers() {
+        let tiers: Vec<Vec<String>> = vec![
+            (0..8)
+                .map(|i| format!("http://t{}.example/announce", i))
+                .collect(),
+            vec!["http://backup.example/announce".to_string()],
+        ];
+        let shuffled = |seed| {
+            let mut list = tiers.clone();
+            shuffle_tiers(&mut list, &mut StdRng::seed_from_u64(seed));
+            list
+        };
+
+        let once = shuffled(7);
+        assert_eq!(once, shuffled(7));
+        for (tier, original) in once.iter().zip(&tiers) {
+            let mut sorted = tier.clone();
+            sorted.sort();
+            assert_eq!(&sorted, original);
+        }
+        // Eight URLs have 40320 orders; two seeds landing on the same one is unlikely
+        assert!((0..4).any(|seed| shuffled(seed) != once));
+    }
+
+    #[test]
+    fn test_deterministic_tier_shuffle_is_stable() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("data.bin");
+        std::fs::write(&source, b"shuffled trackers").unwrap();
+        let urls: Vec<String> = (0..8)
+            .map(|i| format!("http://t{}.example/announce", i))
+            .collect();
+        let options = TorrentOptions {
+            announce: vec![urls.join(","), "http://backup.example/announce".to_string()],
+            announce_tier_shuffle: true,
+            deterministic: true,
+            ..Default::default()
+        };
+
+        let build = || {
+            TorrentBuilder::new(source.clone(), options.clone())
+                .build()
+                .unwrap()
+        };
+        let torrent = build();
+        let list = torrent.announce_list.clone().unwrap();
+        assert_eq!(Some(list.clone()), build().announce_list);
+        assert_eq!(torrent.announce.as_ref(), Some(&list[0][0]));
+        let mut first_tier = list[0].clone();
+        first_tier.sort();
+        assert_eq!(first_tier, urls);
+        assert_eq!(list[1], ["http://backup.example/announce"]);
+    }
+
+    #[test]
+    fn test_check_written_torrent_detects_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("data.bin");
+        std::fs::write(&source, vec![7u8; 70_000]).unwrap();
+        let options = TorrentOptions {
+            mode: Mode::Hybrid,
+            ..Default::default()
+        };
+        let torrent = TorrentBuilder::new(source, options).build().unwrap();
+
+        let path = dir.path().join("data.torrent");
+        let data = serde_bencode::to_bytes(&torrent).unwrap();
+        std::fs::write(&path, &data).unwrap();
+        check_written_torrent(&path, &torrent).unwrap();
+
+        // Fault injection: flip a byte inside the piece hashes, as bad storage might
+        let pieces = torrent.info.pieces.as_ref().unwrap();
+        let offset = data
+            .windows(pieces.len())
+            .position(|window| window == pieces.as_slice())
+            .unwrap();
+        let mut corrupt = data.clone();
+        corrupt[offset] ^= 0xFF;
+        std::fs::write(&path, &corrupt).unwrap();
+        let err = check_written_torrent(&path, &torrent).unwrap_err();
+        assert!(err.to_string().contains("V1 info hash"));
+
+        std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+        assert!(check_written_torrent(&path, &torrent).is_err());
+    }
+
+    /// Flips one byte as it passes through, like a fault between writer and disk
+    struct CorruptingWriter<W> {
+        inner: W,
+        offset: usize,
+        written: usize,
+    }
+
+    impl<W: std::io::Write> std::io::Write for CorruptingWriter<W> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let mut buf = buf.to_vec();
+            if let Some(byte) = self
+                .offset
+                .checked_sub(self.written)
+                .and_then(|i| buf.get_mut(i))
+            {
+                *byte ^= 0xFF;
+            }
+            let n = self.inner.write(&buf)?;
+            self.written += n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    #[test]
+    fn test_single_file_chunks_per_thread_keeps_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("data.bin");
+        std::fs::write(&source, vec![3u8; 300_000]).unwrap();
+        let options = TorrentOptions {
+            mode: Mode::V2,
+            ..Default::default()
+        };
+        let build = |chunks| {
+            TorrentBuilder::new(source.clone(), options.clone())
+                .with_threads(4)
+                .with_single_file_chunks_per_thread(chunks)
+                .build()
+                .unwrap()
+                .info_hash_v2()
+        };
+        assert_eq!(build(1), build(SINGLE_FILE_CHUNKS_PER_THREAD));
+        assert_eq!(build(0), build(64));
+    }
+
+    #[test]
+    fn test_cancelled_build_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("data.bin");
+        std::fs::write(&source, vec![7u8; 70_000]).unwrap();
+
+        for mode in [Mode::V1, Mode::V2, Mode::Hybrid] {
+            let cancel = Cancellation::new();
+            cancel.cancel();
+            let options = TorrentOptions {
+                mode,
+                ..Default::default()
+            };
+            let err = TorrentBuilder::new(source.clone(), options)
+                .with_cancellation(cancel)
+                .build()
+                .unwrap_err();
+            assert_eq!(err.downcast_ref::<HashError>(), Some(&HashError::Cancelled));
+        }
+
+        // Streamed sources stop too
+        let cancel = Cancellation::new();
+        cancel.cancel();
+        let options = TorrentOptions {
+            length: Some(1000),
+            ..Default::default()
+        };
+        let err = TorrentBuilder::new(source, options)
+            .with_cancellation(cancel)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<HashError>(), Some(&HashError::Cancelled));
+    }
+
+    #[test]
+    fn test_check_written_torrent_catches_write_faults() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("data.bin");
+        std::fs::write(&source, vec![7u8; 70_000]).unwrap();
+        let options = TorrentOptions {
+            mode: Mode::Hybrid,
+            ..Default::default()
+        };
+        let torrent = TorrentBuilder::new(source, options).build().unwrap();
+        let data = serde_bencode::to_bytes(&torrent).unwrap();
+        let pieces = torrent.info.pieces.as_ref().unwrap();
+        let offset = data
+            .windows(pieces.len())
+            .position(|window| window == pieces.as_slice())
+            .unwrap();
+
+        let path = dir.path().join("data.torrent");
+        torrent
+            .write_to(CorruptingWriter {
+                inner: std::fs::File::create(&path).unwrap(),
+                offset,
+                written: 0,
+            })
+            .unwrap();
+        let err = check_written_torrent(&path, &torrent).unwrap_err();
+        assert!(err.to_string().contains("info hash"), "{err:#}");
+    }
+
     #[test]
     fn test_tracker_defaults_anthelion() {
-        let mut options = TorrentOptions::default();
-        options.announce = vec!["https://anthelion.me/announce".to_string()];
+        let options = TorrentOptions {
+            announce: vec!["https://anthelion.me/announce".to_string()],
+            ..Default::default()
+        };
 
         let builder = TorrentBuilder::new(PathBuf::from("."), options);
         let config = builder.resolve_tracker_config().unwrap();
@@ -573,8 +2051,10 @@ mod tests {
 
     #[test]
     fn test_tracker_defaults_ptp() {
-        let mut options = TorrentOptions::default();
-        options.announce = vec!["https://passthepopcorn.me/announce".to_string()];
+        let options = TorrentOptions {
+            announce: vec!["https://passthepopcorn.me/announce".to_string()],
+            ..Default::default()
+        };
 
         let builder = TorrentBuilder::new(PathBuf::from("."), options);
         let config = builder.resolve_tracker_config().unwrap();
@@ -598,8 +2078,10 @@ mod tests {
 
     #[test]
     fn test_tracker_defaults_ggn_max_limit() {
-        let mut options = TorrentOptions::default();
-        options.announce = vec!["https://gazellegames.net/announce".to_string()];
+        let options = TorrentOptions {
+            announce: vec!["https://gazellegames.net/announce".to_string()],
+            ..Default::default()
+        };
 
         // GGn has max piece length 26.
         let builder = TorrentBuilder::new(PathBuf::from("."), options.clone());
@@ -613,4 +2095,119 @@ mod tests {
         assert_eq!(pow, 26);
         assert_eq!(len, 1 << 26);
     }
+
+    #[test]
+    fn test_tiny_file_gets_smallest_tracker_range() {
+        let piece_exp = |announce: &str, total_size: u64| {
+            let options = TorrentOptions {
+                announce: vec![announce.to_string()],
+                ..Default::default()
+            };
+            let builder = TorrentBuilder::new(PathBuf::from("."), options);
+            let config = builder.resolve_tracker_config();
+            assert!(config.is_some());
+            builder
+                .calculate_piece_length_with_config(total_size, config)
+                .1
+        };
+
+        for total_size in [1, 1024] {
+            assert_eq!(
+                piece_exp("https://gazellegames.net/announce", total_size),
+                15
+            );
+            assert_eq!(
+                piece_exp("https://passthepopcorn.me/announce", total_size),
+                16
+            );
+            // No ranges: the default table, capped at the tracker's limit
+            assert_eq!(piece_exp("https://beyond-hd.me/announce", total_size), 15);
+        }
+    }
+
+    #[test]
+    fn test_tracker_range_fallbacks() {
+        use crate::trackers::{PieceSizeRange, TrackerConfig};
+
+        let config = |use_default_ranges, max_piece_length| TrackerConfig {
+            urls: &[],
+            default_source: None,
+            // Deliberately unsorted
+            piece_size_ranges: &[
+                PieceSizeRange {
+                    max_size: 1 << 30,
+                    piece_exp: 20,
+                },
+                PieceSizeRange {
+                    max_size: 1 << 20,
+                    piece_exp: 15,
+                },
+            ],
+            max_piece_length,
+            max_torrent_size: None,
+            use_default_ranges,
+            requires_private: false,
+        };
+        let builder = TorrentBuilder::new(PathBuf::from("."), TorrentOptions::default());
+        let pick = |config: &TrackerConfig, total_size| {
+            builder
+                .calculate_piece_length_with_config(total_size, Some(config))
+                .1
+        };
+
+        // The tightest matching range wins regardless of order
+        assert_eq!(pick(&config(false, None), 1), 15);
+        assert_eq!(pick(&config(false, None), 2 << 20), 20);
+        // Beyond every range: the largest range, or the default table
+        let huge = 20 << 30;
+        assert_eq!(pick(&config(false, None), huge), 20);
+        assert_eq!(
+            pick(&config(true, None), huge),
+            calculate_piece_length(huge)
+        );
+        // The tracker's limit also caps the default table
+        assert_eq!(pick(&config(true, Some(21)), huge), 21);
+    }
+
+    #[test]
+    fn test_max_piece_exp_clamps_default_calc() {
+        // 20 GiB would normally get 2^23 pieces
+        let total_size = 20 * 1024 * 1024 * 1024;
+        let mut options = TorrentOptions::default();
+        let builder = TorrentBuilder::new(PathBuf::from("."), options.clone());
+        assert_eq!(
+            builder
+                .calculate_piece_length_with_config(total_size, None)
+                .1,
+            23
+        );
+
+        options.max_piece_exp = Some(20);
+        let builder = TorrentBuilder::new(PathBuf::from("."), options);
+        let (len, pow) = builder.calculate_piece_length_with_config(total_size, None);
+        assert_eq!(pow, 20);
+        assert_eq!(len, 1 << 20);
+    }
+
+    #[test]
+    fn test_piece_exp_clamp_overrides_tracker_and_user_choice() {
+        let mut options = TorrentOptions {
+            announce: vec!["https://passthepopcorn.me/announce".to_string()],
+            min_piece_exp: Some(18),
+            ..Default::default()
+        };
+        let builder = TorrentBuilder::new(PathBuf::from("."), options.clone());
+        let config = builder.resolve_tracker_config();
+
+        // PTP would pick 2^16 for 50 MiB
+        let (_, pow) = builder.calculate_piece_length_with_config(50 * 1024 * 1024, config);
+        assert_eq!(pow, 18);
+
+        // An explicit -l is still bounded
+        options.piece_length = Some(24);
+        options.max_piece_exp = Some(22);
+        let builder = TorrentBuilder::new(PathBuf::from("."), options);
+        let (_, pow) = builder.calculate_piece_length_with_config(50 * 1024 * 1024, config);
+        assert_eq!(pow, 22);
+    }
 }
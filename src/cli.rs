@@ -1,6 +1,8 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use std::str::FromStr;
 
+use crate::config::check_piece_exp;
 use crate::models::{Mode, TorrentOptions};
 
 #[derive(Parser, Debug, Clone)]
@@ -17,7 +19,7 @@ pub struct Cli {
 #[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     /// Create a new torrent (default)
-    Create(CreateArgs),
+    Create(Box<CreateArgs>),
 
     /// Verify local files against a torrent
     Verify(VerifyArgs),
@@ -27,11 +29,21 @@ pub enum Commands {
 
     /// Edit an existing torrent's metadata
     Edit(EditArgs),
+
+    /// List the files a torrent would include, without hashing
+    Scan(ScanArgs),
+
+    /// Rebuild a V2 torrent's piece layers from its data
+    Recalc(RecalcArgs),
+
+    /// Print a shell completion script to stdout
+    #[command(hide = true, alias = "complete")]
+    Completions(CompletionsArgs),
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct CreateArgs {
-    /// The file or directory to create a torrent from
+    /// The file or directory to create a torrent from, or a glob like `videos/*.mkv`
     #[arg(value_name = "TARGET")]
     pub source: Option<PathBuf>,
 
@@ -43,27 +55,174 @@ pub struct CreateArgs {
     #[arg(short = 'a', long = "announce", value_name = "URL")]
     pub announce: Vec<String>,
 
+    /// Add the profile's trackers as well, instead of letting -a replace them. The
+    /// profile's tiers come first, then the -a tiers that aren't already listed.
+    #[arg(long = "merge-trackers")]
+    pub merge_trackers: bool,
+
+    /// Apply a tracker's piece size and source defaults by short name (e.g. ptp, ggn)
+    /// without adding its announce URL
+    #[arg(long = "tracker-preset", value_name = "NAME")]
+    pub tracker_preset: Option<String>,
+
+    /// Put every announce URL in a single tier, for clients that mishandle multi-tier lists
+    #[arg(long = "announce-list-flatten")]
+    pub announce_list_flatten: bool,
+
+    /// Write only the first announce URL and no announce-list, for old clients that
+    /// can't parse one
+    #[arg(long = "no-announce-list", conflicts_with = "announce_list_flatten")]
+    pub no_announce_list: bool,
+
+    /// Shuffle the URLs within each tracker tier (not across tiers), so clients
+    /// that try a tier in order spread their load over its trackers
+    #[arg(long = "announce-tier-shuffle")]
+    pub announce_tier_shuffle: bool,
+
+    /// Use a fixed seed for random choices like --announce-tier-shuffle, so the
+    /// same inputs give the same torrent
+    #[arg(long = "deterministic")]
+    pub deterministic: bool,
+
     /// Add a comment to the metainfo
     #[arg(short = 'c', long = "comment", value_name = "COMMENT")]
     pub comment: Option<String>,
 
+    /// Set the comment from a template resolved after hashing.
+    /// Placeholders: {date}, {name}, {size}, {files}, {infohash}
+    #[arg(
+        long = "comment-template",
+        value_name = "TEMPLATE",
+        conflicts_with = "comment"
+    )]
+    pub comment_template: Option<String>,
+
+    /// Escape the comment for trackers that render it as BBCode or Markdown
+    #[arg(
+        long = "escape-comment",
+        value_name = "SYNTAX",
+        value_enum,
+        default_value_t = CommentEscape::None
+    )]
+    pub escape_comment: CommentEscape,
+
     /// Don't write the creation date
     #[arg(short = 'd', long = "no-date")]
     pub no_date: bool,
 
+    /// Write TEXT as `created by` instead of torrite's name and version, or leave
+    /// the field out if TEXT is empty. For matching another tool's output exactly.
+    #[arg(long = "created-by", value_name = "TEXT")]
+    pub created_by: Option<String>,
+
+    /// Record who released the content as `publisher` (outside the info hash)
+    #[arg(long = "publisher", value_name = "NAME")]
+    pub publisher: Option<String>,
+
+    /// Record the publisher's website as `publisher-url` (outside the info hash)
+    #[arg(long = "publisher-url", value_name = "URL")]
+    pub publisher_url: Option<String>,
+
+    /// Append the creation date (ISO 8601, UTC) to the comment. Ignored with --no-date.
+    #[arg(long = "date-comment")]
+    pub date_comment: bool,
+
     /// Exclude files matching pattern (glob) - can be comma-separated
     #[arg(short = 'e', long = "exclude", value_name = "PATTERN", value_delimiter = ',')]
     pub exclude: Vec<String>,
 
+    /// Don't add the config file's `[defaults] exclude` patterns
+    #[arg(long = "no-default-excludes")]
+    pub no_default_excludes: bool,
+
+    /// Only include files matching pattern (glob), applied after excludes - can be comma-separated
+    #[arg(long = "include-only", value_name = "PATTERN", value_delimiter = ',')]
+    pub include_only: Vec<String>,
+
+    /// Keep files and directories whose name starts with a dot (e.g. .git, .DS_Store),
+    /// which are skipped by default. An --include-only pattern that spells out the dot,
+    /// such as .env, picks a hidden file without this.
+    #[arg(long = "include-hidden")]
+    pub include_hidden: bool,
+
+    /// Leave zero-byte files out of the torrent
+    #[arg(long = "skip-empty-files")]
+    pub skip_empty_files: bool,
+
+    /// Record each file's modification time as a non-standard `mtime` key in the
+    /// file list. It's part of the info dict, so it changes the info hash, and only
+    /// applies to multi-file V1 and hybrid torrents.
+    #[arg(long = "store-mtime", conflicts_with_all = ["from_archive", "from_url"])]
+    pub store_mtime: bool,
+
+    /// Don't warn when the content includes .torrent files
+    #[arg(long = "allow-torrent-files")]
+    pub allow_torrent_files: bool,
+
     /// Overwrite output file if it exists
     #[arg(short = 'f', long = "force")]
     pub force: bool,
 
-    /// Set the piece length to 2^N bytes (e.g., 18 for 256KB)
-    #[arg(short = 'l', long = "piece-length", value_name = "N")]
-    pub piece_length: Option<u32>,
+    /// After the build, print bytes hashed, throughput and time per phase to stderr
+    #[arg(long = "stats")]
+    pub stats: bool,
+
+    /// After writing, read the torrent back and check its info hashes match the ones
+    /// just computed, to catch a truncated write or disk corruption
+    #[arg(long = "rehash-verify")]
+    pub rehash_verify: bool,
+
+    /// Write V2 piece layers to a `.layers` file next to the torrent instead of
+    /// inside it. Non-standard: merge them back with `recalc --layers` before seeding.
+    #[arg(long = "piece-layers-external")]
+    pub piece_layers_external: bool,
+
+    /// Also write the magnet link to a `.magnet` file next to the torrent
+    #[arg(long = "write-magnet")]
+    pub write_magnet: bool,
 
-    /// Set the name of the torrent (defaults to basename of target)
+    /// Also write a plain-text summary (as shown by `inspect`) to a `.torrent.txt`
+    /// file next to the torrent
+    #[arg(long = "write-summary")]
+    pub write_summary: bool,
+
+    /// Set the piece length to 2^N bytes (e.g., 18 for 256KB), or a size like 256KiB or 1MiB
+    #[arg(short = 'l', long = "piece-length", value_name = "N")]
+    pub piece_length: Option<PieceLength>,
+
+    /// Round --piece-length sizes that aren't a power of two to the nearest one
+    #[arg(long = "round-piece-length")]
+    pub round_piece_length: bool,
+
+    /// Never use a piece length below 2^N bytes (or a size), regardless of tracker defaults
+    #[arg(long = "min-piece-length", value_name = "N")]
+    pub min_piece_length: Option<PieceLength>,
+
+    /// Halve an automatically chosen piece length until there are at least N pieces
+    /// (never below 16 KiB). Ignored with --piece-length.
+    #[arg(long = "min-pieces", value_name = "N")]
+    pub min_pieces: Option<u64>,
+
+    /// Never use a piece length above 2^N bytes (or a size), regardless of tracker defaults
+    #[arg(long = "max-piece-length", value_name = "N")]
+    pub max_piece_length: Option<PieceLength>,
+
+    /// Derive the output path from a template after hashing. Placeholders:
+    /// {name}, {infohash}, {infohash8}, {tracker}, {date}
+    #[arg(
+        long = "output-pattern",
+        value_name = "TEMPLATE",
+        conflicts_with = "output"
+    )]
+    pub output_pattern: Option<String>,
+
+    /// What to write: a regular .torrent, or a JSON dump of its structure for debugging
+    /// (binary fields hex-encoded; not loadable by clients)
+    #[arg(long = "emit", value_name = "FORMAT", value_enum, default_value_t = Emit::Torrent)]
+    pub emit: Emit,
+
+    /// Set the name of the torrent (defaults to basename of target).
+    /// For directories this is the folder name clients extract into.
     #[arg(short = 'n', long = "name", value_name = "NAME")]
     pub name: Option<String>,
 
@@ -71,26 +230,67 @@ pub struct CreateArgs {
     #[arg(short = 'o', long = "output", value_name = "FILE")]
     pub output: Option<PathBuf>,
 
-    /// Set the creation date (Unix timestamp)
-    #[arg(long = "date", value_name = "TIMESTAMP")]
+    /// Set the creation date (Unix timestamp, or an RFC 3339 date like 2020-01-01T00:00:00Z)
+    #[arg(long = "date", value_name = "TIMESTAMP", value_parser = parse_creation_date)]
     pub date: Option<i64>,
 
+    /// Set the creation date from an RFC 3339 date (e.g. 2020-01-01T00:00:00Z)
+    #[arg(
+        long = "date-rfc3339",
+        value_name = "DATE",
+        value_parser = parse_rfc3339,
+        conflicts_with = "date"
+    )]
+    pub date_rfc3339: Option<i64>,
+
     /// Set the private flag
     #[arg(short = 'p', long = "private")]
     pub private: bool,
 
+    /// Don't set the private flag automatically for trackers that require it
+    #[arg(long = "public", conflicts_with = "private")]
+    pub public: bool,
+
     /// Add source string embedded in infohash
     #[arg(short = 's', long = "source", value_name = "SOURCE")]
     pub source_string: Option<String>,
 
-    /// Number of threads for hashing (defaults to number of CPU cores)
+    /// Number of threads for hashing (0 or unset: one per CPU core)
     #[arg(short = 't', long = "threads", value_name = "N")]
     pub threads: Option<usize>,
 
+    /// Limit V1 piece buffers held at once to about this many MiB, using fewer
+    /// threads when threads * piece length would exceed it
+    #[arg(long = "max-memory", value_name = "MIB")]
+    pub max_memory: Option<u64>,
+
+    /// Blocks per parallel V2 hashing work item, for benchmarking read patterns.
+    /// Also turns off the finer split used when a single file is hashed.
+    #[arg(long = "v2-chunk-blocks", value_name = "N", hide = true)]
+    pub v2_chunk_blocks: Option<usize>,
+
+    /// Allow options that write deliberately nonstandard torrents, for client testing
+    #[arg(long = "experimental", hide = true)]
+    pub experimental: bool,
+
+    /// Write this `meta version` into the info dict instead of the standard value
+    /// (changes the info hash; needs --experimental)
+    #[arg(
+        long = "meta-version",
+        value_name = "N",
+        hide = true,
+        requires = "experimental"
+    )]
+    pub meta_version: Option<u8>,
+
     /// Verbose output
     #[arg(short = 'v', long = "verbose")]
     pub verbose: bool,
 
+    /// Print which SHA-1/SHA-256 implementation hashing uses on this machine and exit
+    #[arg(long = "print-backend")]
+    pub print_backend: bool,
+
     /// Web seed URL(s) - can be specified multiple times
     #[arg(short = 'w', long = "web-seed", value_name = "URL", value_delimiter = ',')]
     pub web_seed: Vec<String>,
@@ -99,14 +299,58 @@ pub struct CreateArgs {
     #[arg(short = 'x', long = "cross-seed")]
     pub cross_seed: bool,
 
+    /// Use an existing torrent's piece length, private flag, source and trackers as defaults.
+    /// Explicit flags still take precedence; content is hashed as usual.
+    #[arg(long = "like", value_name = "TORRENT")]
+    pub like: Option<PathBuf>,
+
+    /// Use an existing torrent's piece length and nothing else, e.g. to make
+    /// comparable torrents of different content
+    #[arg(
+        long = "piece-length-from",
+        value_name = "TORRENT",
+        conflicts_with = "piece_length"
+    )]
+    pub piece_length_from: Option<PathBuf>,
+
+    /// Existing torrent this one will be cross-seeded alongside (implies --cross-seed)
+    #[arg(long = "cross-seed-from", value_name = "TORRENT")]
+    pub cross_seed_from: Option<PathBuf>,
+
+    /// Copy the comment and source string from the --cross-seed-from torrent.
+    /// Explicit --comment/--source still take precedence.
+    #[arg(long = "inherit-metadata", requires = "cross_seed_from")]
+    pub inherit_metadata: bool,
+
+    /// Info hash (hex) of a torrent sharing files with this one (BEP 38) - can be specified multiple times
+    #[arg(long = "similar", value_name = "INFOHASH", value_parser = parse_info_hash)]
+    pub similar: Vec<String>,
+
+    /// Collection this torrent belongs to (BEP 38) - can be specified multiple times
+    #[arg(long = "collection", value_name = "NAME")]
+    pub collection: Vec<String>,
+
     /// Display the info hash of the created torrent
     #[arg(long = "info-hash")]
     pub info_hash: bool,
 
+    /// Report files with identical content and the space they waste (needs --v2 or --hybrid)
+    #[arg(long = "report-duplicates")]
+    pub report_duplicates: bool,
+
+    /// Report the padding files a hybrid torrent needs to align files to pieces and
+    /// how much they add, in total and per file
+    #[arg(long = "piece-length-report", conflicts_with = "from_url")]
+    pub piece_length_report: bool,
+
     /// Output results in JSON format
     #[arg(long = "json")]
     pub json: bool,
 
+    /// Create a v1-only torrent, overriding `TORRITE_DEFAULT_MODE` and a profile's mode
+    #[arg(long = "v1", conflicts_with_all = ["v2", "hybrid"])]
+    pub v1: bool,
+
     /// Create a v2-only torrent (no v1 compatibility)
     #[arg(long = "v2", conflicts_with = "hybrid")]
     pub v2: bool,
@@ -115,9 +359,135 @@ pub struct CreateArgs {
     #[arg(long = "hybrid", conflicts_with = "v2")]
     pub hybrid: bool,
 
+    /// Don't insert padding files into hybrid torrents, for clients that mishandle them.
+    /// V1 pieces then straddle file boundaries, which violates BEP 52.
+    #[arg(long = "no-padding", conflicts_with = "v2")]
+    pub no_padding: bool,
+
     /// Calculate piece length and show info without hashing
     #[arg(long = "dry-run")]
     pub dry_run: bool,
+
+    /// Fail instead of warning when the torrent doesn't meet tracker requirements
+    /// or has portability problems (e.g. paths differing only in case)
+    #[arg(long = "strict")]
+    pub strict: bool,
+
+    /// Double-check that the V1 file list and V2 file tree of a hybrid torrent
+    /// describe the same files in the same order, failing if they diverge
+    #[arg(long = "force-v1-name-compat")]
+    pub force_v1_name_compat: bool,
+
+    /// Treat SOURCE as a zip/tar archive and hash its entries without extracting.
+    /// Zip entries may be stored or deflated; tarballs may be plain or gzipped
+    /// (zstd, bzip2 and xz tarballs aren't supported).
+    #[arg(long = "from-archive")]
+    pub from_archive: bool,
+
+    /// Build a single-file torrent from a file served over HTTP(S), hashing it as it
+    /// downloads. The URL is added as a web seed. Needs a build with the `http`
+    /// feature.
+    #[arg(
+        long = "from-url",
+        value_name = "URL",
+        conflicts_with_all = ["source", "from_archive", "relative_to", "dry_run"]
+    )]
+    pub from_url: Option<String>,
+
+    /// Hash exactly BYTES of SOURCE, for block devices and files whose reported size
+    /// can't be trusted. Fails if fewer bytes can be read.
+    #[arg(
+        long = "length",
+        value_name = "BYTES",
+        conflicts_with_all = ["from_archive", "from_url", "relative_to", "store_mtime"]
+    )]
+    pub length: Option<u64>,
+
+    /// Store paths relative to DIR instead of SOURCE, e.g. to keep `season/episode.mkv`
+    /// when SOURCE is the episode. Always produces a multi-file torrent named after DIR.
+    #[arg(
+        long = "relative-to",
+        visible_alias = "base",
+        value_name = "DIR",
+        conflicts_with = "from_archive"
+    )]
+    pub relative_to: Option<PathBuf>,
+
+    /// Keep empty directories by adding a zero-length `.keep` file to each.
+    /// V1 has no notion of directories, so clients only see the placeholder files.
+    #[arg(long = "keep-empty-dirs")]
+    pub keep_empty_dirs: bool,
+
+    /// Only include files directly inside TARGET, without descending into subdirectories
+    #[arg(long = "no-recursion")]
+    pub no_recursion: bool,
+
+    /// When TARGET is a directory holding exactly one file, make a single-file torrent
+    /// named after that file instead of a multi-file torrent with a containing directory.
+    /// The info hash differs from the directory torrent's.
+    #[arg(
+        long = "flatten-single",
+        conflicts_with_all = ["relative_to", "from_archive"]
+    )]
+    pub flatten_single: bool,
+
+    /// When everything in TARGET sits under one directory, drop that directory from the
+    /// stored paths and name the torrent after it. Clients then extract into a single
+    /// `NAME/` folder instead of `NAME/DIR/`. The info hash differs from the unstripped torrent's.
+    #[arg(
+        long = "strip-root",
+        conflicts_with_all = ["relative_to", "from_archive", "flatten_single"]
+    )]
+    pub strip_root: bool,
+
+    /// Cache V2 block hashes in FILE so unchanged files are skipped on re-runs
+    #[arg(long = "hash-cache", value_name = "FILE")]
+    pub hash_cache: Option<PathBuf>,
+
+    /// Compress the hash cache with zstd
+    #[arg(long = "cache-compress", requires = "hash_cache")]
+    pub cache_compress: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ScanArgs {
+    /// The file or directory to scan
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+
+    /// Exclude files matching pattern (glob) - can be comma-separated
+    #[arg(
+        short = 'e',
+        long = "exclude",
+        value_name = "PATTERN",
+        value_delimiter = ','
+    )]
+    pub exclude: Vec<String>,
+
+    /// Only include files matching pattern (glob) - can be comma-separated
+    #[arg(long = "include-only", value_name = "PATTERN", value_delimiter = ',')]
+    pub include_only: Vec<String>,
+
+    /// Leave zero-byte files out
+    #[arg(long = "skip-empty-files")]
+    pub skip_empty_files: bool,
+
+    /// List files and directories whose name starts with a dot, as `create
+    /// --include-hidden` would hash them
+    #[arg(long = "include-hidden")]
+    pub include_hidden: bool,
+
+    /// List empty directories as the zero-length `.keep` files `create` would add
+    #[arg(long = "keep-empty-dirs")]
+    pub keep_empty_dirs: bool,
+
+    /// Only list files directly inside PATH, without descending into subdirectories
+    #[arg(long = "no-recursion")]
+    pub no_recursion: bool,
+
+    /// Output results in JSON format
+    #[arg(long = "json")]
+    pub json: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -129,6 +499,50 @@ pub struct VerifyArgs {
     /// The path to the data directory or file (defaults to current directory)
     #[arg(long = "path", value_name = "PATH")]
     pub path: Option<PathBuf>,
+
+    /// Search DIR (a few levels deep) for the torrent's content instead of giving an exact --path
+    #[arg(long = "find", value_name = "DIR", conflicts_with = "path")]
+    pub find: Option<PathBuf>,
+
+    /// List every missing or wrongly sized file instead of stopping at the first
+    #[arg(long = "continue-on-error")]
+    pub continue_on_error: bool,
+
+    /// Check the V1 pieces against FILE as one concatenated image of the torrent's
+    /// data (e.g. a disk image), ignoring the per-file layout
+    #[arg(long = "raw", value_name = "FILE", conflicts_with_all = ["path", "find", "continue_on_error"])]
+    pub raw: Option<PathBuf>,
+
+    /// Print the result as a JSON object on stdout instead of progress and messages
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for
+    #[arg(value_enum, value_name = "SHELL")]
+    pub shell: clap_complete::Shell,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RecalcArgs {
+    /// The V2 or hybrid torrent whose piece layers are missing
+    #[arg(value_name = "TORRENT")]
+    pub torrent: PathBuf,
+
+    /// The path to the data directory or file (defaults to ./NAME)
+    #[arg(long = "path", value_name = "PATH")]
+    pub path: Option<PathBuf>,
+
+    /// Write the completed torrent here instead of overwriting TORRENT
+    #[arg(short = 'o', long = "output", value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Merge piece layers from a `.layers` file written by
+    /// `--piece-layers-external` instead of re-hashing the data
+    #[arg(long = "layers", value_name = "FILE", conflicts_with = "path")]
+    pub layers: Option<PathBuf>,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -136,13 +550,25 @@ pub struct InspectArgs {
     /// The torrent file to inspect
     #[arg(value_name = "TORRENT")]
     pub torrent: PathBuf,
+
+    /// Print the raw bencode structure with types and lengths instead of the summary
+    #[arg(long = "dump-bencode")]
+    pub dump_bencode: bool,
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct EditArgs {
     /// The torrent file to edit
-    #[arg(value_name = "TORRENT")]
-    pub torrent: PathBuf,
+    #[arg(value_name = "TORRENT", required_unless_present = "batch")]
+    pub torrent: Option<PathBuf>,
+
+    /// Apply the changes to every .torrent file matching GLOB (quote it), in place
+    #[arg(
+        long = "batch",
+        value_name = "GLOB",
+        conflicts_with_all = ["torrent", "output"]
+    )]
+    pub batch: Option<String>,
 
     /// Append announce URL(s)
     #[arg(short = 'a', long = "announce", value_name = "URL")]
@@ -152,6 +578,19 @@ pub struct EditArgs {
     #[arg(long = "replace-announce", value_name = "URL", conflicts_with = "announce")]
     pub replace_announce: Option<String>,
 
+    /// Move trackers from OLD host to NEW host, keeping paths and passkeys
+    #[arg(
+        long = "replace-host",
+        num_args = 2,
+        value_names = ["OLD", "NEW"],
+        conflicts_with = "replace_announce"
+    )]
+    pub replace_host: Option<Vec<String>>,
+
+    /// Merge all tracker tiers into a single tier, dropping duplicate URLs
+    #[arg(long = "announce-list-flatten")]
+    pub announce_list_flatten: bool,
+
     /// Set or update the comment
     #[arg(short = 'c', long = "comment", value_name = "COMMENT")]
     pub comment: Option<String>,
@@ -167,39 +606,312 @@ pub struct EditArgs {
     /// Set the output file path (defaults to overwriting input)
     #[arg(short = 'o', long = "output", value_name = "FILE")]
     pub output: Option<PathBuf>,
+
+    /// Check the torrent for problems and exit non-zero if any are found, without
+    /// opening the editor or writing anything
+    #[arg(
+        long = "validate",
+        alias = "validate-only",
+        conflicts_with_all = [
+            "batch", "announce", "replace_announce", "replace_host",
+            "announce_list_flatten", "comment", "private", "public", "output"
+        ]
+    )]
+    pub validate: bool,
+}
+
+/// Environment variable overriding the built-in default mode (V1)
+pub const DEFAULT_MODE_ENV: &str = "TORRITE_DEFAULT_MODE";
+
+/// Mode used when neither `--v1`/`--v2`/`--hybrid` nor a profile picks one
+fn default_mode_from_env() -> anyhow::Result<Mode> {
+    let Ok(value) = std::env::var(DEFAULT_MODE_ENV) else {
+        return Ok(Mode::V1);
+    };
+    match value.trim().to_ascii_lowercase().as_str() {
+        "" | "v1" => Ok(Mode::V1),
+        "v2" => Ok(Mode::V2),
+        "hybrid" => Ok(Mode::Hybrid),
+        _ => anyhow::bail!(
+            "Invalid {}={:?}; expected v1, v2 or hybrid",
+            DEFAULT_MODE_ENV,
+            value
+        ),
+    }
 }
 
 impl CreateArgs {
     /// Convert CLI arguments to TorrentOptions
-    pub fn into_options(self) -> TorrentOptions {
+    pub fn into_options(self) -> anyhow::Result<TorrentOptions> {
         let mode = if self.hybrid {
             Mode::Hybrid
         } else if self.v2 {
             Mode::V2
-        } else {
+        } else if self.v1 {
             Mode::V1
+        } else {
+            default_mode_from_env()?
         };
 
-        let creation_date = self.date.or_else(|| {
+        let creation_date = self.date.or(self.date_rfc3339).or_else(|| {
             std::env::var("SOURCE_DATE_EPOCH")
                 .ok()
                 .and_then(|s| s.parse::<i64>().ok())
         });
 
-        TorrentOptions {
+        let round = self.round_piece_length;
+        let to_exponent = |value: Option<PieceLength>| {
+            value
+                .map(|v| v.to_exponent(round))
+                .transpose()
+                .map_err(anyhow::Error::msg)
+        };
+
+        if let Some(preset) = &self.tracker_preset
+            && crate::trackers::find_tracker_preset(preset).is_none()
+        {
+            let known: Vec<&str> = crate::trackers::TRACKER_PRESETS
+                .iter()
+                .map(|(name, _)| *name)
+                .collect();
+            anyhow::bail!(
+                "Unknown tracker preset '{}' (known: {})",
+                preset,
+                known.join(", ")
+            );
+        }
+
+        Ok(TorrentOptions {
             mode,
-            piece_length: self.piece_length,
+            piece_length: to_exponent(self.piece_length)?,
+            min_piece_exp: to_exponent(self.min_piece_length)?,
+            min_pieces: self.min_pieces,
+            max_piece_exp: to_exponent(self.max_piece_length)?,
             private: self.private,
+            public: self.public,
             comment: self.comment,
             announce: self.announce,
+            no_announce_list: self.no_announce_list,
+            announce_tier_shuffle: self.announce_tier_shuffle,
+            deterministic: self.deterministic,
+            tracker_preset: self.tracker_preset,
             web_seed: self.web_seed,
             source_string: self.source_string,
             cross_seed: self.cross_seed,
             no_date: self.no_date,
+            created_by: self.created_by,
+            publisher: self.publisher,
+            publisher_url: self.publisher_url,
             creation_date,
             name: self.name,
             exclude: self.exclude,
+            include_only: self.include_only,
+            include_hidden: self.include_hidden,
+            allow_torrent_files: self.allow_torrent_files,
+            skip_empty_files: self.skip_empty_files,
+            store_mtime: self.store_mtime,
+            similar: self
+                .similar
+                .iter()
+                .filter_map(|h| hex::decode(h).ok())
+                .collect(),
+            collections: self.collection,
             dry_run: self.dry_run,
+            from_archive: self.from_archive,
+            from_url: self.from_url.is_some(),
+            length: self.length,
+            keep_empty_dirs: self.keep_empty_dirs,
+            no_recursion: self.no_recursion,
+            flatten_single: self.flatten_single,
+            strip_root: self.strip_root,
+            no_padding: self.no_padding,
+            meta_version: self.meta_version,
+            relative_to: self.relative_to,
+            strict: self.strict,
+            force_v1_name_compat: self.force_v1_name_compat,
+        })
+    }
+}
+
+/// Output format of the create command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// Bencoded metainfo
+    Torrent,
+    /// JSON representation of the metainfo
+    Json,
+}
+
+/// Markup to escape in the comment (see `--escape-comment`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CommentEscape {
+    /// Store the comment as written
+    None,
+    /// Turn `[`, `]` and `&` into HTML entities so tags aren't rendered
+    Bbcode,
+    /// Backslash-escape Markdown punctuation
+    Markdown,
+}
+
+/// A piece length given either as a power-of-two exponent (`18`) or as a size (`256KiB`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceLength {
+    Exponent(u32),
+    Bytes(u64),
+}
+
+impl PieceLength {
+    /// Resolve to an exponent. Sizes must be a power of two unless `round` is set,
+    /// in which case the nearest power of two is used.
+    pub fn to_exponent(self, round: bool) -> Result<u32, String> {
+        let bytes = match self {
+            PieceLength::Exponent(exp) => return check_piece_exp(exp),
+            PieceLength::Bytes(0) => {
+                return Err("piece length must be greater than zero".to_string());
+            }
+            PieceLength::Bytes(bytes) => bytes,
+        };
+        if bytes.is_power_of_two() {
+            return Ok(bytes.trailing_zeros());
+        }
+
+        let lower = 63 - bytes.leading_zeros();
+        let nearest = match 1u64.checked_shl(lower + 1) {
+            Some(upper) if upper - bytes <= bytes - (1u64 << lower) => lower + 1,
+            _ => lower,
+        };
+        if round {
+            check_piece_exp(nearest)
+        } else {
+            Err(format!(
+                "piece length of {} bytes is not a power of two (nearest is 2^{}); pass --round-piece-length to round it",
+                bytes, nearest
+            ))
         }
     }
 }
+
+impl FromStr for PieceLength {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(exp) = s.parse::<u32>() {
+            return Ok(PieceLength::Exponent(exp));
+        }
+
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid piece length '{}'", s))?;
+        let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+            "b" => 1,
+            "k" | "kb" | "kib" => 1 << 10,
+            "m" | "mb" | "mib" => 1 << 20,
+            "g" | "gb" | "gib" => 1 << 30,
+            _ => {
+                return Err(format!(
+                    "invalid piece length '{}' (expected an exponent or a size like 256KiB)",
+                    s
+                ));
+            }
+        };
+        number
+            .checked_mul(multiplier)
+            .map(PieceLength::Bytes)
+            .ok_or_else(|| format!("piece length '{}' is too large", s))
+    }
+}
+
+/// Accepts a v1 (40 hex chars) or v2 (64 hex chars) info hash, normalized to lowercase
+fn parse_info_hash(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    if (s.len() == 40 || s.len() == 64) && s.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(s.to_ascii_lowercase())
+    } else {
+        Err("expected a 40 or 64 character hex info hash".to_string())
+    }
+}
+
+/// Accepts a Unix timestamp or an RFC 3339 date, as seconds since the epoch
+fn parse_creation_date(s: &str) -> Result<i64, String> {
+    s.trim().parse::<i64>().or_else(|_| parse_rfc3339(s))
+}
+
+/// Parses an RFC 3339 date with an explicit offset into seconds since the epoch
+fn parse_rfc3339(s: &str) -> Result<i64, String> {
+    chrono::DateTime::parse_from_rfc3339(s.trim())
+        .map(|dt| dt.timestamp())
+        .map_err(|e| {
+            format!(
+                "expected an RFC 3339 date like 2020-01-01T00:00:00Z ({})",
+                e
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_creation_date_parsing() {
+        assert_eq!(parse_rfc3339("2020-01-01T00:00:00Z"), Ok(1_577_836_800));
+        assert_eq!(
+            parse_rfc3339("2020-01-01T02:00:00+02:00"),
+            Ok(1_577_836_800)
+        );
+        assert!(parse_rfc3339("2020-01-01").is_err());
+        assert!(parse_rfc3339("1577836800").is_err());
+
+        assert_eq!(parse_creation_date("1577836800"), Ok(1_577_836_800));
+        assert_eq!(
+            parse_creation_date("2020-01-01T00:00:00Z"),
+            Ok(1_577_836_800)
+        );
+        assert!(parse_creation_date("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_piece_length_parsing() {
+        assert_eq!("18".parse(), Ok(PieceLength::Exponent(18)));
+        assert_eq!("256KiB".parse(), Ok(PieceLength::Bytes(256 * 1024)));
+        assert_eq!("1 MiB".parse(), Ok(PieceLength::Bytes(1024 * 1024)));
+        assert!("256XB".parse::<PieceLength>().is_err());
+        assert!("KiB".parse::<PieceLength>().is_err());
+
+        assert_eq!(PieceLength::Exponent(18).to_exponent(false), Ok(18));
+        assert_eq!(
+            "256KiB".parse::<PieceLength>().unwrap().to_exponent(false),
+            Ok(18)
+        );
+        assert_eq!(
+            "1MiB".parse::<PieceLength>().unwrap().to_exponent(false),
+            Ok(20)
+        );
+    }
+
+    #[test]
+    fn test_piece_length_requires_power_of_two() {
+        let size: PieceLength = "300KiB".parse().unwrap();
+        assert!(size.to_exponent(false).is_err());
+        // 300 KiB is closer to 256 KiB than to 512 KiB
+        assert_eq!(size.to_exponent(true), Ok(18));
+        assert_eq!(
+            "400KiB".parse::<PieceLength>().unwrap().to_exponent(true),
+            Ok(19)
+        );
+        assert!(PieceLength::Bytes(0).to_exponent(true).is_err());
+    }
+
+    #[test]
+    fn test_piece_length_exponent_out_of_range() {
+        assert!(PieceLength::Exponent(64).to_exponent(false).is_err());
+        assert!(PieceLength::Exponent(u32::MAX).to_exponent(false).is_err());
+        assert_eq!(PieceLength::Exponent(62).to_exponent(false), Ok(62));
+        // Rounding a size just below 2^64 would need 2^64 bytes
+        assert!(PieceLength::Bytes(u64::MAX).to_exponent(true).is_err());
+        assert_eq!(PieceLength::Bytes(u64::MAX / 4).to_exponent(true), Ok(62));
+    }
+}
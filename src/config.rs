@@ -4,6 +4,22 @@ pub const BLOCK_SIZE: usize = 16384;
 /// Megabyte constant for piece length calculations
 pub const MB: u64 = 1_048_576;
 
+/// Largest piece length exponent: 2^62 is the biggest power of two that fits the
+/// signed 64-bit integers bencode stores the piece length in
+pub const MAX_PIECE_EXP: u32 = 62;
+
+/// Check a piece length exponent from the CLI, a profile or library options
+pub fn check_piece_exp(exp: u32) -> std::result::Result<u32, String> {
+    if exp > MAX_PIECE_EXP {
+        Err(format!(
+            "piece length exponent {} is too large (2^{} bytes at most)",
+            exp, MAX_PIECE_EXP
+        ))
+    } else {
+        Ok(exp)
+    }
+}
+
 /// Piece length thresholds for automatic calculation
 /// Maps total size to piece length power (2^N)
 pub const PIECE_LENGTH_THRESHOLDS: [(u64, u32); 9] = [
@@ -25,16 +41,44 @@ use anyhow::{Context, Result};
 use serde::Deserialize;
 use directories::ProjectDirs;
 
+/// Profile that is always applied underneath `-P` and explicit flags
+pub const DEFAULT_PROFILE: &str = "default";
+
 #[derive(Debug, Default, Deserialize, Clone)]
 pub struct Config {
     #[serde(default)]
     pub profiles: HashMap<String, Profile>,
+
+    #[serde(default)]
+    pub output: OutputConfig,
+
+    #[serde(default)]
+    pub defaults: DefaultsConfig,
+}
+
+/// The `[defaults]` table, applied to every `create` on top of flags and profiles
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DefaultsConfig {
+    /// Exclude patterns added to the ones from `-e` or a profile, e.g. `.DS_Store`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// The `[output]` table
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OutputConfig {
+    /// Directory torrents are written to when `-o` isn't given
+    pub directory: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct Profile {
-    pub announce: Option<Vec<String>>,
-    
+    /// Announce tiers, tried in order; the URLs within a tier are alternatives.
+    /// Written either as `[["a"], ["b", "c"]]` or, as before, one comma-separated
+    /// string per tier: `["a", "b,c"]`
+    #[serde(default, deserialize_with = "deserialize_tiers")]
+    pub announce: Option<Vec<Vec<String>>>,
+
     #[serde(rename = "source")]
     pub source_string: Option<String>,
     
@@ -59,6 +103,52 @@ pub struct Profile {
     
     #[serde(rename = "no_date")]
     pub no_date: Option<bool>,
+
+    /// Overrides `[output] directory` for this profile
+    #[serde(rename = "output_directory")]
+    pub output_directory: Option<String>,
+}
+
+/// One entry of a profile's `announce` list
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TierSpec {
+    Joined(String),
+    Urls(Vec<String>),
+}
+
+fn deserialize_tiers<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<Vec<String>>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let specs: Option<Vec<TierSpec>> = Option::deserialize(deserializer)?;
+    Ok(specs.map(|specs| {
+        specs
+            .into_iter()
+            .map(|spec| {
+                let urls = match spec {
+                    TierSpec::Joined(joined) => joined.split(',').map(str::to_string).collect(),
+                    TierSpec::Urls(urls) => urls,
+                };
+                urls.into_iter()
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|tier| !tier.is_empty())
+            .collect()
+    }))
+}
+
+impl Profile {
+    /// The announce tiers in `--announce` form: one comma-separated string per tier
+    pub fn announce_args(&self) -> Option<Vec<String>> {
+        self.announce
+            .as_ref()
+            .map(|tiers| tiers.iter().map(|tier| tier.join(",")).collect())
+    }
 }
 
 impl Config {
@@ -99,11 +189,37 @@ impl Config {
         Ok(Config::default())
     }
 
+    /// Where to write torrents when no output path is given: the profile's
+    /// `output_directory`, else the default profile's, else `[output] directory`,
+    /// with `~` expanded
+    pub fn output_directory(&self, profile: Option<&str>) -> Option<PathBuf> {
+        [profile, Some(DEFAULT_PROFILE)]
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.profiles.get(name))
+            .find_map(|p| p.output_directory.as_deref())
+            .or(self.output.directory.as_deref())
+            .map(|dir| PathBuf::from(shellexpand::tilde(dir).as_ref()))
+    }
+
     fn from_file(path: &Path) -> Result<Self> {
         let content = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {}", path.display()))?;
-        toml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path.display()))
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+        config
+            .validate()
+            .with_context(|| format!("Invalid config file: {}", path.display()))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, profile) in &self.profiles {
+            if let Some(exp) = profile.piece_length {
+                check_piece_exp(exp).map_err(|e| anyhow::anyhow!("Profile '{}': {}", name, e))?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -131,8 +247,11 @@ mod tests {
         let ptp = &config.profiles["ptp"];
         assert_eq!(ptp.source_string, Some("PTP".to_string()));
         assert_eq!(ptp.piece_length, Some(18));
-        assert_eq!(ptp.announce.as_ref().unwrap()[0], "https://ptp.tracker");
-        
+        assert_eq!(
+            ptp.announce.as_ref().unwrap()[0],
+            vec!["https://ptp.tracker"]
+        );
+
         assert!(config.profiles.contains_key("default"));
         assert_eq!(config.profiles["default"].threads, Some(4));
     }
@@ -151,4 +270,97 @@ mod tests {
         
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_default_excludes() {
+        let config: Config = toml::from_str(
+            r#"
+            [defaults]
+            exclude = [".DS_Store", "Thumbs.db"]
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.defaults.exclude, vec![".DS_Store", "Thumbs.db"]);
+        assert!(Config::default().defaults.exclude.is_empty());
+    }
+
+    #[test]
+    fn test_output_directory() {
+        let config: Config = toml::from_str(
+            r#"
+            [output]
+            directory = "/srv/torrents"
+
+            [profiles.ptp]
+            output_directory = "/srv/ptp"
+
+            [profiles.other]
+            source = "X"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.output_directory(None),
+            Some(PathBuf::from("/srv/torrents"))
+        );
+        assert_eq!(
+            config.output_directory(Some("ptp")),
+            Some(PathBuf::from("/srv/ptp"))
+        );
+        assert_eq!(
+            config.output_directory(Some("other")),
+            Some(PathBuf::from("/srv/torrents"))
+        );
+        assert_eq!(Config::default().output_directory(None), None);
+    }
+
+    #[test]
+    fn test_parse_announce_tiers() {
+        let config: Config = toml::from_str(
+            r#"
+            [profiles.flat]
+            announce = ["https://a.example/announce", "https://b.example/announce, https://c.example/announce"]
+
+            [profiles.tiers]
+            announce = [["https://a.example/announce"], ["https://b.example/announce", "https://c.example/announce"]]
+
+            [profiles.mixed]
+            announce = ["https://a.example/announce", ["https://b.example/announce", "https://c.example/announce"]]
+        "#,
+        )
+        .unwrap();
+
+        let expected = vec![
+            vec!["https://a.example/announce".to_string()],
+            vec![
+                "https://b.example/announce".to_string(),
+                "https://c.example/announce".to_string(),
+            ],
+        ];
+        for name in ["flat", "tiers", "mixed"] {
+            assert_eq!(
+                config.profiles[name].announce.as_ref(),
+                Some(&expected),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            config.profiles["tiers"].announce_args().unwrap(),
+            vec![
+                "https://a.example/announce",
+                "https://b.example/announce,https://c.example/announce"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profile_piece_length_out_of_range() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[profiles.huge]\npiece_length = 100").unwrap();
+
+        let err = Config::load(Some(file.path().to_path_buf())).unwrap_err();
+        assert!(format!("{:#}", err).contains("Profile 'huge'"));
+    }
+}
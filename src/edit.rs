@@ -13,51 +13,144 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
 };
-use std::{fs, io, path::PathBuf};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use torrite::cli::EditArgs;
 use torrite::models::Torrent;
 
-pub fn edit_torrent(args: EditArgs) -> Result<()> {
-    let content = fs::read(&args.torrent).context("Failed to read torrent file")?;
-    let mut torrent: Torrent =
-        serde_bencode::from_bytes(&content).context("Invalid torrent file")?;
+use crate::write_replacing;
 
+pub fn edit_torrent(args: EditArgs) -> Result<()> {
     // Check if any modification flags are set (headless mode)
     let headless = !args.announce.is_empty()
         || args.replace_announce.is_some()
+        || args.replace_host.is_some()
+        || args.announce_list_flatten
         || args.comment.is_some()
         || args.private
         || args.public;
 
+    if let Some(pattern) = &args.batch {
+        if !headless {
+            anyhow::bail!("--batch needs at least one change, e.g. --comment or --replace-host");
+        }
+        return edit_batch(pattern, &args);
+    }
+
+    let path = args.torrent.clone().context("No torrent file given")?;
+    let content = fs::read(&path).context("Failed to read torrent file")?;
+    let mut torrent: Torrent =
+        serde_bencode::from_bytes(&content).context("Invalid torrent file")?;
+
+    if args.validate {
+        return validate_torrent(&path, &torrent);
+    }
+
     if headless {
         if apply_changes(&mut torrent, &args) {
-            let output_path = args.output.unwrap_or(args.torrent);
+            let output_path = args.output.unwrap_or(path);
             println!("Saving to: {}", style(output_path.display()).cyan());
 
-            let bencode_data =
-                serde_bencode::to_bytes(&torrent).context("Failed to serialize torrent")?;
-            fs::write(output_path, bencode_data).context("Failed to write torrent file")?;
+            save_edited(&output_path, &content, &torrent)?;
         } else {
             println!("No changes made.");
         }
     } else {
         // TUI mode
-        let output_path = args.output.unwrap_or(args.torrent.clone());
-        run_tui(torrent, output_path)?;
+        let output_path = args.output.unwrap_or(path);
+        run_tui(torrent, content, output_path)?;
+    }
+
+    Ok(())
+}
+
+/// Report every problem `Info::validate` finds, failing if there are any
+fn validate_torrent(path: &Path, torrent: &Torrent) -> Result<()> {
+    let problems = torrent.info.validate();
+    if problems.is_empty() {
+        println!(
+            "{} {}",
+            style(path.display()).bold(),
+            style("is valid.").green()
+        );
+        return Ok(());
+    }
+    println!("{}", style(path.display()).bold());
+    for problem in &problems {
+        println!("  {} {}", style("Problem:").red(), problem);
+    }
+    anyhow::bail!("{} has {} problem(s)", path.display(), problems.len())
+}
+
+/// Apply the same headless edit to every torrent matching `pattern`, reporting each
+/// file and carrying on past failures
+fn edit_batch(pattern: &str, args: &EditArgs) -> Result<()> {
+    let paths = glob::glob(pattern)
+        .with_context(|| format!("Invalid glob pattern: {}", pattern))?
+        .collect::<Result<Vec<PathBuf>, _>>()
+        .context("Failed to list matching torrents")?;
+    if paths.is_empty() {
+        anyhow::bail!("No torrent files match {}", pattern);
+    }
+
+    let mut edited = 0;
+    let mut failed = 0;
+    for path in &paths {
+        println!("{}", style(path.display()).bold());
+        match edit_in_place(path, args) {
+            Ok(true) => {
+                edited += 1;
+                println!("  {}", style("Saved.").green());
+            }
+            Ok(false) => println!("  No changes made."),
+            Err(e) => {
+                failed += 1;
+                eprintln!("{} {}: {:#}", style("Error:").red(), path.display(), e);
+            }
+        }
     }
 
+    println!();
+    println!("Edited {} of {} torrent(s)", edited, paths.len());
+    if failed > 0 {
+        anyhow::bail!("{} torrent(s) could not be edited", failed);
+    }
     Ok(())
 }
 
-fn run_tui(mut torrent: Torrent, path: PathBuf) -> Result<()> {
+/// Apply `args` to the torrent at `path` and overwrite it; false if nothing changed
+fn edit_in_place(path: &Path, args: &EditArgs) -> Result<bool> {
+    let content = fs::read(path).context("Failed to read torrent file")?;
+    let mut torrent: Torrent =
+        serde_bencode::from_bytes(&content).context("Invalid torrent file")?;
+    if !apply_changes(&mut torrent, args) {
+        return Ok(false);
+    }
+    save_edited(path, &content, &torrent)?;
+    Ok(true)
+}
+
+/// Save an edited torrent over `path`, keeping the keys of `original` the model
+/// doesn't know about
+fn save_edited(path: &Path, original: &[u8], torrent: &Torrent) -> Result<()> {
+    write_replacing(path, |writer| {
+        torrent
+            .write_edited(original, writer)
+            .context("Failed to write torrent file")
+    })
+}
+
+fn run_tui(torrent: Torrent, original: Vec<u8>, path: PathBuf) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let app_result = run_app(&mut terminal, &mut torrent, path);
+    let app_result = run_app(&mut terminal, App::new(torrent, original, path));
 
     disable_raw_mode()?;
     execute!(
@@ -76,6 +169,8 @@ fn run_tui(mut torrent: Torrent, path: PathBuf) -> Result<()> {
 
 struct App {
     torrent: Torrent,
+    // The file as it was read, for the keys the model doesn't know about
+    original: Vec<u8>,
     path: PathBuf,
     list_state: ListState,
     items: Vec<&'static str>,
@@ -86,17 +181,21 @@ struct App {
     show_unsaved_quit_dialog: bool,
     dialog_selection: bool, // true = Yes, false = No
     is_dirty: bool,
+    // Info hashes when the file was opened, to flag edits that change them
+    original_hashes: (Option<[u8; 20]>, Option<[u8; 32]>),
 }
 
 impl App {
-    fn new(torrent: Torrent, path: PathBuf) -> App {
+    fn new(torrent: Torrent, original: Vec<u8>, path: PathBuf) -> App {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
         App {
+            original_hashes: (torrent.info_hash_v1(), torrent.info_hash_v2()),
             torrent,
+            original,
             path,
             list_state,
-            items: vec!["Announce URL", "Comment", "Private"],
+            items: vec!["Announce URL", "Comment", "Private", "Source", "Web Seeds"],
             editing: false,
             input: String::new(),
             show_save_quit_dialog: false,
@@ -145,6 +244,13 @@ impl App {
                     "No".to_string()
                 }
             }
+            3 => self.torrent.info.source.clone().unwrap_or_default(),
+            4 => self
+                .torrent
+                .url_list
+                .as_ref()
+                .map(|urls| urls.join(", "))
+                .unwrap_or_default(),
             _ => String::new(),
         }
     }
@@ -157,138 +263,142 @@ impl App {
 
         match index {
             0 => {
-                self.torrent.announce = if value.is_empty() {
-                    None
+                if value.is_empty() {
+                    if let Some(old) = self.torrent.announce.clone() {
+                        self.torrent.remove_tracker(&old);
+                    }
                 } else {
-                    Some(value.clone())
-                };
-                // Also update first tier of announce list if it exists, roughly
-                if let Some(list) = &mut self.torrent.announce_list {
-                    if !list.is_empty() && !list[0].is_empty() {
-                        if let Some(ann) = &self.torrent.announce {
-                            list[0][0] = ann.clone();
-                        }
-                    } else if list.is_empty() && !value.is_empty() {
-                        // Create list if it doesn't exist
-                        list.push(vec![value]);
+                    // Replace the primary tracker, keeping any backup tiers
+                    let mut tiers = self.torrent.trackers();
+                    match tiers.first_mut().and_then(|tier| tier.first_mut()) {
+                        Some(first) => *first = value,
+                        None => tiers.push(vec![value]),
                     }
-                } else if !value.is_empty() {
-                    self.torrent.announce_list = Some(vec![vec![value]]);
+                    self.torrent.set_trackers(tiers);
                 }
             }
             1 => self.torrent.comment = if value.is_empty() { None } else { Some(value) },
+            3 => {
+                let source = value.trim();
+                self.torrent.info.source = (!source.is_empty()).then(|| source.to_string());
+            }
+            4 => {
+                let urls: Vec<String> = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .map(String::from)
+                    .collect();
+                self.torrent.url_list = (!urls.is_empty()).then_some(urls);
+            }
             _ => {}
         }
     }
-}
 
-fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    torrent: &mut Torrent,
-    path: PathBuf,
-) -> Result<()> {
-    let mut app = App::new(torrent.clone(), path);
+    /// Whether the edits so far change the info hash (private flag or source)
+    fn info_hash_changed(&self) -> bool {
+        (self.torrent.info_hash_v1(), self.torrent.info_hash_v2()) != self.original_hashes
+    }
+}
 
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<()> {
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
 
-        if let Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press {
-                if app.show_save_quit_dialog || app.show_unsaved_quit_dialog {
-                    match key.code {
-                        KeyCode::Left | KeyCode::Right => {
-                            app.dialog_selection = !app.dialog_selection;
-                        }
-                        KeyCode::Enter => {
-                            if app.show_save_quit_dialog {
-                                if app.dialog_selection {
-                                    // Yes -> Quit
-                                    return Ok(());
-                                } else {
-                                    // No -> Close dialog
-                                    app.show_save_quit_dialog = false;
-                                }
-                            } else if app.show_unsaved_quit_dialog {
-                                if app.dialog_selection {
-                                    // Yes -> Quit
-                                    return Ok(());
-                                } else {
-                                    // No -> Close dialog
-                                    app.show_unsaved_quit_dialog = false;
-                                }
-                            }
-                        }
-                        KeyCode::Esc | KeyCode::Char('q') => {
-                            app.show_save_quit_dialog = false;
-                            app.show_unsaved_quit_dialog = false;
-                        }
-                        _ => {}
-                    }
-                } else if app.editing {
-                    match key.code {
-                        KeyCode::Enter => {
-                            if let Some(idx) = app.list_state.selected() {
-                                app.set_value(idx, app.input.clone());
-                            }
-                            app.editing = false;
-                        }
-                        KeyCode::Esc => {
-                            app.editing = false;
-                        }
-                        KeyCode::Backspace => {
-                            app.input.pop();
-                        }
-                        KeyCode::Char(c) => {
-                            app.input.push(c);
-                        }
-                        _ => {}
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            if app.show_save_quit_dialog || app.show_unsaved_quit_dialog {
+                match key.code {
+                    KeyCode::Left | KeyCode::Right => {
+                        app.dialog_selection = !app.dialog_selection;
                     }
-                } else {
-                    match key.code {
-                        KeyCode::Char('q') => {
-                            if app.is_dirty {
-                                app.show_unsaved_quit_dialog = true;
-                                app.dialog_selection = false; // Default to No
+                    KeyCode::Enter => {
+                        if app.show_save_quit_dialog {
+                            if app.dialog_selection {
+                                // Yes -> Quit
+                                return Ok(());
                             } else {
+                                // No -> Close dialog
+                                app.show_save_quit_dialog = false;
+                            }
+                        } else if app.show_unsaved_quit_dialog {
+                            if app.dialog_selection {
+                                // Yes -> Quit
                                 return Ok(());
+                            } else {
+                                // No -> Close dialog
+                                app.show_unsaved_quit_dialog = false;
                             }
                         }
-                        KeyCode::Char('s') => {
-                            let bencode_data = serde_bencode::to_bytes(&app.torrent)
-                                .context("Failed to serialize torrent")?;
-                            fs::write(&app.path, bencode_data)
-                                .context("Failed to write torrent file")?;
-                            app.is_dirty = false;
-                            app.show_save_quit_dialog = true;
-                            app.dialog_selection = true; // Default to Yes
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        app.show_save_quit_dialog = false;
+                        app.show_unsaved_quit_dialog = false;
+                    }
+                    _ => {}
+                }
+            } else if app.editing {
+                match key.code {
+                    KeyCode::Enter => {
+                        if let Some(idx) = app.list_state.selected() {
+                            app.set_value(idx, app.input.clone());
+                        }
+                        app.editing = false;
+                    }
+                    KeyCode::Esc => {
+                        app.editing = false;
+                    }
+                    KeyCode::Backspace => {
+                        app.input.pop();
+                    }
+                    KeyCode::Char(c) => {
+                        app.input.push(c);
+                    }
+                    _ => {}
+                }
+            } else {
+                match key.code {
+                    KeyCode::Char('q') => {
+                        if app.is_dirty {
+                            app.show_unsaved_quit_dialog = true;
+                            app.dialog_selection = false; // Default to No
+                        } else {
+                            return Ok(());
                         }
-                        KeyCode::Down => app.next(),
-                        KeyCode::Up => app.previous(),
-                        KeyCode::Enter => {
-                            if let Some(idx) = app.list_state.selected() {
-                                match idx {
-                                    0 | 1 => {
-                                        app.editing = true;
-                                        app.input = app.get_value(idx);
+                    }
+                    KeyCode::Char('s') => {
+                        save_edited(&app.path, &app.original, &app.torrent)?;
+                        app.is_dirty = false;
+                        app.show_save_quit_dialog = true;
+                        app.dialog_selection = true; // Default to Yes
+                    }
+                    KeyCode::Down => app.next(),
+                    KeyCode::Up => app.previous(),
+                    KeyCode::Enter => {
+                        if let Some(idx) = app.list_state.selected() {
+                            match idx {
+                                0 | 1 | 3 | 4 => {
+                                    app.editing = true;
+                                    app.input = app.get_value(idx);
+                                }
+                                2 => {
+                                    // Toggle Private
+                                    let old_val = app.torrent.info.private;
+                                    if app.torrent.info.private == Some(1) {
+                                        app.torrent.info.private = None;
+                                    } else {
+                                        app.torrent.info.private = Some(1);
                                     }
-                                    2 => {
-                                        // Toggle Private
-                                        let old_val = app.torrent.info.private;
-                                        if app.torrent.info.private == Some(1) {
-                                            app.torrent.info.private = None;
-                                        } else {
-                                            app.torrent.info.private = Some(1);
-                                        }
-                                        if old_val != app.torrent.info.private {
-                                            app.is_dirty = true;
-                                        }
+                                    if old_val != app.torrent.info.private {
+                                        app.is_dirty = true;
                                     }
-                                    _ => {}
                                 }
+                                _ => {}
                             }
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
             }
         }
@@ -335,9 +445,14 @@ fn ui(f: &mut Frame, app: &mut App) {
     f.render_stateful_widget(list, chunks[1], &mut app.list_state);
 
     if app.editing {
+        let title = match app.list_state.selected() {
+            Some(3) => "Edit Value (changing the source changes the info hash)",
+            Some(4) => "Edit Value (comma-separated URLs)",
+            _ => "Edit Value",
+        };
         let input = Paragraph::new(app.input.as_str())
             .style(Style::default().fg(Color::Yellow))
-            .block(Block::default().borders(Borders::ALL).title("Edit Value"));
+            .block(Block::default().borders(Borders::ALL).title(title));
         f.render_widget(input, chunks[2]);
     } else {
         let help_text = if app.is_dirty {
@@ -345,12 +460,23 @@ fn ui(f: &mut Frame, app: &mut App) {
         } else {
             "Use Arrow Keys to navigate, Enter to edit, s to save, q to quit"
         };
-        let help = Paragraph::new(help_text).style(Style::default().fg(if app.is_dirty {
-            Color::Red
-        } else {
-            Color::Gray
-        }));
-        f.render_widget(help, chunks[2]);
+        let mut lines = vec![Line::from(Span::styled(
+            help_text,
+            Style::default().fg(if app.is_dirty {
+                Color::Red
+            } else {
+                Color::Gray
+            }),
+        ))];
+        if app.info_hash_changed() {
+            lines.push(Line::from(Span::styled(
+                "Warning: the info hash has changed; clients and trackers will see a new torrent",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        f.render_widget(Paragraph::new(lines), chunks[2]);
     }
 
     if app.show_save_quit_dialog || app.show_unsaved_quit_dialog {
@@ -454,21 +580,32 @@ fn apply_changes(torrent: &mut Torrent, args: &EditArgs) -> bool {
     // Announce
     if let Some(ref new_announce) = args.replace_announce {
         println!("Replaced announce with: {}", new_announce);
-        torrent.announce = Some(new_announce.clone());
-        torrent.announce_list = Some(vec![vec![new_announce.clone()]]);
+        torrent.set_trackers(vec![vec![new_announce.clone()]]);
         modified = true;
     } else if !args.announce.is_empty() {
-        let mut list = torrent.announce_list.clone().unwrap_or_else(Vec::new);
         // Append as new tiers
         for url in &args.announce {
             println!("Added announce: {}", url);
-            list.push(vec![url.clone()]);
+            torrent.add_tracker_tier(vec![url.clone()]);
         }
-        // If main announce was empty, set it to the first one
-        if torrent.announce.is_none() && !list.is_empty() {
-            torrent.announce = Some(list[0][0].clone());
+        modified = true;
+    }
+
+    if let Some([old_host, new_host]) = args.replace_host.as_deref() {
+        let count = torrent.replace_tracker_host(old_host, new_host);
+        if count > 0 {
+            println!(
+                "Moved {} tracker(s) from {} to {}",
+                count, old_host, new_host
+            );
+            modified = true;
+        } else {
+            println!("No trackers on host: {}", old_host);
         }
-        torrent.announce_list = Some(list);
+    }
+
+    if args.announce_list_flatten && torrent.flatten_trackers() {
+        println!("Flattened trackers into a single tier.");
         modified = true;
     }
 
@@ -486,12 +623,10 @@ fn apply_changes(torrent: &mut Torrent, args: &EditArgs) -> bool {
             torrent.info.private = Some(1);
             modified = true;
         }
-    } else if args.public {
-        if torrent.info.private.is_some() {
-            println!("Removed private flag.");
-            torrent.info.private = None;
-            modified = true;
-        }
+    } else if args.public && torrent.info.private.is_some() {
+        println!("Removed private flag.");
+        torrent.info.private = None;
+        modified = true;
     }
 
     modified
@@ -521,23 +656,78 @@ mod tests {
                 x_cross_seed: None,
                 meta_version: None,
                 file_tree: None,
+                similar: None,
+                collections: None,
             },
             url_list: None,
             piece_layers: None,
+            publisher: None,
+            publisher_url: None,
         }
     }
 
+    #[test]
+    fn test_tui_source_and_web_seeds() {
+        let mut torrent = create_dummy_torrent();
+        torrent.info.source = Some("OLD".to_string());
+        torrent.url_list = Some(vec![
+            "https://a.example/".to_string(),
+            "https://b.example/".to_string(),
+        ]);
+        let mut app = App::new(torrent, Vec::new(), PathBuf::from("test.torrent"));
+
+        // Pre-filled from the torrent
+        assert_eq!(app.get_value(3), "OLD");
+        assert_eq!(app.get_value(4), "https://a.example/, https://b.example/");
+        assert!(!app.is_dirty && !app.info_hash_changed());
+
+        // Web seeds live outside the info dict
+        app.set_value(4, " https://c.example/ ,, https://d.example/".to_string());
+        assert_eq!(
+            app.torrent.url_list,
+            Some(vec![
+                "https://c.example/".to_string(),
+                "https://d.example/".to_string()
+            ])
+        );
+        assert!(app.is_dirty && !app.info_hash_changed());
+
+        app.set_value(3, " NEW ".to_string());
+        assert_eq!(app.torrent.info.source.as_deref(), Some("NEW"));
+        assert!(app.info_hash_changed());
+
+        // Clearing removes the keys entirely
+        app.set_value(3, String::new());
+        app.set_value(4, "  ".to_string());
+        assert_eq!(app.torrent.info.source, None);
+        assert_eq!(app.torrent.url_list, None);
+        assert_eq!(app.get_value(4), "");
+    }
+
+    #[test]
+    fn test_unchanged_value_keeps_tui_clean() {
+        let mut torrent = create_dummy_torrent();
+        torrent.info.source = Some("SRC".to_string());
+        let mut app = App::new(torrent, Vec::new(), PathBuf::from("test.torrent"));
+        app.set_value(3, "SRC".to_string());
+        assert!(!app.is_dirty);
+    }
+
     #[test]
     fn test_apply_changes_comment() {
         let mut torrent = create_dummy_torrent();
         let args = EditArgs {
-            torrent: PathBuf::from("test.torrent"),
+            torrent: Some(PathBuf::from("test.torrent")),
+            batch: None,
             announce: vec![],
             replace_announce: None,
+            replace_host: None,
+            announce_list_flatten: false,
             comment: Some("New Comment".to_string()),
             private: false,
             public: false,
             output: None,
+            validate: false,
         };
 
         assert!(apply_changes(&mut torrent, &args));
@@ -548,13 +738,17 @@ mod tests {
     fn test_apply_changes_announce_replace() {
         let mut torrent = create_dummy_torrent();
         let args = EditArgs {
-            torrent: PathBuf::from("test.torrent"),
+            torrent: Some(PathBuf::from("test.torrent")),
+            batch: None,
             announce: vec![],
             replace_announce: Some("http://new.tracker".to_string()),
+            replace_host: None,
+            announce_list_flatten: false,
             comment: None,
             private: false,
             public: false,
             output: None,
+            validate: false,
         };
 
         assert!(apply_changes(&mut torrent, &args));
@@ -566,13 +760,17 @@ mod tests {
     fn test_apply_changes_private() {
         let mut torrent = create_dummy_torrent();
         let args = EditArgs {
-            torrent: PathBuf::from("test.torrent"),
+            torrent: Some(PathBuf::from("test.torrent")),
+            batch: None,
             announce: vec![],
             replace_announce: None,
+            replace_host: None,
+            announce_list_flatten: false,
             comment: None,
             private: true,
             public: false,
             output: None,
+            validate: false,
         };
 
         assert!(apply_changes(&mut torrent, &args));
@@ -587,16 +785,61 @@ mod tests {
         let mut torrent = create_dummy_torrent();
         torrent.info.private = Some(1);
         let args = EditArgs {
-            torrent: PathBuf::from("test.torrent"),
+            torrent: Some(PathBuf::from("test.torrent")),
+            batch: None,
             announce: vec![],
             replace_announce: None,
+            replace_host: None,
+            announce_list_flatten: false,
             comment: None,
             private: false,
             public: true,
             output: None,
+            validate: false,
         };
 
         assert!(apply_changes(&mut torrent, &args));
         assert_eq!(torrent.info.private, None);
     }
+
+    #[test]
+    fn test_apply_changes_replace_host() {
+        let mut torrent = create_dummy_torrent();
+        torrent.set_trackers(vec![
+            vec!["https://old.example/abc123/announce".to_string()],
+            vec![
+                "udp://other.example:1337/announce".to_string(),
+                "http://old.example:8080/announce?passkey=def456".to_string(),
+            ],
+        ]);
+        let args = EditArgs {
+            torrent: Some(PathBuf::from("test.torrent")),
+            batch: None,
+            announce: vec![],
+            replace_announce: None,
+            replace_host: Some(vec!["old.example".to_string(), "new.example".to_string()]),
+            announce_list_flatten: false,
+            comment: None,
+            private: false,
+            public: false,
+            output: None,
+            validate: false,
+        };
+
+        assert!(apply_changes(&mut torrent, &args));
+        assert_eq!(
+            torrent.announce.as_deref(),
+            Some("https://new.example/abc123/announce")
+        );
+        assert_eq!(
+            torrent.announce_list.unwrap(),
+            vec![
+                vec!["https://new.example/abc123/announce".to_string()],
+                vec![
+                    "udp://other.example:1337/announce".to_string(),
+                    "http://new.example:8080/announce?passkey=def456".to_string(),
+                ],
+            ]
+        );
+    }
 }
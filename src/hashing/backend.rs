@@ -0,0 +1,42 @@
+/// The SHA-1/SHA-256 implementation hashing uses on this machine
+///
+/// The `sha1` and `sha2` crates pick CPU hash instructions at runtime when they're
+/// available and fall back to assembly (with the `asm` feature) or portable Rust
+/// otherwise. Both digests always use the same kind of implementation.
+pub fn hash_backend() -> &'static str {
+    if let Some(name) = hardware_backend() {
+        name
+    } else if cfg!(all(
+        feature = "asm",
+        any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "aarch64"
+        )
+    )) {
+        "assembly"
+    } else {
+        "portable"
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn hardware_backend() -> Option<&'static str> {
+    let shani = std::is_x86_feature_detected!("sha")
+        && std::is_x86_feature_detected!("sse2")
+        && std::is_x86_feature_detected!("ssse3")
+        && std::is_x86_feature_detected!("sse4.1");
+    shani.then_some("SHA-NI")
+}
+
+#[cfg(target_arch = "aarch64")]
+fn hardware_backend() -> Option<&'static str> {
+    // Only the asm build has an aarch64 backend that uses the crypto extensions
+    (cfg!(feature = "asm") && std::arch::is_aarch64_feature_detected!("sha2"))
+        .then_some("ARMv8 SHA extensions")
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn hardware_backend() -> Option<&'static str> {
+    None
+}
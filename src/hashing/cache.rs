@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::models::FileInfo;
+
+/// Frame header that every zstd stream starts with
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// zstd level used for compressed caches (favours speed, hashes compress poorly anyway)
+const ZSTD_LEVEL: i32 = 3;
+
+/// Cached V2 block hashes for a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    length: u64,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    /// Concatenated 32-byte SHA256 block hashes
+    hashes: serde_bytes::ByteBuf,
+}
+
+/// On-disk cache of V2 block hashes, keyed by absolute file path.
+///
+/// Block hashes don't depend on the piece length, so a cache stays valid across
+/// re-runs with different settings as long as the file's size and mtime are unchanged.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HashCache {
+    #[serde(default)]
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+impl HashCache {
+    /// Load a cache file, plain or zstd-compressed.
+    ///
+    /// A missing, truncated or corrupt cache is treated as empty, so the files are simply re-hashed.
+    pub fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|data| Self::decode(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        if data.starts_with(&ZSTD_MAGIC) {
+            let mut decoded = Vec::new();
+            zstd::stream::read::Decoder::new(data)?.read_to_end(&mut decoded)?;
+            Ok(serde_bencode::from_bytes(&decoded)?)
+        } else {
+            Ok(serde_bencode::from_bytes(data)?)
+        }
+    }
+
+    /// Write the cache, optionally zstd-compressed
+    pub fn save(&self, path: &Path, compress: bool) -> Result<()> {
+        let encoded = serde_bencode::to_bytes(self).context("Failed to serialize hash cache")?;
+        let data = if compress {
+            zstd::stream::encode_all(encoded.as_slice(), ZSTD_LEVEL)
+                .context("Failed to compress hash cache")?
+        } else {
+            encoded
+        };
+        fs::write(path, data)
+            .with_context(|| format!("Failed to write hash cache: {}", path.display()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return the cached block hashes if the file is unchanged since it was cached
+    pub fn lookup(&self, file: &FileInfo) -> Option<Vec<[u8; 32]>> {
+        let entry = self.entries.get(&cache_key(file))?;
+        let (secs, nanos) = file_mtime(&file.full_path)?;
+        if entry.length != file.len || entry.mtime_secs != secs || entry.mtime_nanos != nanos {
+            return None;
+        }
+        if entry.hashes.len() % 32 != 0 {
+            return None;
+        }
+        Some(
+            entry
+                .hashes
+                .chunks_exact(32)
+                .map(|c| {
+                    let mut hash = [0u8; 32];
+                    hash.copy_from_slice(c);
+                    hash
+                })
+                .collect(),
+        )
+    }
+
+    /// Record freshly computed block hashes for a file
+    pub fn insert(&mut self, file: &FileInfo, hashes: &[[u8; 32]]) {
+        let Some((mtime_secs, mtime_nanos)) = file_mtime(&file.full_path) else {
+            return;
+        };
+        self.entries.insert(
+            cache_key(file),
+            CacheEntry {
+                length: file.len,
+                mtime_secs,
+                mtime_nanos,
+                hashes: serde_bytes::ByteBuf::from(hashes.concat()),
+            },
+        );
+    }
+}
+
+fn cache_key(file: &FileInfo) -> String {
+    if file.data_offset == 0 {
+        file.full_path.to_string_lossy().into_owned()
+    } else {
+        // Archive entries share the archive's path
+        format!("{}@{}", file.full_path.to_string_lossy(), file.data_offset)
+    }
+}
+
+fn file_mtime(path: &Path) -> Option<(u64, u32)> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn file_info(path: PathBuf, len: u64) -> FileInfo {
+        FileInfo {
+            path: PathBuf::from(path.file_name().unwrap()),
+            full_path: path,
+            len,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_compressed_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_path = dir.path().join("data.bin");
+        fs::write(&data_path, b"cached content").unwrap();
+        let file = file_info(data_path, 14);
+
+        let hashes = vec![[7u8; 32], [9u8; 32]];
+        let mut cache = HashCache::default();
+        cache.insert(&file, &hashes);
+
+        let cache_path = dir.path().join("hashes.cache");
+        cache.save(&cache_path, true).unwrap();
+        assert!(fs::read(&cache_path).unwrap().starts_with(&ZSTD_MAGIC));
+
+        let loaded = HashCache::load(&cache_path);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.lookup(&file), Some(hashes));
+    }
+
+    #[test]
+    fn test_corrupt_cache_is_a_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_path = dir.path().join("data.bin");
+        fs::write(&data_path, b"cached content").unwrap();
+        let file = file_info(data_path, 14);
+
+        let mut cache = HashCache::default();
+        cache.insert(&file, &[[1u8; 32]; 64]);
+        let cache_path = dir.path().join("hashes.cache");
+        cache.save(&cache_path, true).unwrap();
+
+        // Truncate the compressed stream halfway through
+        let data = fs::read(&cache_path).unwrap();
+        fs::write(&cache_path, &data[..data.len() / 2]).unwrap();
+        assert!(HashCache::load(&cache_path).is_empty());
+
+        // Garbage that isn't bencode at all
+        fs::write(&cache_path, b"not a cache").unwrap();
+        assert!(HashCache::load(&cache_path).is_empty());
+
+        // Missing file
+        assert!(HashCache::load(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_changed_file_is_a_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_path = dir.path().join("data.bin");
+        fs::write(&data_path, b"cached content").unwrap();
+
+        let mut cache = HashCache::default();
+        cache.insert(&file_info(data_path.clone(), 14), &[[3u8; 32]]);
+
+        fs::write(&data_path, b"new").unwrap();
+        assert!(cache.lookup(&file_info(data_path, 3)).is_none());
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Cooperative cancellation for long hashing runs, e.g. from a Ctrl-C handler.
+/// Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask hashing to stop; work already in flight finishes first
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::{
+        HashError, hash_v1_pieces, hash_v1_pieces_cancellable, hash_v2_files_cancellable,
+    };
+    use crate::models::FileInfo;
+    use std::path::PathBuf;
+
+    fn test_files(dir: &tempfile::TempDir) -> Vec<FileInfo> {
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, vec![4u8; 100_000]).unwrap();
+        vec![FileInfo {
+            path: PathBuf::from("data.bin"),
+            full_path: path,
+            len: 100_000,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        }]
+    }
+
+    #[test]
+    fn test_cancelled_hashing_returns_partial_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = test_files(&dir);
+
+        // Not cancelled: every piece is hashed, matching the plain function
+        let cancel = Cancellation::new();
+        let hashes = hash_v1_pieces_cancellable(&files, 1 << 15, None, &cancel).unwrap();
+        assert_eq!(hashes.len(), 4);
+        assert_eq!(
+            hashes.iter().flatten().flatten().copied().collect::<Vec<u8>>(),
+            hash_v1_pieces(&files, 1 << 15, None).unwrap()
+        );
+
+        // Cancelled (a clone shares the flag): no pieces hashed, but no error either
+        cancel.clone().cancel();
+        let hashes = hash_v1_pieces_cancellable(&files, 1 << 15, None, &cancel).unwrap();
+        assert_eq!(hashes.len(), 4);
+        assert!(hashes.iter().all(Option::is_none));
+
+        let err = hash_v2_files_cancellable(&files, 1 << 15, true, None, &cancel)
+            .err()
+            .unwrap();
+        assert_eq!(err.downcast_ref::<HashError>(), Some(&HashError::Cancelled));
+    }
+}
@@ -1,25 +1,112 @@
 use anyhow::{Context, Result};
 use std::cmp::{max, min};
+use std::fmt;
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::path::PathBuf;
 
 use crate::models::FileInfo;
 
+/// Hashing failures callers may want to tell apart (via `anyhow::Error::downcast_ref`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashError {
+    /// The file's size no longer matches what the scan recorded
+    FileChangedDuringHashing { path: PathBuf },
+    /// Hashing was stopped through a [`Cancellation`](super::Cancellation)
+    Cancelled,
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashError::FileChangedDuringHashing { path } => write!(
+                f,
+                "File changed while it was being hashed: {} (re-run once it is no longer being written)",
+                path.display()
+            ),
+            HashError::Cancelled => write!(f, "Hashing was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for HashError {}
+
+fn changed(file: &FileInfo) -> anyhow::Error {
+    HashError::FileChangedDuringHashing {
+        path: file.full_path.clone(),
+    }
+    .into()
+}
+
+/// Open a source file positioned at `pos`, failing if its size differs from the scan
+pub(crate) fn open_at(file: &FileInfo, pos: u64) -> Result<File> {
+    let mut f = File::open(&file.full_path)
+        .with_context(|| format!("Failed to open file: {}", file.full_path.display()))?;
+    let actual = f
+        .metadata()
+        .with_context(|| format!("Failed to stat file: {}", file.full_path.display()))?
+        .len();
+    // Archive entries only need the archive to still contain them
+    let size_ok = if file.data_offset == 0 {
+        actual == file.len
+    } else {
+        actual >= file.data_offset + file.len
+    };
+    if !size_ok {
+        return Err(changed(file));
+    }
+    f.seek(SeekFrom::Start(pos))?;
+    Ok(f)
+}
+
+/// `read_exact` that reports a short read as the file having changed
+pub(crate) fn read_exact_at(f: &mut File, buf: &mut [u8], file: &FileInfo) -> Result<()> {
+    f.read_exact(buf).map_err(|e| {
+        if e.kind() == ErrorKind::UnexpectedEof {
+            changed(file)
+        } else {
+            anyhow::Error::new(e)
+                .context(format!("Failed to read file: {}", file.full_path.display()))
+        }
+    })
+}
+
+/// Allocate a zeroed buffer for a piece of `len` bytes. A piece that doesn't fit in
+/// `usize` (over 4 GiB on 32-bit targets) or can't be allocated is an error rather
+/// than a truncated buffer or an abort.
+pub(crate) fn piece_buffer(len: u64) -> Result<Vec<u8>> {
+    let size = usize::try_from(len).map_err(|_| {
+        anyhow::anyhow!(
+            "A {} byte piece doesn't fit in memory on this platform; use a smaller piece length",
+            len
+        )
+    })?;
+    let mut buffer = Vec::new();
+    buffer.try_reserve_exact(size).with_context(|| {
+        format!(
+            "Failed to allocate a {} byte piece buffer; use a smaller piece length",
+            len
+        )
+    })?;
+    buffer.resize(size, 0);
+    Ok(buffer)
+}
+
 /// Read data for a specific piece, potentially spanning multiple files
 pub fn read_piece_data(
     files: &[FileInfo],
-    piece_index: usize,
+    piece_index: u64,
     piece_length: u64,
     total_len: u64,
 ) -> Result<Vec<u8>> {
-    let global_start = piece_index as u64 * piece_length;
+    let global_start = piece_index * piece_length;
     let expected_len = min(piece_length, total_len.saturating_sub(global_start));
     if expected_len == 0 {
         return Ok(Vec::new());
     }
     let global_end = global_start + expected_len;
 
-    let mut buffer = vec![0u8; expected_len as usize];
+    let mut buffer = piece_buffer(expected_len)?;
 
     // Find the first file that overlaps with this piece
     // We want the first file where end_offset > global_start
@@ -34,6 +121,7 @@ pub fn read_piece_data(
         let overlap_end = min(global_end, file.start_offset + file.len);
 
         if overlap_end > overlap_start {
+            // Both are within the buffer, whose length fits in usize
             let buf_start = (overlap_start - global_start) as usize;
             let buf_end = (overlap_end - global_start) as usize;
 
@@ -42,14 +130,143 @@ pub fn read_piece_data(
                 continue;
             }
 
-            let file_seek_pos = overlap_start - file.start_offset;
+            let file_seek_pos = file.data_offset + overlap_start - file.start_offset;
 
-            let mut f = File::open(&file.full_path).with_context(|| {
-                format!("Failed to open file: {}", file.full_path.display())
-            })?;
-            f.seek(SeekFrom::Start(file_seek_pos))?;
-            f.read_exact(&mut buffer[buf_start..buf_end])?;
+            let mut f = open_at(file, file_seek_pos)?;
+            read_exact_at(&mut f, &mut buffer[buf_start..buf_end], file)?;
         }
     }
     Ok(buffer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::{hash_v1_pieces, hash_v2_files};
+
+    #[test]
+    fn test_truncated_file_is_reported_not_panicked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("live.bin");
+        std::fs::write(&path, vec![1u8; 100_000]).unwrap();
+
+        // Scanned at 100 KB, then truncated before hashing reached it
+        let files = vec![FileInfo {
+            path: PathBuf::from("live.bin"),
+            full_path: path.clone(),
+            len: 100_000,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        }];
+        std::fs::write(&path, vec![1u8; 40_000]).unwrap();
+
+        let expected = HashError::FileChangedDuringHashing { path };
+        let v1 = hash_v1_pieces(&files, 1 << 15, None).unwrap_err();
+        assert_eq!(v1.downcast_ref::<HashError>(), Some(&expected));
+        let v2 = hash_v2_files(&files, 1 << 15, true, None).err().unwrap();
+        assert_eq!(v2.downcast_ref::<HashError>(), Some(&expected));
+    }
+
+    #[test]
+    fn test_unreadable_file_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gone.bin");
+        std::fs::write(&path, vec![7u8; 50_000]).unwrap();
+        let files = vec![FileInfo {
+            path: PathBuf::from("gone.bin"),
+            full_path: path.clone(),
+            len: 50_000,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        }];
+        // Deleted between scan and hash: every worker hits an open error
+        std::fs::remove_file(&path).unwrap();
+
+        let v1 = hash_v1_pieces(&files, 1 << 14, None).unwrap_err();
+        assert!(v1.to_string().starts_with("Failed to open file"));
+        let v2 = hash_v2_files(&files, 1 << 14, false, None).err().unwrap();
+        assert!(v2.to_string().starts_with("Failed to open file"));
+    }
+
+    #[test]
+    fn test_piece_beyond_4_gib_is_read_at_the_right_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tail.bin");
+        let data: Vec<u8> = (0..100u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        // 5 GiB of padding (never read from disk) pushes the file past u32 offsets
+        let padding_len = 5u64 << 30;
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from(".pad/5368709120"),
+                full_path: PathBuf::new(),
+                len: padding_len,
+                start_offset: 0,
+                is_padding: true,
+                data_offset: 0,
+            },
+            FileInfo {
+                path: PathBuf::from("tail.bin"),
+                full_path: path,
+                len: 100,
+                start_offset: padding_len,
+                is_padding: false,
+                data_offset: 0,
+            },
+        ];
+        let piece_length = 1 << 14;
+        let total_len = padding_len + 100;
+
+        let last = padding_len / piece_length;
+        assert!(last * piece_length > u64::from(u32::MAX));
+        let piece = read_piece_data(&files, last, piece_length, total_len).unwrap();
+        assert_eq!(piece, data);
+        let padding = read_piece_data(&files, last - 1, piece_length, total_len).unwrap();
+        assert_eq!(padding, vec![0u8; piece_length as usize]);
+    }
+
+    #[test]
+    fn test_oversized_piece_buffer_is_an_error() {
+        // Too big for usize on 32-bit targets, too big to allocate on 64-bit ones
+        let err = piece_buffer(u64::MAX).unwrap_err();
+        assert!(err.to_string().contains("use a smaller piece length"));
+        assert_eq!(piece_buffer(3).unwrap(), [0, 0, 0]);
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn test_piece_over_4_gib_does_not_truncate_on_32_bit() {
+        let err = piece_buffer(1 << 32).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("doesn't fit in memory on this platform")
+        );
+    }
+
+    #[test]
+    fn test_short_read_is_reported_as_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("short.bin");
+        std::fs::write(&path, b"0123456789").unwrap();
+        let file = FileInfo {
+            path: PathBuf::from("short.bin"),
+            full_path: path,
+            len: 10,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        };
+
+        // The size check passed, but the data ran out mid-read
+        let mut f = open_at(&file, 6).unwrap();
+        let mut buf = [0u8; 8];
+        let err = read_exact_at(&mut f, &mut buf, &file).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<HashError>(),
+            Some(HashError::FileChangedDuringHashing { .. })
+        ));
+    }
+}
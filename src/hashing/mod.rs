@@ -1,6 +1,20 @@
+mod backend;
+mod cache;
+mod cancel;
 pub(crate) mod io;
+mod stream;
 mod v1;
 mod v2;
 
-pub use v1::hash_v1_pieces;
-pub use v2::{compute_merkle_root, hash_v2_files, V2HashResult};
+pub use backend::hash_backend;
+pub use cache::HashCache;
+pub use cancel::Cancellation;
+pub use io::HashError;
+pub use stream::{StreamHashResult, StreamHasher, StreamedFile, hash_reader};
+pub use v1::{hash_v1_pieces, hash_v1_pieces_cancellable};
+pub(crate) use v2::{ChunkSize, hash_v2};
+pub use v2::{
+    DEFAULT_V2_CHUNK_BLOCKS, MAX_V2_CHUNK_BLOCKS, MIN_V2_CHUNK_BLOCKS,
+    SINGLE_FILE_CHUNKS_PER_THREAD, V2HashResult, compute_merkle_root, hash_v2_files,
+    hash_v2_files_cached, hash_v2_files_cancellable, hash_v2_files_chunked,
+};
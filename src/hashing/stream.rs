@@ -0,0 +1,260 @@
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+use log::info;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::io::{ErrorKind, Read};
+
+use super::v2::{compute_merkle_root, piece_layer};
+use super::{Cancellation, HashError};
+use crate::config::BLOCK_SIZE;
+
+/// Hashes of a single file read front to back from a stream
+pub struct StreamHashResult {
+    /// Bytes read from the stream
+    pub length: u64,
+    /// Concatenated V1 piece hashes (empty unless V1 hashing was requested)
+    pub pieces: Vec<u8>,
+    /// V2 pieces root (`None` unless V2 hashing was requested)
+    pub pieces_root: Option<[u8; 32]>,
+    /// V2 piece layer, present when the file spans more than one piece
+    pub piece_layer: Option<Vec<u8>>,
+}
+
+/// Hash a single file from `reader` in one sequential pass, computing V1 pieces
+/// and/or the V2 merkle tree as requested
+///
+/// For sources that can't be read at random offsets, such as an HTTP response body.
+/// The results match [`hash_v1_pieces`](super::hash_v1_pieces) and
+/// [`hash_v2_files`](super::hash_v2_files) for the same bytes on disk.
+pub fn hash_reader(
+    reader: impl Read,
+    piece_length: u64,
+    v1: bool,
+    v2: bool,
+    pb: Option<ProgressBar>,
+) -> Result<StreamHashResult> {
+    info!("Hashing stream...");
+
+    let mut hasher = StreamHasher::new(piece_length, v1, v2, pb);
+    let file = hasher.add_file(reader)?;
+    Ok(StreamHashResult {
+        length: file.length,
+        pieces: hasher.finish(),
+        pieces_root: file.pieces_root,
+        piece_layer: file.piece_layer,
+    })
+}
+
+/// V2 hashes of one file fed through a [`StreamHasher`]
+pub struct StreamedFile {
+    /// Bytes read for the file
+    pub length: u64,
+    /// V2 pieces root (`None` unless V2 hashing was requested)
+    pub pieces_root: Option<[u8; 32]>,
+    /// V2 piece layer, present when the file spans more than one piece
+    pub piece_layer: Option<Vec<u8>>,
+}
+
+/// Hashes files read front to back, one after another, as a single V1 piece
+/// stream with a V2 merkle tree per file
+///
+/// For content that can only be read in order, such as entries of a compressed
+/// archive. Padding between files is fed with [`add_padding`](Self::add_padding).
+pub struct StreamHasher {
+    piece_length: u64,
+    v1: bool,
+    v2: bool,
+    pieces: Vec<u8>,
+    piece_hasher: Sha1,
+    piece_filled: u64,
+    buffer: Vec<u8>,
+    pb: Option<ProgressBar>,
+    cancel: Option<Cancellation>,
+}
+
+impl StreamHasher {
+    pub fn new(piece_length: u64, v1: bool, v2: bool, pb: Option<ProgressBar>) -> Self {
+        Self {
+            piece_length,
+            v1,
+            v2,
+            pieces: Vec::new(),
+            piece_hasher: Sha1::new(),
+            piece_filled: 0,
+            buffer: vec![0u8; BLOCK_SIZE],
+            pb,
+            cancel: None,
+        }
+    }
+
+    /// Fail with [`HashError::Cancelled`] once `cancel` is triggered
+    pub fn with_cancellation(mut self, cancel: Cancellation) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Hash the next file, reading `reader` to the end
+    pub fn add_file(&mut self, mut reader: impl Read) -> Result<StreamedFile> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let mut length = 0u64;
+        let mut block_hashes: Vec<[u8; 32]> = Vec::new();
+
+        loop {
+            if self.cancel.as_ref().is_some_and(Cancellation::is_cancelled) {
+                return Err(HashError::Cancelled.into());
+            }
+            let n = read_block(&mut reader, &mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            let block = &buffer[..n];
+            length += n as u64;
+
+            if self.v1 {
+                self.update_v1(block);
+            }
+            if self.v2 {
+                block_hashes.push(Sha256::digest(block).into());
+            }
+
+            if let Some(ref pb) = self.pb {
+                pb.inc(n as u64);
+            }
+            if n < BLOCK_SIZE {
+                break;
+            }
+        }
+        self.buffer = buffer;
+
+        let (pieces_root, piece_layer) = if self.v2 {
+            let (root, layers) = compute_merkle_root(block_hashes);
+            (Some(root), piece_layer(&layers, length, self.piece_length))
+        } else {
+            (None, None)
+        };
+
+        Ok(StreamedFile {
+            length,
+            pieces_root,
+            piece_layer,
+        })
+    }
+
+    /// Feed `len` zero bytes of padding into the V1 pieces; V2 doesn't hash padding
+    pub fn add_padding(&mut self, mut len: u64) {
+        if !self.v1 {
+            return;
+        }
+        let zeros = [0u8; BLOCK_SIZE];
+        while len > 0 {
+            let n = usize::try_from(len).unwrap_or(usize::MAX).min(BLOCK_SIZE);
+            self.update_v1(&zeros[..n]);
+            len -= n as u64;
+            if let Some(ref pb) = self.pb {
+                pb.inc(n as u64);
+            }
+        }
+    }
+
+    /// The concatenated V1 piece hashes, including a final partial piece (empty
+    /// unless V1 hashing was requested)
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.v1 && self.piece_filled > 0 {
+            self.pieces
+                .extend_from_slice(&self.piece_hasher.finalize_reset());
+        }
+        self.pieces
+    }
+
+    fn update_v1(&mut self, data: &[u8]) {
+        // A block can straddle pieces when pieces are smaller than a block
+        let mut rest = data;
+        while !rest.is_empty() {
+            // What's left of the piece can exceed usize on 32-bit targets
+            let piece_left =
+                usize::try_from(self.piece_length - self.piece_filled).unwrap_or(usize::MAX);
+            let take = rest.len().min(piece_left);
+            self.piece_hasher.update(&rest[..take]);
+            self.piece_filled += take as u64;
+            rest = &rest[take..];
+            if self.piece_filled == self.piece_length {
+                self.pieces
+                    .extend_from_slice(&self.piece_hasher.finalize_reset());
+                self.piece_filled = 0;
+            }
+        }
+    }
+}
+
+/// Fill `buf` from `reader`, stopping short only at end of stream
+fn read_block(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e).context("Failed to read from stream"),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::{hash_v1_pieces, hash_v2_files};
+    use crate::models::FileInfo;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    /// A reader that hands out at most 1000 bytes per call, like a network socket
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(1000);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_hash_reader_matches_file_hashing() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut tmp = tempfile::NamedTempFile::new().unwrap();
+        tmp.write_all(&data).unwrap();
+        let files = vec![FileInfo {
+            path: PathBuf::from("data.bin"),
+            full_path: tmp.path().to_path_buf(),
+            len: data.len() as u64,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        }];
+
+        for piece_length in [1u64 << 14, 1 << 15] {
+            let streamed = hash_reader(Trickle(&data), piece_length, true, true, None).unwrap();
+            assert_eq!(streamed.length, data.len() as u64);
+            assert_eq!(
+                streamed.pieces,
+                hash_v1_pieces(&files, piece_length, None).unwrap()
+            );
+
+            let v2 = hash_v2_files(&files, piece_length, true, None).unwrap();
+            let crate::models::Node::File(node) = &v2.file_tree["data.bin"] else {
+                panic!("Expected file node");
+            };
+            let root = streamed.pieces_root.unwrap();
+            assert_eq!(node.metadata.pieces_root.as_ref(), &root);
+            assert_eq!(
+                v2.piece_layers
+                    .get(&serde_bytes::ByteBuf::from(root.to_vec()))
+                    .map(|layer| layer.to_vec()),
+                streamed.piece_layer
+            );
+        }
+    }
+}
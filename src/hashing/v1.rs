@@ -2,29 +2,42 @@ use anyhow::Result;
 use rayon::prelude::*;
 use sha1::{Digest, Sha1};
 use indicatif::ProgressBar;
+use log::info;
 
 use crate::models::FileInfo;
+use super::Cancellation;
 use super::io::read_piece_data;
 
 /// Hash all pieces using V1 SHA1 algorithm (piece-parallel)
 pub fn hash_v1_pieces(
     files: &[FileInfo],
     piece_length: u64,
-    verbose: bool,
     pb: Option<ProgressBar>,
 ) -> Result<Vec<u8>> {
-    if verbose && pb.is_none() {
-        println!("  Computing V1 (SHA1) hashes...");
-    }
+    let hashes = hash_v1_pieces_cancellable(files, piece_length, pb, &Cancellation::new())?;
+    Ok(hashes.into_iter().flatten().flatten().collect())
+}
+
+/// Same as [`hash_v1_pieces`], but stops once `cancel` is triggered. Pieces that
+/// weren't hashed by then come back as `None`, so callers can still use the rest.
+pub fn hash_v1_pieces_cancellable(
+    files: &[FileInfo],
+    piece_length: u64,
+    pb: Option<ProgressBar>,
+    cancel: &Cancellation,
+) -> Result<Vec<Option<[u8; 20]>>> {
+    info!("Computing V1 (SHA1) hashes...");
 
     let total_len: u64 = files.iter().map(|f| f.len).sum();
-    let num_pieces = (total_len + piece_length - 1) / piece_length;
+    let num_pieces = total_len.div_ceil(piece_length);
 
     let results: Vec<_> = (0..num_pieces)
         .into_par_iter()
-        .map(|piece_idx| {
-            let data = read_piece_data(files, piece_idx as usize, piece_length, total_len)
-                .expect("Failed to read piece data");
+        .map(|piece_idx| -> Result<Option<[u8; 20]>> {
+            if cancel.is_cancelled() {
+                return Ok(None);
+            }
+            let data = read_piece_data(files, piece_idx, piece_length, total_len)?;
 
             let mut hasher = Sha1::new();
             hasher.update(&data);
@@ -36,13 +49,9 @@ pub fn hash_v1_pieces(
 
             let mut v1_hash_arr = [0u8; 20];
             v1_hash_arr.copy_from_slice(&v1_hash);
-            v1_hash_arr
+            Ok(Some(v1_hash_arr))
         })
-        .collect();
+        .collect::<Result<_>>()?;
 
-    let mut bytes = Vec::with_capacity((num_pieces as usize) * 20);
-    for hash in results {
-        bytes.extend_from_slice(&hash);
-    }
-    Ok(bytes)
+    Ok(results)
 }
@@ -2,11 +2,12 @@ use anyhow::Result;
 use rayon::prelude::*;
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
 use std::path::PathBuf;
 use indicatif::ProgressBar;
+use log::{debug, info};
 
+use super::io::{HashError, open_at, read_exact_at};
+use super::{Cancellation, HashCache};
 use crate::config::BLOCK_SIZE;
 use crate::models::{FileInfo, FileMetadata, FileNode, Node};
 use crate::tree::insert_into_tree;
@@ -28,7 +29,6 @@ struct FileResult {
 /// Each chunk is processed sequentially with one file handle
 struct ChunkWork {
     file_index: usize,
-    file_path: PathBuf,
     start_offset: u64,
     chunk_size: u64,
     start_block_index: usize,
@@ -41,73 +41,215 @@ struct ChunkResult {
     hashes: Vec<[u8; 32]>,
 }
 
-/// Chunk size in bytes (128 blocks = 2MB)
+/// Default work chunk size in blocks (128 blocks = 2MB)
 /// This balances parallelism granularity with file I/O overhead
-const CHUNK_SIZE_BLOCKS: usize = 128;
-const CHUNK_SIZE_BYTES: u64 = (CHUNK_SIZE_BLOCKS * BLOCK_SIZE) as u64;
+pub const DEFAULT_V2_CHUNK_BLOCKS: usize = 128;
+/// Smallest accepted chunk size: one 16 KiB block per read
+pub const MIN_V2_CHUNK_BLOCKS: usize = 1;
+/// Largest accepted chunk size (64MB); bigger chunks starve the thread pool
+pub const MAX_V2_CHUNK_BLOCKS: usize = 4096;
+/// When a single file is all there is to hash, default-sized chunks are shrunk until
+/// each thread gets at least this many, so a file of a few chunks still keeps every
+/// core busy and progress moves evenly
+pub const SINGLE_FILE_CHUNKS_PER_THREAD: usize = 4;
 
 /// Hash all files using V2 SHA256 algorithm with merkle trees (block-parallel)
 pub fn hash_v2_files(
     files: &[FileInfo],
     piece_length: u64,
-    verbose: bool,
     is_single_file: bool,
     pb: Option<ProgressBar>,
 ) -> Result<V2HashResult> {
-    if verbose && pb.is_none() {
-        println!("  Computing V2 (SHA256) hashes and Merkle trees...");
-    }
+    hash_v2_files_cached(files, piece_length, is_single_file, pb, None)
+}
 
-    let layer_index = if piece_length > BLOCK_SIZE as u64 {
-        piece_length.trailing_zeros() as usize - BLOCK_SIZE.trailing_zeros() as usize
-    } else {
-        0
-    };
+/// Same as [`hash_v2_files`], but reuses block hashes from `cache` for unchanged files
+/// and records the hashes of everything it had to read
+pub fn hash_v2_files_cached(
+    files: &[FileInfo],
+    piece_length: u64,
+    is_single_file: bool,
+    pb: Option<ProgressBar>,
+    cache: Option<&mut HashCache>,
+) -> Result<V2HashResult> {
+    hash_v2(
+        files,
+        piece_length,
+        is_single_file,
+        pb,
+        cache,
+        None,
+        ChunkSize::Auto(SINGLE_FILE_CHUNKS_PER_THREAD),
+    )
+}
 
-    // Step 1: Build global work list of chunks across all files
-    let mut work_list: Vec<ChunkWork> = Vec::new();
+/// Same as [`hash_v2_files_cached`], but splits files into work items of
+/// `chunk_blocks` blocks instead of [`DEFAULT_V2_CHUNK_BLOCKS`]. The size is kept
+/// as given, even for a lone file that [`SINGLE_FILE_CHUNKS_PER_THREAD`] would split
+/// further.
+///
+/// Only throughput depends on the chunk size; the hashes are identical. Values
+/// outside [`MIN_V2_CHUNK_BLOCKS`]..=[`MAX_V2_CHUNK_BLOCKS`] are clamped.
+pub fn hash_v2_files_chunked(
+    files: &[FileInfo],
+    piece_length: u64,
+    is_single_file: bool,
+    pb: Option<ProgressBar>,
+    cache: Option<&mut HashCache>,
+    chunk_blocks: usize,
+) -> Result<V2HashResult> {
+    hash_v2(
+        files,
+        piece_length,
+        is_single_file,
+        pb,
+        cache,
+        None,
+        ChunkSize::Blocks(chunk_blocks),
+    )
+}
 
-    for (file_index, file) in files.iter().enumerate() {
-        if file.is_padding {
-            continue;
-        }
+/// Same as [`hash_v2_files`], but fails with [`HashError::Cancelled`] once `cancel`
+/// is triggered. A file's merkle root needs all of its blocks, so nothing partial
+/// is returned.
+pub fn hash_v2_files_cancellable(
+    files: &[FileInfo],
+    piece_length: u64,
+    is_single_file: bool,
+    pb: Option<ProgressBar>,
+    cancel: &Cancellation,
+) -> Result<V2HashResult> {
+    hash_v2(
+        files,
+        piece_length,
+        is_single_file,
+        pb,
+        None,
+        Some(cancel),
+        ChunkSize::Auto(SINGLE_FILE_CHUNKS_PER_THREAD),
+    )
+}
 
-        if file.len == 0 {
-            // Empty files will be handled separately, contribute no chunks
-            continue;
-        }
+/// How files are split into work items
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ChunkSize {
+    /// [`DEFAULT_V2_CHUNK_BLOCKS`], shrunk for a lone file until each thread gets
+    /// this many work items (normally [`SINGLE_FILE_CHUNKS_PER_THREAD`])
+    Auto(usize),
+    /// Exactly this many blocks, clamped to the accepted range
+    Blocks(usize),
+}
 
-        // Split file into chunks of CHUNK_SIZE_BYTES
+/// Bytes per work item when `file_len` is the only thing to hash on `threads`
+/// threads: small enough for `per_thread` items per thread, but never below one
+/// block or above `chunk_size_bytes`. Always a whole number of blocks, so block
+/// indices line up across chunks.
+fn single_file_chunk_size(
+    file_len: u64,
+    chunk_size_bytes: u64,
+    threads: usize,
+    per_thread: usize,
+) -> u64 {
+    let block = BLOCK_SIZE as u64;
+    let target_chunks = (threads * per_thread).max(1) as u64;
+    let blocks_per_chunk = file_len.div_ceil(block).div_ceil(target_chunks).max(1);
+    (blocks_per_chunk * block).min(chunk_size_bytes)
+}
+
+/// Split each file into work items of `chunk_size_bytes` (a multiple of the block
+/// size), in file order
+fn plan_chunks(files: &[FileInfo], to_hash: &[usize], chunk_size_bytes: u64) -> Vec<ChunkWork> {
+    let mut work_list = Vec::new();
+    for &file_index in to_hash {
+        let file = &files[file_index];
         let mut offset = 0u64;
         let mut block_index = 0usize;
 
         while offset < file.len {
             let remaining = file.len - offset;
-            let chunk_size = std::cmp::min(CHUNK_SIZE_BYTES, remaining);
+            let chunk_size = std::cmp::min(chunk_size_bytes, remaining);
 
             work_list.push(ChunkWork {
                 file_index,
-                file_path: file.full_path.clone(),
-                start_offset: offset,
+                start_offset: file.data_offset + offset,
                 chunk_size,
                 start_block_index: block_index,
             });
 
-            let blocks_in_chunk = ((chunk_size + BLOCK_SIZE as u64 - 1) / BLOCK_SIZE as u64) as usize;
+            let blocks_in_chunk = chunk_size.div_ceil(BLOCK_SIZE as u64) as usize;
             block_index += blocks_in_chunk;
             offset += chunk_size;
         }
     }
+    work_list
+}
+
+pub(crate) fn hash_v2(
+    files: &[FileInfo],
+    piece_length: u64,
+    is_single_file: bool,
+    pb: Option<ProgressBar>,
+    mut cache: Option<&mut HashCache>,
+    cancel: Option<&Cancellation>,
+    chunk_size: ChunkSize,
+) -> Result<V2HashResult> {
+    info!("Computing V2 (SHA256) hashes and Merkle trees...");
+    let chunk_blocks = match chunk_size {
+        ChunkSize::Auto(_) => DEFAULT_V2_CHUNK_BLOCKS,
+        ChunkSize::Blocks(blocks) => blocks,
+    };
+    let chunk_size_bytes =
+        (chunk_blocks.clamp(MIN_V2_CHUNK_BLOCKS, MAX_V2_CHUNK_BLOCKS) * BLOCK_SIZE) as u64;
+
+    // Step 1: Build global work list of chunks across all files
+    let mut to_hash: Vec<usize> = Vec::new();
+    let mut cached_hashes: BTreeMap<usize, Vec<[u8; 32]>> = BTreeMap::new();
+
+    for (file_index, file) in files.iter().enumerate() {
+        if file.is_padding {
+            continue;
+        }
+
+        if file.len == 0 {
+            // Empty files will be handled separately, contribute no chunks
+            continue;
+        }
+
+        if let Some(hashes) = cache.as_deref().and_then(|c| c.lookup(file)) {
+            if let Some(ref pb) = pb {
+                pb.inc(file.len);
+            }
+            cached_hashes.insert(file_index, hashes);
+            continue;
+        }
+        to_hash.push(file_index);
+    }
+
+    let chunk_size_bytes = match (chunk_size, to_hash.as_slice()) {
+        (ChunkSize::Auto(per_thread), [only]) => {
+            let threads = rayon::current_num_threads();
+            let size =
+                single_file_chunk_size(files[*only].len, chunk_size_bytes, threads, per_thread);
+            debug!(
+                "Single file to hash: {} byte chunks across {} threads",
+                size, threads
+            );
+            size
+        }
+        _ => chunk_size_bytes,
+    };
+    let work_list = plan_chunks(files, &to_hash, chunk_size_bytes);
 
     // Step 2: Process all chunks in parallel
     let chunk_results: Vec<ChunkResult> = work_list
         .par_iter()
-        .map(|work| {
+        .map(|work| -> Result<ChunkResult> {
+            if cancel.is_some_and(Cancellation::is_cancelled) {
+                return Err(HashError::Cancelled.into());
+            }
             // Open file and seek to chunk start
-            let mut file = File::open(&work.file_path)
-                .expect("Failed to open file for V2 hashing");
-            file.seek(SeekFrom::Start(work.start_offset))
-                .expect("Failed to seek in file");
+            let source = &files[work.file_index];
+            let mut file = open_at(source, work.start_offset)?;
 
             // Read and hash all blocks in this chunk sequentially
             let mut hashes = Vec::new();
@@ -116,8 +258,7 @@ pub fn hash_v2_files(
 
             while remaining > 0 {
                 let to_read = std::cmp::min(BLOCK_SIZE as u64, remaining) as usize;
-                file.read_exact(&mut buffer[..to_read])
-                    .expect("Failed to read file block");
+                read_exact_at(&mut file, &mut buffer[..to_read], source)?;
 
                 let mut hasher = Sha256::new();
                 hasher.update(&buffer[..to_read]);
@@ -130,19 +271,19 @@ pub fn hash_v2_files(
                 remaining -= to_read as u64;
             }
 
-            ChunkResult {
+            Ok(ChunkResult {
                 file_index: work.file_index,
                 start_block_index: work.start_block_index,
                 hashes,
-            }
+            })
         })
-        .collect();
+        .collect::<Result<_>>()?;
 
     // Step 3: Reconstruct per-file results
     let mut file_hashes: BTreeMap<usize, Vec<(usize, [u8; 32])>> = BTreeMap::new();
 
     for result in chunk_results {
-        let entry = file_hashes.entry(result.file_index).or_insert_with(Vec::new);
+        let entry = file_hashes.entry(result.file_index).or_default();
         for (i, hash) in result.hashes.into_iter().enumerate() {
             entry.push((result.start_block_index + i, hash));
         }
@@ -156,10 +297,16 @@ pub fn hash_v2_files(
             continue;
         }
 
-        let hashes = if let Some(mut block_list) = file_hashes.remove(&file_index) {
+        let hashes = if let Some(hashes) = cached_hashes.remove(&file_index) {
+            hashes
+        } else if let Some(mut block_list) = file_hashes.remove(&file_index) {
             // Sort by block index to ensure correct order
             block_list.sort_by_key(|(block_idx, _)| *block_idx);
-            block_list.into_iter().map(|(_, hash)| hash).collect()
+            let hashes: Vec<[u8; 32]> = block_list.into_iter().map(|(_, hash)| hash).collect();
+            if let Some(cache) = cache.as_deref_mut() {
+                cache.insert(file, &hashes);
+            }
+            hashes
         } else {
             // Empty file
             Vec::new()
@@ -167,22 +314,11 @@ pub fn hash_v2_files(
 
         let (root, layers) = compute_merkle_root(hashes);
 
-        let mut layer_bytes = None;
-        if file.len > piece_length {
-            if let Some(layer) = layers.get(layer_index) {
-                let mut lb = Vec::with_capacity(layer.len() * 32);
-                for h in layer {
-                    lb.extend_from_slice(h);
-                }
-                layer_bytes = Some(lb);
-            }
-        }
-
         file_results.push(FileResult {
             path: file.path.clone(),
             len: file.len,
             root: root.to_vec(),
-            layer_bytes,
+            layer_bytes: piece_layer(&layers, file.len, piece_length),
         });
     }
 
@@ -199,8 +335,9 @@ pub fn hash_v2_files(
         }
 
         if is_single_file {
+            // BEP 52: the lone file sits directly under its name
             file_tree_nodes.insert(
-                "".to_string(),
+                res.path.to_string_lossy().into_owned(),
                 Node::File(FileNode {
                     metadata: FileMetadata {
                         length: res.len,
@@ -219,18 +356,35 @@ pub fn hash_v2_files(
     })
 }
 
+/// The piece layer of a file's merkle tree, concatenated, if the file spans more
+/// than one piece (smaller files are covered by their pieces root alone)
+pub(crate) fn piece_layer(
+    layers: &[Vec<[u8; 32]>],
+    len: u64,
+    piece_length: u64,
+) -> Option<Vec<u8>> {
+    if len <= piece_length {
+        return None;
+    }
+    let layer_index = if piece_length > BLOCK_SIZE as u64 {
+        piece_length.trailing_zeros() as usize - BLOCK_SIZE.trailing_zeros() as usize
+    } else {
+        0
+    };
+    layers.get(layer_index).map(|layer| layer.concat())
+}
+
 /// Compute Merkle Root and layers from block hashes
 pub fn compute_merkle_root(hashes: Vec<[u8; 32]>) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
     if hashes.is_empty() {
         // Root of empty file is SHA256("")
-        let empty_hash = Sha256::digest(&[]);
+        let empty_hash = Sha256::digest([]);
         return (empty_hash.into(), vec![vec![]]);
     }
 
     let mut layers = vec![hashes];
-    while layers.last().unwrap().len() > 1 {
-        let prev = layers.last().unwrap();
-        let mut next = Vec::with_capacity((prev.len() + 1) / 2);
+    while let Some(prev) = layers.last().filter(|layer| layer.len() > 1) {
+        let mut next = Vec::with_capacity(prev.len().div_ceil(2));
         for chunk in prev.chunks(2) {
             if chunk.len() == 2 {
                 let mut hasher = Sha256::new();
@@ -243,7 +397,7 @@ pub fn compute_merkle_root(hashes: Vec<[u8; 32]>) -> ([u8; 32], Vec<Vec<[u8; 32]
         }
         layers.push(next);
     }
-    let root = layers.last().unwrap()[0];
+    let root = layers[layers.len() - 1][0];
     (root, layers)
 }
 
@@ -256,7 +410,7 @@ mod tests {
     fn test_compute_merkle_root() {
         // Test 1: Empty
         let (root, layers) = compute_merkle_root(vec![]);
-        let expected_empty = Sha256::digest(&[]);
+        let expected_empty = Sha256::digest([]);
         let expected_root: [u8; 32] = expected_empty.into();
         assert_eq!(root, expected_root);
         assert_eq!(layers.len(), 1);
@@ -309,4 +463,117 @@ mod tests {
         let h123: [u8; 32] = hasher.finalize().into();
         assert_eq!(root, h123);
     }
+
+    #[test]
+    fn test_chunk_size_does_not_change_hashes() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = Vec::new();
+        let mut offset = 0;
+        // Odd sizes so chunks end mid-file and on a partial last block
+        for (i, len) in [5 * BLOCK_SIZE + 123, 17, 3 * BLOCK_SIZE]
+            .iter()
+            .enumerate()
+        {
+            let data: Vec<u8> = (0..*len).map(|b| (b * (i + 3) % 251) as u8).collect();
+            let full_path = dir.path().join(format!("f{}", i));
+            std::fs::File::create(&full_path)
+                .unwrap()
+                .write_all(&data)
+                .unwrap();
+            files.push(FileInfo {
+                path: PathBuf::from(format!("f{}", i)),
+                full_path,
+                len: *len as u64,
+                start_offset: offset,
+                is_padding: false,
+                data_offset: 0,
+            });
+            offset += *len as u64;
+        }
+
+        let hash = |chunk_blocks| {
+            hash_v2_files_chunked(&files, 1 << 15, false, None, None, chunk_blocks).unwrap()
+        };
+        let reference = hash(DEFAULT_V2_CHUNK_BLOCKS);
+        for chunk_blocks in [0, 1, 2, 3, MAX_V2_CHUNK_BLOCKS + 1] {
+            let result = hash(chunk_blocks);
+            assert_eq!(result.file_tree, reference.file_tree);
+            assert_eq!(result.piece_layers, reference.piece_layers);
+        }
+    }
+
+    #[test]
+    fn test_single_file_is_split_across_threads() {
+        let file = |len: u64| FileInfo {
+            path: PathBuf::from("big.bin"),
+            full_path: PathBuf::from("big.bin"),
+            len,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        };
+        let default_bytes = (DEFAULT_V2_CHUNK_BLOCKS * BLOCK_SIZE) as u64;
+
+        // 10 MiB is five default chunks; 8 threads want 32 items of 320 KiB
+        let size =
+            single_file_chunk_size(10 << 20, default_bytes, 8, SINGLE_FILE_CHUNKS_PER_THREAD);
+        assert_eq!(size, 20 * BLOCK_SIZE as u64);
+        let files = [file(10 << 20)];
+        let work = plan_chunks(&files, &[0], size);
+        assert_eq!(work.len(), 8 * SINGLE_FILE_CHUNKS_PER_THREAD);
+        assert_eq!(work[1].start_block_index, 20);
+
+        // A file already big enough keeps the default size
+        let size = single_file_chunk_size(1 << 30, default_bytes, 8, SINGLE_FILE_CHUNKS_PER_THREAD);
+        assert_eq!(size, default_bytes);
+        // Never below one block, and a partial last block gets a chunk of its own
+        let block = BLOCK_SIZE as u64;
+        assert_eq!(
+            single_file_chunk_size(100, default_bytes, 64, SINGLE_FILE_CHUNKS_PER_THREAD),
+            block
+        );
+        let size = single_file_chunk_size(
+            3 * block + 1,
+            default_bytes,
+            1,
+            SINGLE_FILE_CHUNKS_PER_THREAD,
+        );
+        assert_eq!(size, block);
+        assert_eq!(plan_chunks(&[file(3 * block + 1)], &[0], size).len(), 4);
+
+        // Fewer items per thread means bigger chunks
+        let size = single_file_chunk_size(10 << 20, default_bytes, 8, 1);
+        assert_eq!(size, 80 * BLOCK_SIZE as u64);
+    }
+
+    #[test]
+    fn test_auto_split_matches_fixed_chunks() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let len = 9 * BLOCK_SIZE + 500;
+        let data: Vec<u8> = (0..len).map(|b| (b % 239) as u8).collect();
+        let full_path = dir.path().join("single.bin");
+        std::fs::File::create(&full_path)
+            .unwrap()
+            .write_all(&data)
+            .unwrap();
+        let files = [FileInfo {
+            path: PathBuf::from("single.bin"),
+            full_path,
+            len: len as u64,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        }];
+
+        let auto = hash_v2_files(&files, 1 << 15, true, None).unwrap();
+        let fixed =
+            hash_v2_files_chunked(&files, 1 << 15, true, None, None, DEFAULT_V2_CHUNK_BLOCKS)
+                .unwrap();
+        assert_eq!(auto.file_tree, fixed.file_tree);
+        assert_eq!(auto.piece_layers, fixed.piece_layers);
+    }
 }
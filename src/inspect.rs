@@ -1,97 +1,351 @@
 use anyhow::{Context, Result};
 use console::{style, Emoji};
 use indicatif::HumanBytes;
+use serde_bencode::value::Value;
+use std::fmt::{self, Write};
 use std::fs;
 
 use torrite::cli::InspectArgs;
 use torrite::models::Torrent;
+use torrite::tree::tree_files;
 
 static INFO: Emoji<'_, '_> = Emoji("ℹ️ ", "i ");
 static FILES: Emoji<'_, '_> = Emoji("📁 ", "f ");
 static TRACKERS: Emoji<'_, '_> = Emoji("📡 ", "t ");
+static LAYERS: Emoji<'_, '_> = Emoji("🌳 ", "l ");
+static WARN: Emoji<'_, '_> = Emoji("⚠️ ", "! ");
+
+/// Max number of files listed per section
+const MAX_LISTED: usize = 20;
+
+/// Max number of bytes of a binary string shown by `--dump-bencode`
+const MAX_HEX_BYTES: usize = 32;
 
 pub fn inspect_torrent(args: InspectArgs) -> Result<()> {
     let path = args.torrent;
-    let content = fs::read(&path).with_context(|| format!("Failed to read torrent file: {}", path.display()))?;
+    let content = fs::read(&path)
+        .with_context(|| format!("Failed to read torrent file: {}", path.display()))?;
+
+    if args.dump_bencode {
+        let value: Value = serde_bencode::from_bytes(&content)
+            .context("Failed to parse torrent file. Is it a valid bencoded file?")?;
+        print!("{}", dump_bencode(&value));
+        return Ok(());
+    }
 
     let torrent: Torrent = serde_bencode::from_bytes(&content)
         .context("Failed to parse torrent file. Is it a valid bencoded file?")?;
 
-    println!("{} {}", INFO, style("Torrent Metadata:").bold());
-    println!("{:<15} {}", style("Name:").bold(), style(&torrent.info.name).cyan());
-    
+    print!("{}", summary(&torrent, false));
+    Ok(())
+}
+
+/// Human-readable description of a torrent, as shown by `inspect`. With `plain`,
+/// colors and emoji are left out, e.g. for the `--write-summary` sidecar.
+pub fn summary(torrent: &Torrent, plain: bool) -> String {
+    let mut out = String::new();
+    write_summary(torrent, plain, &mut out).expect("writing to a String can't fail");
+    if plain {
+        console::strip_ansi_codes(&out).into_owned()
+    } else {
+        out
+    }
+}
+
+fn write_summary(torrent: &Torrent, plain: bool, out: &mut String) -> fmt::Result {
+    let icon = |emoji: &Emoji<'_, '_>| {
+        if plain {
+            String::new()
+        } else {
+            emoji.to_string()
+        }
+    };
+
+    writeln!(out, "{}{}", icon(&INFO), style("Torrent Metadata:").bold())?;
+    writeln!(
+        out,
+        "{:<15} {}",
+        style("Name:").bold(),
+        style(&torrent.info.name).cyan()
+    )?;
+
     if let Some(comment) = &torrent.comment {
-         println!("{:<15} {}", style("Comment:").bold(), comment);
+        writeln!(out, "{:<15} {}", style("Comment:").bold(), comment)?;
     }
-    
-    println!("{:<15} {}", style("Created By:").bold(), torrent.created_by);
-    
+
+    if !torrent.created_by.is_empty() {
+        writeln!(
+            out,
+            "{:<15} {}",
+            style("Created By:").bold(),
+            torrent.created_by
+        )?;
+    }
+
+    match (&torrent.publisher, &torrent.publisher_url) {
+        (Some(publisher), Some(url)) => writeln!(
+            out,
+            "{:<15} {} ({})",
+            style("Publisher:").bold(),
+            publisher,
+            url
+        )?,
+        (Some(publisher), None) => {
+            writeln!(out, "{:<15} {}", style("Publisher:").bold(), publisher)?
+        }
+        (None, Some(url)) => writeln!(out, "{:<15} {}", style("Publisher:").bold(), url)?,
+        (None, None) => {}
+    }
+
     if let Some(date) = torrent.creation_date {
          let datetime = chrono::DateTime::from_timestamp(date, 0)
             .map(|dt| dt.to_string())
             .unwrap_or_else(|| date.to_string());
-        println!("{:<15} {}", style("Date:").bold(), datetime);
+        writeln!(out, "{:<15} {}", style("Date:").bold(), datetime)?;
     }
 
     if let Some(source) = &torrent.info.source {
-        println!("{:<15} {}", style("Source:").bold(), source);
+        writeln!(out, "{:<15} {}", style("Source:").bold(), source)?;
     }
 
-    println!("{:<15} {}", style("Total Size:").bold(), style(HumanBytes(torrent.total_size())).green());
-    println!("{:<15} {}", style("Piece Size:").bold(), style(HumanBytes(torrent.info.piece_length)).yellow());
-    
+    writeln!(
+        out,
+        "{:<15} {}",
+        style("Total Size:").bold(),
+        style(HumanBytes(torrent.total_size())).green()
+    )?;
+    writeln!(
+        out,
+        "{:<15} {}",
+        style("Piece Size:").bold(),
+        style(HumanBytes(torrent.info.piece_length)).yellow()
+    )?;
+
+    let file_count = match (&torrent.info.files, &torrent.info.file_tree) {
+        (Some(files), _) => files
+            .iter()
+            .filter(|f| !f.attr.as_deref().is_some_and(|attr| attr.contains('p')))
+            .count(),
+        (None, Some(tree)) => tree_files(tree).len(),
+        (None, None) => 1,
+    };
+    writeln!(out, "{:<15} {}", style("File Count:").bold(), file_count)?;
+
     if let Some(pieces) = &torrent.info.pieces {
         let num_pieces = pieces.len() / 20;
-        println!("{:<15} {}", style("Piece Count:").bold(), num_pieces);
+        writeln!(out, "{:<15} {}", style("Piece Count:").bold(), num_pieces)?;
+        if let Some(problem) = torrent.info.piece_count_mismatch() {
+            writeln!(out, "{}{}", icon(&WARN), style(problem).yellow())?;
+        }
     }
 
-    println!("{:<15} {}", style("Private:").bold(), if torrent.info.private.unwrap_or(0) == 1 { style("yes").red() } else { style("no").dim() });
+    writeln!(
+        out,
+        "{:<15} {}",
+        style("Private:").bold(),
+        if torrent.info.private.unwrap_or(0) == 1 {
+            style("yes").red()
+        } else {
+            style("no").dim()
+        }
+    )?;
 
     if let Some(v1_hash) = torrent.info_hash_v1() {
-        println!("{:<15} {}", style("Info Hash v1:").bold(), hex::encode(v1_hash));
+        writeln!(
+            out,
+            "{:<15} {}",
+            style("Info Hash v1:").bold(),
+            hex::encode(v1_hash)
+        )?;
     }
     
     if let Some(v2_hash) = torrent.info_hash_v2() {
-        println!("{:<15} {}", style("Info Hash v2:").bold(), hex::encode(v2_hash));
+        writeln!(
+            out,
+            "{:<15} {}",
+            style("Info Hash v2:").bold(),
+            hex::encode(v2_hash)
+        )?;
     }
 
-    println!("\n{} {}", TRACKERS, style("Trackers:").bold());
+    writeln!(out, "\n{}{}", icon(&TRACKERS), style("Trackers:").bold())?;
     if let Some(announce) = &torrent.announce {
-        println!("  - {}", style(announce).underlined());
+        writeln!(out, "  - {}", style(announce).underlined())?;
     }
     
     if let Some(announce_list) = &torrent.announce_list {
         for tier in announce_list {
             for tracker in tier {
                 if Some(tracker) != torrent.announce.as_ref() {
-                    println!("  - {}", style(tracker).underlined());
+                    writeln!(out, "  - {}", style(tracker).underlined())?;
                 }
             }
         }
     }
     
     if let Some(web_seeds) = &torrent.url_list {
-        println!("\n{}", style("Web Seeds:").bold());
+        writeln!(out, "\n{}", style("Web Seeds:").bold())?;
         for url in web_seeds {
-            println!("  - {}", style(url).underlined());
+            writeln!(out, "  - {}", style(url).underlined())?;
         }
     }
 
-    println!("\n{} {}", FILES, style("Files:").bold());
+    writeln!(out, "\n{}{}", icon(&FILES), style("Files:").bold())?;
     if let Some(files) = &torrent.info.files {
         for (i, file) in files.iter().enumerate() {
-            if i >= 20 {
-                println!("  ... and {} more files", style(files.len() - 20).dim());
+            if i >= MAX_LISTED {
+                writeln!(
+                    out,
+                    "  ... and {} more files",
+                    style(files.len() - MAX_LISTED).dim()
+                )?;
                 break;
             }
             let path = file.path.join("/");
-            println!("  - {:<40} {}", path, style(HumanBytes(file.length)).dim());
+            writeln!(
+                out,
+                "  - {:<40} {}",
+                path,
+                style(HumanBytes(file.length)).dim()
+            )?;
         }
     } else if let Some(_tree) = &torrent.info.file_tree {
-        println!("  {}", style("(V2 File Tree structure present)").italic().dim());
+        writeln!(
+            out,
+            "  {}",
+            style("(V2 File Tree structure present)").italic().dim()
+        )?;
     } else {
-        println!("  - {:<40} {}", torrent.info.name, style(HumanBytes(torrent.total_size())).dim());
+        writeln!(
+            out,
+            "  - {:<40} {}",
+            torrent.info.name,
+            style(HumanBytes(torrent.total_size())).dim()
+        )?;
+    }
+
+    if torrent.info.meta_version == Some(2) {
+        write_piece_layers(torrent, &icon(&LAYERS), out)?;
     }
 
     Ok(())
 }
+
+/// Summarise the V2 piece layers: clients need a layer for every file larger than
+/// one piece to seed without re-hashing
+fn write_piece_layers(torrent: &Torrent, icon: &str, out: &mut String) -> fmt::Result {
+    writeln!(out, "\n{}{}", icon, style("Piece Layers:").bold())?;
+
+    let layers = torrent.piece_layers.as_ref();
+    let layer_bytes: usize = layers.map_or(0, |l| l.values().map(|v| v.len()).sum());
+    writeln!(
+        out,
+        "  {:<13} {}",
+        style("Entries:").bold(),
+        layers.map_or(0, |l| l.len())
+    )?;
+    writeln!(
+        out,
+        "  {:<13} {}",
+        style("Layer Bytes:").bold(),
+        HumanBytes(layer_bytes as u64)
+    )?;
+
+    let piece_length = torrent.info.piece_length;
+    let tree = match &torrent.info.file_tree {
+        Some(tree) => tree,
+        None => return Ok(()),
+    };
+    let large_files: Vec<_> = tree_files(tree)
+        .into_iter()
+        .filter(|(_, meta)| meta.length > piece_length)
+        .collect();
+    if large_files.is_empty() {
+        writeln!(
+            out,
+            "  {}",
+            style("(no file spans more than one piece, so no layers are needed)")
+                .italic()
+                .dim()
+        )?;
+        return Ok(());
+    }
+
+    for (i, (path, meta)) in large_files.iter().enumerate() {
+        if i >= MAX_LISTED {
+            writeln!(
+                out,
+                "  ... and {} more files",
+                style(large_files.len() - MAX_LISTED).dim()
+            )?;
+            break;
+        }
+        let expected = meta.length.div_ceil(piece_length) as usize;
+        let status = match layers.and_then(|l| l.get(&meta.pieces_root)) {
+            Some(layer) if layer.len() == expected * 32 => {
+                style(format!("{} pieces", expected)).green()
+            }
+            Some(layer) => style(format!("{} of {} hashes", layer.len() / 32, expected)).yellow(),
+            None => style("layer missing".to_string()).red(),
+        };
+        writeln!(
+            out,
+            "  - {:<40} {} {}",
+            path,
+            style(hex::encode(&meta.pieces_root)).dim(),
+            status
+        )?;
+    }
+    Ok(())
+}
+
+/// Generic view of a bencoded value: one line per value with its type and length.
+/// Strings under `pieces` and `piece layers`, and any that aren't UTF-8, are shown
+/// as (truncated) hex.
+pub fn dump_bencode(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(value, 0, false, &mut out).expect("writing to a String can't fail");
+    out
+}
+
+fn write_value(value: &Value, depth: usize, binary: bool, out: &mut String) -> fmt::Result {
+    let indent = "  ".repeat(depth + 1);
+    match value {
+        Value::Int(i) => writeln!(out, "int {}", i),
+        Value::Bytes(bytes) => writeln!(out, "{}", describe_bytes(bytes, binary)),
+        Value::List(items) => {
+            writeln!(out, "list[{}]", items.len())?;
+            for item in items {
+                write!(out, "{}- ", indent)?;
+                write_value(item, depth + 1, binary, out)?;
+            }
+            Ok(())
+        }
+        Value::Dict(entries) => {
+            writeln!(out, "dict[{}]", entries.len())?;
+            let mut keys: Vec<&Vec<u8>> = entries.keys().collect();
+            keys.sort();
+            for key in keys {
+                let child_binary = binary || key == b"pieces" || key == b"piece layers";
+                match std::str::from_utf8(key) {
+                    Ok(key) if !binary => write!(out, "{}{:?}: ", indent, key)?,
+                    _ => write!(out, "{}<{}>: ", indent, hex::encode(key))?,
+                }
+                write_value(&entries[key], depth + 1, child_binary, out)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn describe_bytes(bytes: &[u8], binary: bool) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !binary => format!("str[{}] {:?}", bytes.len(), text),
+        _ if bytes.len() > MAX_HEX_BYTES => format!(
+            "bytes[{}] {}...",
+            bytes.len(),
+            hex::encode(&bytes[..MAX_HEX_BYTES])
+        ),
+        _ => format!("bytes[{}] {}", bytes.len(), hex::encode(bytes)),
+    }
+}
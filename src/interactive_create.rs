@@ -1,4 +1,5 @@
 use anyhow::Result;
+use crossbeam_channel::{Receiver, TryRecvError};
 use crossterm::{
     event::{
         self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
@@ -7,6 +8,7 @@ use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use indicatif::HumanBytes;
 use ratatui::{
     Frame, Terminal,
     backend::{Backend, CrosstermBackend},
@@ -17,10 +19,13 @@ use ratatui::{
 };
 use std::io;
 use std::path::{MAIN_SEPARATOR, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
-use torrite::cli::CreateArgs;
+use torrite::cli::{CommentEscape, CreateArgs, Emit};
 use torrite::config::Config;
+use torrite::models::FileInfo;
+use torrite::scanner::{Scanner, WalkOptions};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Step {
@@ -30,6 +35,46 @@ enum Step {
     Summary,
 }
 
+/// File list and totals shown in the Metadata step's preview panel
+#[derive(Debug, Clone, PartialEq)]
+struct ScanSummary {
+    file_count: usize,
+    total_size: u64,
+    entries: Vec<(String, u64)>,
+}
+
+impl ScanSummary {
+    fn from_files(files: &[FileInfo]) -> Self {
+        Self {
+            file_count: files.len(),
+            total_size: files.iter().map(|f| f.len).sum(),
+            entries: files
+                .iter()
+                .map(|f| (f.path.to_string_lossy().into_owned(), f.len))
+                .collect(),
+        }
+    }
+}
+
+/// State of the background scan feeding the preview panel
+enum PreviewState {
+    Idle,
+    Scanning,
+    Ready(ScanSummary),
+    Failed(String),
+}
+
+/// Outcome of a background scan, with the walk so later previews can reuse it
+type PreviewResult = Result<(ScanSummary, Arc<Scanner>), String>;
+
+/// Apply the exclude patterns to an already walked source (runs off the UI thread)
+fn scan_preview(scanner: &Scanner, exclude: &[String]) -> Result<ScanSummary, String> {
+    scanner
+        .files(None, exclude, &[])
+        .map(|(files, _)| ScanSummary::from_files(&files))
+        .map_err(|e| format!("{:#}", e))
+}
+
 struct App {
     step: Step,
     config: Config,
@@ -46,6 +91,14 @@ struct App {
     private: bool,
     source_string: String,
     web_seeds: String,
+    exclude: String, // Comma separated glob patterns
+
+    // File preview
+    preview: PreviewState,
+    preview_rx: Option<Receiver<PreviewResult>>,
+    preview_scroll: u16,
+    // Last walk of the source, reused while only the excludes change
+    scanner: Option<Arc<Scanner>>,
 
     // Output
     output_path: String,
@@ -82,6 +135,11 @@ impl App {
             private: false,
             source_string: String::new(),
             web_seeds: String::new(),
+            exclude: String::new(),
+            preview: PreviewState::Idle,
+            preview_rx: None,
+            preview_scroll: 0,
+            scanner: None,
             output_path: String::new(),
             metadata_list_state: list_state,
             metadata_editing_idx: None,
@@ -98,7 +156,7 @@ impl App {
         }
         let name = &self.available_profiles[self.profile_idx];
         if let Some(profile) = self.config.profiles.get(name) {
-            if let Some(ann) = &profile.announce {
+            if let Some(ann) = profile.announce_args() {
                 self.announce = ann.join("\n");
             }
             if let Some(comm) = &profile.comment {
@@ -116,6 +174,67 @@ impl App {
             if let Some(ws) = &profile.web_seed {
                 self.web_seeds = ws.join("\n");
             }
+            if let Some(ex) = &profile.exclude {
+                self.exclude = ex.join(",");
+            }
+        }
+    }
+
+    fn exclude_patterns(&self) -> Vec<String> {
+        self.exclude
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    /// Start a background scan of the source; any in-flight scan is superseded
+    fn request_preview(&mut self) {
+        let Some(source) = self.source.clone() else {
+            return;
+        };
+        let exclude = self.exclude_patterns();
+        let cached = self
+            .scanner
+            .clone()
+            .filter(|s| source.canonicalize().is_ok_and(|p| p == s.source()));
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        std::thread::spawn(move || {
+            let result = match cached {
+                Some(scanner) => Ok(scanner),
+                None => Scanner::new(&source, WalkOptions::default())
+                    .map(Arc::new)
+                    .map_err(|e| format!("{:#}", e)),
+            }
+            .and_then(|scanner| Ok((scan_preview(&scanner, &exclude)?, scanner)));
+            // The receiver may have been replaced by a newer scan; ignore send errors
+            let _ = tx.send(result);
+        });
+        self.preview = PreviewState::Scanning;
+        self.preview_rx = Some(rx);
+        self.preview_scroll = 0;
+    }
+
+    /// Pick up the result of the background scan, if it has finished
+    fn poll_preview(&mut self) {
+        if let Some(rx) = &self.preview_rx {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.preview = match result {
+                        Ok((summary, scanner)) => {
+                            self.scanner = Some(scanner);
+                            PreviewState::Ready(summary)
+                        }
+                        Err(e) => PreviewState::Failed(e),
+                    };
+                    self.preview_rx = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    self.preview = PreviewState::Failed("Scan thread exited".to_string());
+                    self.preview_rx = None;
+                }
+            }
         }
     }
 
@@ -142,15 +261,35 @@ impl App {
                 None
             },
             announce: announce_vec,
+            announce_list_flatten: false,
+            no_announce_list: false,
+            announce_tier_shuffle: false,
+            deterministic: false,
+            tracker_preset: None,
+            print_backend: false,
             comment: if self.comment.is_empty() {
                 None
             } else {
                 Some(self.comment.clone())
             },
-            no_date: false,  // Not exposed in UI for simplicity
-            exclude: vec![], // Not exposed
-            force: false,    // Will be handled by main logic possibly, or we assume force
+            no_date: false, // Not exposed in UI for simplicity
+            created_by: None,
+            publisher: None,
+            publisher_url: None,
+            date_comment: false,
+            exclude: self.exclude_patterns(),
+            merge_trackers: false,
+            rehash_verify: false,
+            stats: false,
+            piece_layers_external: false,
+            write_magnet: false,
+            write_summary: false,
+            force: false, // Will be handled by main logic possibly, or we assume force
             piece_length: self.piece_length.parse().ok(),
+            round_piece_length: false,
+            min_piece_length: None,
+            min_pieces: None,
+            max_piece_length: None,
             name: None, // Auto-derive
             output: if self.output_path.is_empty() {
                 None
@@ -158,21 +297,59 @@ impl App {
                 Some(PathBuf::from(&self.output_path))
             },
             date: None,
+            date_rfc3339: None,
             private: self.private,
+            public: false,
             source_string: if self.source_string.is_empty() {
                 None
             } else {
                 Some(self.source_string.clone())
             },
             threads: None,
+            max_memory: None,
+            v2_chunk_blocks: None,
+            experimental: false,
+            meta_version: None,
             verbose: false,
             web_seed: web_seed_vec,
             cross_seed: false,
+            like: None,
+            cross_seed_from: None,
+            piece_length_from: None,
+            inherit_metadata: false,
+            similar: Vec::new(),
+            collection: Vec::new(),
             info_hash: false,
+            report_duplicates: false,
+            piece_length_report: false,
             json: false,
+            v1: false,
             v2: false, // Default to v1/hybrid depending on detection, or add toggle
             hybrid: false,
+            comment_template: None,
+            no_padding: false,
             dry_run: false,
+            output_pattern: None,
+            emit: Emit::Torrent,
+            escape_comment: CommentEscape::None,
+            strict: false,
+            force_v1_name_compat: false,
+            from_archive: false,
+            from_url: None,
+            length: None,
+            relative_to: None,
+            keep_empty_dirs: false,
+            no_recursion: false,
+            flatten_single: false,
+            strip_root: false,
+            include_only: vec![],
+            no_default_excludes: false,
+            include_hidden: false,
+            allow_torrent_files: false,
+            skip_empty_files: false,
+            store_mtime: false,
+            hash_cache: None,
+            cache_compress: false,
         }
     }
 }
@@ -205,6 +382,7 @@ pub fn run(config: Config) -> Result<Option<CreateArgs>> {
 
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<Option<CreateArgs>> {
     loop {
+        app.poll_preview();
         terminal.draw(|f| ui(f, &mut app))?;
 
         // 1. Wait for the first event (with timeout for redraws)
@@ -385,6 +563,11 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<Optio
                                 app.announce = app.input_buffer.clone();
                                 app.is_dirty = true;
                             }
+                            7 => {
+                                app.exclude = app.input_buffer.clone();
+                                app.is_dirty = true;
+                                app.request_preview();
+                            }
                             999 => {
                                 app.output_path = app.input_buffer.clone();
                                 app.is_dirty = true;
@@ -413,10 +596,9 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<Optio
             // Handle navigation and actions based on current step
             match app.step {
                 Step::InputSelection => match key.code {
-                    KeyCode::Tab | KeyCode::Enter => {
-                        if app.source.is_some() {
-                            app.step = Step::Metadata;
-                        }
+                    KeyCode::Tab | KeyCode::Enter if app.source.is_some() => {
+                        app.step = Step::Metadata;
+                        app.request_preview();
                     }
                     _ => {} // Ignore other keys
                 },
@@ -424,21 +606,26 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<Optio
                     KeyCode::Tab => {
                         app.step = Step::OutputSelection;
                         // Auto-suggest output path based on source
-                        if app.output_path.is_empty() {
-                            if let Some(src) = &app.source {
-                                let file_name =
-                                    src.file_name().unwrap_or_default().to_string_lossy();
-                                app.output_path = format!("{}.torrent", file_name);
-                            }
+                        if app.output_path.is_empty()
+                            && let Some(src) = &app.source
+                        {
+                            let file_name = src.file_name().unwrap_or_default().to_string_lossy();
+                            app.output_path = format!("{}.torrent", file_name);
                         }
                     }
                     KeyCode::BackTab => {
                         app.step = Step::InputSelection;
                     }
+                    KeyCode::PageDown => {
+                        app.preview_scroll = app.preview_scroll.saturating_add(10);
+                    }
+                    KeyCode::PageUp => {
+                        app.preview_scroll = app.preview_scroll.saturating_sub(10);
+                    }
                     KeyCode::Down => {
                         let i = match app.metadata_list_state.selected() {
                             Some(i) => {
-                                if i >= 6 {
+                                if i >= 7 {
                                     0
                                 } else {
                                     i + 1
@@ -452,7 +639,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<Optio
                         let i = match app.metadata_list_state.selected() {
                             Some(i) => {
                                 if i == 0 {
-                                    6
+                                    7
                                 } else {
                                     i - 1
                                 }
@@ -473,6 +660,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<Optio
                                     }
                                     app.apply_profile();
                                     app.is_dirty = true;
+                                    app.request_preview();
                                 }
                                 3 => {
                                     // Private
@@ -487,6 +675,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> Result<Optio
                                         4 => app.source_string.clone(),
                                         5 => app.web_seeds.clone(),
                                         6 => app.announce.clone(),
+                                        7 => app.exclude.clone(),
                                         _ => String::new(),
                                     };
                                 }
@@ -585,7 +774,9 @@ fn ui(f: &mut Frame, app: &mut App) {
                             .add_modifier(Modifier::BOLD),
                     )]),
                     Line::from(""),
-                    Line::from("Press Tab or Enter to continue, or drag & drop another file to replace."),
+                    Line::from(
+                        "Press Tab or Enter to continue, or drag & drop another file to replace.",
+                    ),
                 ]
             } else {
                 vec![
@@ -616,7 +807,7 @@ fn ui(f: &mut Frame, app: &mut App) {
             f.render_widget(p, v_chunks[1]);
         }
         Step::Metadata => {
-            let items = vec![
+            let items = [
                 format!("Profile:      {}", app.available_profiles[app.profile_idx]),
                 format!("Comment:      {}", app.comment),
                 format!(
@@ -631,6 +822,14 @@ fn ui(f: &mut Frame, app: &mut App) {
                 format!("Source:       {}", app.source_string),
                 format!("Web Seeds:    {}", app.web_seeds.replace('\n', ", ")),
                 format!("Announce URLs: {}", app.announce.lines().count()),
+                format!(
+                    "Exclude:      {}",
+                    if app.exclude.is_empty() {
+                        "None"
+                    } else {
+                        &app.exclude
+                    }
+                ),
             ];
 
             let list_items: Vec<ListItem> =
@@ -645,11 +844,16 @@ fn ui(f: &mut Frame, app: &mut App) {
                 )
                 .highlight_symbol("> ");
 
-            // Layout for metadata and announce box
+            // Metadata and announce box on the left, file preview on the right
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(content_area);
+
             let meta_chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
-                .split(content_area);
+                .split(columns[0]);
 
             f.render_stateful_widget(list, meta_chunks[0], &mut app.metadata_list_state);
 
@@ -662,25 +866,28 @@ fn ui(f: &mut Frame, app: &mut App) {
             );
             f.render_widget(p, meta_chunks[1]);
 
+            render_preview(f, app, columns[1]);
+
             // Editing popup
-            if let Some(idx) = app.metadata_editing_idx {
-                if idx != 999 {
-                    // Not output editing
-                    let area = centered_rect(60, 20, f.area());
-                    f.render_widget(Clear, area);
-                    let title = match idx {
-                        1 => "Edit Comment",
-                        2 => "Edit Piece Length (e.g. 18 for 256KB)",
-                        4 => "Edit Source String",
-                        5 => "Edit Web Seeds (newline separated)",
-                        6 => "Edit Announce URLs (newline separated)",
-                        _ => "Edit",
-                    };
-                    let input = Paragraph::new(app.input_buffer.as_str())
-                        .block(Block::default().borders(Borders::ALL).title(title))
-                        .style(Style::default().fg(Color::Yellow));
-                    f.render_widget(input, area);
-                }
+            if let Some(idx) = app.metadata_editing_idx
+                && idx != 999
+            {
+                // Not output editing
+                let area = centered_rect(60, 20, f.area());
+                f.render_widget(Clear, area);
+                let title = match idx {
+                    1 => "Edit Comment",
+                    2 => "Edit Piece Length (e.g. 18 for 256KB)",
+                    4 => "Edit Source String",
+                    5 => "Edit Web Seeds (newline separated)",
+                    6 => "Edit Announce URLs (newline separated)",
+                    7 => "Edit Exclude Patterns (comma separated globs)",
+                    _ => "Edit",
+                };
+                let input = Paragraph::new(app.input_buffer.as_str())
+                    .block(Block::default().borders(Borders::ALL).title(title))
+                    .style(Style::default().fg(Color::Yellow));
+                f.render_widget(input, area);
             }
         }
         Step::OutputSelection => {
@@ -836,7 +1043,9 @@ fn ui(f: &mut Frame, app: &mut App) {
                 "Esc: Quit | Drag & drop a file or directory to begin"
             }
         }
-        Step::Metadata => "Esc: Quit | Tab: Continue | Shift+Tab: Back | ↑/↓: Navigate | Enter: Edit/Toggle",
+        Step::Metadata => {
+            "Esc: Quit | Tab: Continue | Shift+Tab: Back | ↑/↓: Navigate | Enter: Edit/Toggle | PgUp/PgDn: Scroll files"
+        }
         Step::OutputSelection => "Esc: Quit | Tab: Continue | Shift+Tab: Back | Enter: Edit path",
         Step::Summary => "Esc: Quit | Enter: Create | Shift+Tab: Back",
     };
@@ -847,6 +1056,49 @@ fn ui(f: &mut Frame, app: &mut App) {
     f.render_widget(help_bar, chunks[2]);
 }
 
+fn render_preview(f: &mut Frame, app: &App, area: Rect) {
+    let (title, lines) = match &app.preview {
+        PreviewState::Idle => (" Files ".to_string(), vec![]),
+        PreviewState::Scanning => (
+            " Files ".to_string(),
+            vec![Line::from(Span::styled(
+                "Scanning...",
+                Style::default().dim(),
+            ))],
+        ),
+        PreviewState::Failed(e) => (
+            " Files ".to_string(),
+            vec![Line::from(Span::styled(
+                e.clone(),
+                Style::default().fg(Color::Red),
+            ))],
+        ),
+        PreviewState::Ready(summary) => (
+            format!(
+                " Files ({}, {}) ",
+                summary.file_count,
+                HumanBytes(summary.total_size)
+            ),
+            summary
+                .entries
+                .iter()
+                .map(|(path, len)| {
+                    Line::from(vec![
+                        Span::raw(path.clone()),
+                        Span::raw("  "),
+                        Span::styled(HumanBytes(*len).to_string(), Style::default().dim()),
+                    ])
+                })
+                .collect(),
+        ),
+    };
+
+    let p = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .scroll((app.preview_scroll, 0));
+    f.render_widget(p, area);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -872,3 +1124,53 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         )
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_scan_summary_from_files() {
+        let files = vec![
+            FileInfo {
+                path: PathBuf::from("a.txt"),
+                full_path: PathBuf::from("/src/a.txt"),
+                len: 100,
+                start_offset: 0,
+                is_padding: false,
+                data_offset: 0,
+            },
+            FileInfo {
+                path: PathBuf::from("sub/b.bin"),
+                full_path: PathBuf::from("/src/sub/b.bin"),
+                len: 250,
+                start_offset: 100,
+                is_padding: false,
+                data_offset: 0,
+            },
+        ];
+
+        let summary = ScanSummary::from_files(&files);
+        assert_eq!(summary.file_count, 2);
+        assert_eq!(summary.total_size, 350);
+        assert_eq!(summary.entries[0], ("a.txt".to_string(), 100));
+        assert_eq!(summary.entries[1].1, 250);
+    }
+
+    #[test]
+    fn test_scan_preview_applies_excludes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join("keep.txt"), "keep").unwrap();
+        fs::write(temp_dir.path().join("skip.tmp"), "skip me").unwrap();
+
+        let scanner = Scanner::new(temp_dir.path(), WalkOptions::default()).unwrap();
+        let all = scan_preview(&scanner, &[]).unwrap();
+        assert_eq!(all.file_count, 2);
+
+        let filtered = scan_preview(&scanner, &["*.tmp".to_string()]).unwrap();
+        assert_eq!(filtered.file_count, 1);
+        assert_eq!(filtered.total_size, 4);
+        assert_eq!(filtered.entries[0].0, "keep.txt");
+    }
+}
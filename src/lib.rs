@@ -16,16 +16,22 @@
 //! let torrent = builder.build().unwrap();
 //! ```
 
+#[cfg(feature = "archive")]
+pub mod archive;
 pub mod builder;
 pub mod cli;
 pub mod config;
 pub mod hashing;
 pub mod models;
 pub mod piece;
+#[cfg(feature = "http")]
+pub mod remote;
 pub mod scanner;
 pub mod trackers;
 pub mod tree;
 
 // Re-export main types for convenience
-pub use builder::TorrentBuilder;
-pub use models::{Mode, Torrent, TorrentOptions};
+pub use builder::{BuildTimings, DryRunReport, FileEntryView, TorrentBuilder};
+pub use models::{
+    FilePadding, Mode, PaddingReport, Torrent, TorrentOptions, TorrentOptionsBuilder,
+};
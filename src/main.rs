@@ -1,23 +1,31 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use console::{Emoji, style};
 use indicatif::HumanBytes;
 use std::fs::File;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 use torrite::TorrentBuilder;
-use torrite::cli::{Cli, Commands, CreateArgs};
-use torrite::config::Config;
-use torrite::models::TorrentSummary;
+use torrite::builder::{BuildTimings, check_written_torrent};
+use torrite::cli::{Cli, Commands, CommentEscape, CreateArgs, Emit, PieceLength};
+use torrite::config::{Config, DEFAULT_PROFILE, Profile};
+use torrite::hashing::{Cancellation, hash_backend};
+use torrite::models::{Mode, Node, PaddingReport, Torrent, TorrentSummary};
+use torrite::trackers::tracker_host;
+use torrite::tree::{DuplicateGroup, find_duplicates};
 
 mod edit;
 mod inspect;
+mod recalc;
+mod scan;
 mod verify;
 mod interactive_create;
 
 use edit::edit_torrent;
 use inspect::inspect_torrent;
+use recalc::recalc_torrent;
+use scan::scan_path;
 use verify::verify_torrent;
 
 static SUCCESS: Emoji<'_, '_> = Emoji("✅ ", "OK");
@@ -37,6 +45,10 @@ fn main() -> Result<()> {
         if first_arg != "verify"
             && first_arg != "edit"
             && first_arg != "inspect"
+            && first_arg != "scan"
+            && first_arg != "recalc"
+            && first_arg != "completions"
+            && first_arg != "complete"
             && first_arg != "create"
             && first_arg != "help"
             && first_arg != "--help"
@@ -59,158 +71,311 @@ fn main() -> Result<()> {
 
     let cli = Cli::parse_from(modified_args);
 
+    init_logging(matches!(&cli.command, Commands::Create(args) if args.verbose));
+
+    // Completions don't depend on the configuration, so a broken config can't block them
+    if let Commands::Completions(args) = &cli.command {
+        return print_completions(args.shell);
+    }
+
     // Load configuration
     let config = Config::load(cli.config)?;
 
     match cli.command {
-        Commands::Create(args) => cmd_create(args, &config),
+        Commands::Create(args) => cmd_create(*args, &config),
         Commands::Verify(args) => verify_torrent(args),
         Commands::Edit(args) => edit_torrent(args),
         Commands::Inspect(args) => inspect_torrent(args),
+        Commands::Scan(args) => scan_path(args),
+        Commands::Recalc(args) => recalc_torrent(args),
+        Commands::Completions(_) => unreachable!("handled before loading the configuration"),
     }
 }
 
-fn cmd_create(mut args: CreateArgs, config: &Config) -> Result<()> {
-    // If source is missing, run interactive mode
-    if args.source.is_none() {
-        if let Some(new_args) = interactive_create::run(config.clone())? {
-            args = new_args;
-        } else {
-            return Ok(()); // User cancelled
+/// Write the completion script for `shell` to stdout
+fn print_completions(shell: clap_complete::Shell) -> Result<()> {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    // Generated into memory first: clap_complete panics if stdout goes away mid-write
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, name, &mut script);
+    std::io::stdout()
+        .write_all(&script)
+        .context("Failed to write completion script")
+}
+
+fn read_torrent(path: &Path) -> Result<Torrent> {
+    let content = std::fs::read(path)
+        .with_context(|| format!("Failed to read torrent file: {}", path.display()))?;
+    serde_bencode::from_bytes(&content).context("Invalid torrent file")
+}
+
+/// Base a cross-seed on an existing torrent, optionally carrying over its comment and source
+fn apply_cross_seed_from(args: &mut CreateArgs, path: &Path) -> Result<()> {
+    let reference = read_torrent(path)?;
+
+    args.cross_seed = true;
+    if args.inherit_metadata {
+        if args.comment.is_none() && args.comment_template.is_none() {
+            args.comment = reference.comment;
+        }
+        if args.source_string.is_none() {
+            args.source_string = reference.info.source;
         }
     }
+    Ok(())
+}
 
-    // Apply profile if specified
-    if let Some(profile_name) = &args.profile {
-        if let Some(profile) = config.profiles.get(profile_name) {
-            if !args.json {
-                eprintln!(
-                    "{} Using profile: {}",
-                    style("ℹ️").blue(),
-                    style(profile_name).bold()
-                );
-            }
+/// Read only `info.piece length` from a torrent, so the rest of it needn't be valid
+fn read_piece_length(path: &Path) -> Result<u64> {
+    #[derive(serde::Deserialize)]
+    struct Reference {
+        info: ReferenceInfo,
+    }
+    #[derive(serde::Deserialize)]
+    struct ReferenceInfo {
+        #[serde(rename = "piece length")]
+        piece_length: u64,
+    }
 
-            if args.announce.is_empty() {
-                if let Some(announce) = &profile.announce {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Announce:").dim(), announce.join(", "));
-                    }
-                    args.announce = announce.clone();
-                }
-            }
+    let content = std::fs::read(path)
+        .with_context(|| format!("Failed to read torrent file: {}", path.display()))?;
+    let reference: Reference = serde_bencode::from_bytes(&content)
+        .with_context(|| format!("No piece length found in {}", path.display()))?;
+    Ok(reference.info.piece_length)
+}
 
-            if args.comment.is_none() {
-                if let Some(comment) = &profile.comment {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Comment:").dim(), comment);
-                    }
-                    args.comment = Some(comment.clone());
-                }
-            }
+/// Fill unset options from an existing torrent so a companion torrent matches it
+fn apply_like(args: &mut CreateArgs, path: &Path) -> Result<()> {
+    let template = read_torrent(path)?;
 
-            if !args.private {
-                if let Some(true) = profile.private {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Private:").dim(), true);
-                    }
-                    args.private = true;
-                }
-            }
+    if args.piece_length.is_none() {
+        args.piece_length = Some(PieceLength::Bytes(template.info.piece_length));
+    }
+    if template.info.private == Some(1) && !args.public {
+        args.private = true;
+    }
+    if args.source_string.is_none() {
+        args.source_string = template.info.source.clone();
+    }
+    if args.announce.is_empty() {
+        // One --announce value per tier, URLs within a tier comma-separated
+        args.announce = template
+            .trackers()
+            .into_iter()
+            .map(|tier| tier.join(","))
+            .collect();
+    }
+    Ok(())
+}
 
-            if args.piece_length.is_none() {
-                if let Some(piece_length) = profile.piece_length {
-                    if !args.json {
-                        eprintln!(
-                            "  {:<15} 2^{} ({})",
-                            style("Piece Length:").dim(),
-                            piece_length,
-                            HumanBytes(1u64 << piece_length)
-                        );
-                    }
-                    args.piece_length = Some(piece_length);
-                }
-            }
+/// Fill in the options `args` leaves unset from a config profile. Only a profile the
+/// user picked with `-P` is announced; the implicit default is logged at debug level.
+fn apply_profile(args: &mut CreateArgs, name: &str, profile: &Profile, explicit: bool) {
+    if !explicit {
+        log::debug!("Applying profile: {}", name);
+    } else if !args.json {
+        eprintln!(
+            "{} Using profile: {}",
+            style("ℹ️").blue(),
+            style(name).bold()
+        );
+    }
 
-            if args.threads.is_none() {
-                if let Some(threads) = profile.threads {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Threads:").dim(), threads);
-                    }
-                    args.threads = Some(threads);
-                }
-            }
+    if args.announce.is_empty()
+        && let Some(announce) = profile.announce_args()
+    {
+        if !args.json {
+            eprintln!(
+                "  {:<15} {}",
+                style("Announce:").dim(),
+                announce.join(" | ")
+            );
+        }
+        args.announce = announce;
+    }
 
-            if args.web_seed.is_empty() {
-                if let Some(web_seed) = &profile.web_seed {
-                    if !args.json {
-                        eprintln!(
-                            "  {:<15} {}",
-                            style("Web Seeds:").dim(),
-                            web_seed.join(", ")
-                        );
-                    }
-                    args.web_seed = web_seed.clone();
-                }
-            }
+    if args.comment.is_none()
+        && args.comment_template.is_none()
+        && let Some(comment) = &profile.comment
+    {
+        if !args.json {
+            eprintln!("  {:<15} {}", style("Comment:").dim(), comment);
+        }
+        args.comment = Some(comment.clone());
+    }
 
-            if !args.cross_seed {
-                if let Some(true) = profile.cross_seed {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Cross-seed:").dim(), true);
-                    }
-                    args.cross_seed = true;
+    // `private = false` is an explicit opt-out, like --public
+    if !args.private && !args.public {
+        match profile.private {
+            Some(true) => {
+                if !args.json {
+                    eprintln!("  {:<15} true", style("Private:").dim());
                 }
+                args.private = true;
             }
-
-            // Handle mode flags (v2/hybrid)
-            // If neither v2 nor hybrid is set in args, check profile
-            if !args.v2 && !args.hybrid {
-                if let Some(true) = profile.v2 {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Mode:").dim(), "V2");
-                    }
-                    args.v2 = true;
-                } else if let Some(true) = profile.hybrid {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Mode:").dim(), "Hybrid");
-                    }
-                    args.hybrid = true;
+            Some(false) => {
+                if !args.json {
+                    eprintln!("  {:<15} false", style("Private:").dim());
                 }
+                args.public = true;
             }
+            None => {}
+        }
+    }
 
-            if args.exclude.is_empty() {
-                if let Some(exclude) = &profile.exclude {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Exclude:").dim(), exclude.join(", "));
-                    }
-                    args.exclude = exclude.clone();
-                }
-            }
+    if args.piece_length.is_none()
+        && let Some(piece_length) = profile.piece_length
+    {
+        if !args.json {
+            eprintln!(
+                "  {:<15} 2^{} ({})",
+                style("Piece Length:").dim(),
+                piece_length,
+                HumanBytes(1u64 << piece_length)
+            );
+        }
+        args.piece_length = Some(PieceLength::Exponent(piece_length));
+    }
 
-            if args.source_string.is_none() {
-                if let Some(source) = &profile.source_string {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("Source:").dim(), source);
-                    }
-                    args.source_string = Some(source.clone());
-                }
-            }
+    if args.threads.is_none()
+        && let Some(threads) = profile.threads
+    {
+        if !args.json {
+            eprintln!("  {:<15} {}", style("Threads:").dim(), threads);
+        }
+        args.threads = Some(threads);
+    }
 
-            if !args.no_date {
-                if let Some(true) = profile.no_date {
-                    if !args.json {
-                        eprintln!("  {:<15} {}", style("No Date:").dim(), true);
-                    }
-                    args.no_date = true;
-                }
-            }
+    if args.web_seed.is_empty()
+        && let Some(web_seed) = &profile.web_seed
+    {
+        if !args.json {
+            eprintln!(
+                "  {:<15} {}",
+                style("Web Seeds:").dim(),
+                web_seed.join(", ")
+            );
+        }
+        args.web_seed = web_seed.clone();
+    }
 
+    if !args.cross_seed
+        && let Some(true) = profile.cross_seed
+    {
+        if !args.json {
+            eprintln!("  {:<15} true", style("Cross-seed:").dim());
+        }
+        args.cross_seed = true;
+    }
+
+    // Handle mode flags (v1/v2/hybrid)
+    // If no mode flag is set in args, check profile
+    if !args.v1 && !args.v2 && !args.hybrid {
+        if let Some(true) = profile.v2 {
+            if !args.json {
+                eprintln!("  {:<15} V2", style("Mode:").dim());
+            }
+            args.v2 = true;
+        } else if let Some(true) = profile.hybrid {
             if !args.json {
-                eprintln!();
+                eprintln!("  {:<15} Hybrid", style("Mode:").dim());
             }
+            args.hybrid = true;
+        }
+    }
+
+    if args.exclude.is_empty()
+        && let Some(exclude) = &profile.exclude
+    {
+        if !args.json {
+            eprintln!("  {:<15} {}", style("Exclude:").dim(), exclude.join(", "));
+        }
+        args.exclude = exclude.clone();
+    }
+
+    if args.source_string.is_none()
+        && let Some(source) = &profile.source_string
+    {
+        if !args.json {
+            eprintln!("  {:<15} {}", style("Source:").dim(), source);
+        }
+        args.source_string = Some(source.clone());
+    }
+
+    if !args.no_date
+        && let Some(true) = profile.no_date
+    {
+        if !args.json {
+            eprintln!("  {:<15} true", style("No Date:").dim());
+        }
+        args.no_date = true;
+    }
+
+    if !args.json {
+        eprintln!();
+    }
+}
+
+fn cmd_create(mut args: CreateArgs, config: &Config) -> Result<()> {
+    if args.print_backend {
+        println!("{}", hash_backend());
+        return Ok(());
+    }
+
+    // If source is missing, run interactive mode
+    if args.source.is_none() && args.from_url.is_none() {
+        if let Some(new_args) = interactive_create::run(config.clone())? {
+            args = new_args;
         } else {
-            anyhow::bail!("Profile '{}' not found in configuration", profile_name);
+            return Ok(()); // User cancelled
+        }
+    }
+
+    // Inherited metadata wins over the profile, explicit flags win over both
+    if let Some(reference) = args.cross_seed_from.clone() {
+        apply_cross_seed_from(&mut args, &reference)?;
+    }
+    if let Some(reference) = &args.piece_length_from {
+        args.piece_length = Some(PieceLength::Bytes(read_piece_length(reference)?));
+    }
+    if let Some(template) = args.like.clone() {
+        apply_like(&mut args, &template)?;
+    }
+
+    // With --merge-trackers the profiles pick their trackers as if none were given,
+    // and the explicit ones are added back afterwards
+    let extra_trackers = if args.merge_trackers {
+        std::mem::take(&mut args.announce)
+    } else {
+        Vec::new()
+    };
+
+    // A selected profile fills in what the flags left unset, then `[profiles.default]`
+    // fills in whatever is still unset
+    if let Some(profile_name) = args.profile.clone() {
+        let profile = config.profiles.get(&profile_name).ok_or_else(|| {
+            anyhow::anyhow!("Profile '{}' not found in configuration", profile_name)
+        })?;
+        apply_profile(&mut args, &profile_name, profile, true);
+    }
+    if args.profile.as_deref() != Some(DEFAULT_PROFILE)
+        && let Some(profile) = config.profiles.get(DEFAULT_PROFILE)
+    {
+        apply_profile(&mut args, DEFAULT_PROFILE, profile, false);
+    }
+    for tier in extra_trackers {
+        if !args.announce.contains(&tier) {
+            args.announce.push(tier);
+        }
+    }
+    // Unlike a profile's excludes, these add to whatever was chosen rather than
+    // filling in when nothing was
+    if !args.no_default_excludes {
+        for pattern in &config.defaults.exclude {
+            if !args.exclude.contains(pattern) {
+                args.exclude.push(pattern.clone());
+            }
         }
     }
 
@@ -221,81 +386,287 @@ fn cmd_create(mut args: CreateArgs, config: &Config) -> Result<()> {
     let use_json = args.json;
     
     // Ensure source is present
-    let source = args.source.clone().ok_or_else(|| anyhow::anyhow!("No source selected"))?;
-
-    // Determine output file path
-    let output_path = if let Some(path) = args.output.clone() {
-        path
-    } else {
-        let name = args.name.clone().unwrap_or_else(|| {
-            source
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("output")
-                .to_string()
-        });
-        PathBuf::from(format!("{}.torrent", name))
-    };
-
-    let is_stdout = output_path.to_str() == Some("-");
+    let source = args
+        .source
+        .clone()
+        .or_else(|| args.from_url.clone().map(PathBuf::from))
+        .ok_or_else(|| anyhow::anyhow!("No source selected"))?;
 
     // Convert args to options
-    let options = args.clone().into_options();
+    let options = args.clone().into_options()?;
     let mode = options.mode; // Capture mode before options is moved into TorrentBuilder
     let is_dry_run = options.dry_run;
 
     // Build the torrent
     let mut builder = TorrentBuilder::new(source.clone(), options)
-        .with_output_file(output_path.clone())
         .with_verbose(verbose)
         .with_progress(!use_json);
 
+    // Determine output file path
+    let output_path = if let Some(path) = args.output.clone() {
+        path
+    } else {
+        let name = match args.name.clone() {
+            Some(name) => name,
+            None => builder.torrent_name()?,
+        };
+        let extension = match args.emit {
+            Emit::Torrent => "torrent",
+            Emit::Json => "json",
+        };
+        let file_name = format!("{}.{}", name, extension);
+        match config.output_directory(args.profile.as_deref()) {
+            Some(dir) => {
+                std::fs::create_dir_all(&dir).with_context(|| {
+                    format!("Failed to create output directory: {}", dir.display())
+                })?;
+                dir.join(file_name)
+            }
+            None => PathBuf::from(file_name),
+        }
+    };
+
+    let is_stdout = output_path.to_str() == Some("-");
+
+    // A pattern's final path isn't known until the torrent is hashed
+    if args.output_pattern.is_none() {
+        builder = builder.with_output_file(output_path.clone());
+    }
+
     if let Some(t) = threads {
         builder = builder.with_threads(t);
     }
 
+    if let Some(mib) = args.max_memory {
+        builder = builder.with_max_memory(mib.saturating_mul(1 << 20));
+    }
+
+    if let Some(blocks) = args.v2_chunk_blocks {
+        builder = builder.with_v2_chunk_blocks(blocks);
+    }
+
+    if let Some(cache_path) = args.hash_cache.clone() {
+        builder = builder.with_hash_cache(cache_path, args.cache_compress);
+    }
+
+    // Scanned separately so it's also available for a dry run
+    let padding = if args.piece_length_report {
+        if mode != Mode::Hybrid {
+            log::warn!("--piece-length-report: only hybrid torrents are padded; use --hybrid");
+        }
+        Some(builder.padding_report()?)
+    } else {
+        None
+    };
+
     if is_dry_run {
         builder.dry_run()?;
+        if let Some(report) = &padding {
+            if use_json {
+                println!("{}", serde_json::to_string_pretty(report)?);
+            } else {
+                print_padding(report);
+            }
+        }
         return Ok(());
     }
 
-    let torrent = builder.build()?;
+    // Ctrl-C stops hashing cleanly, before anything is written. Scanning and the
+    // like don't check the flag, so a second Ctrl-C exits outright.
+    let cancel = Cancellation::new();
+    {
+        let cancel = cancel.clone();
+        ctrlc::set_handler(move || {
+            if cancel.is_cancelled() {
+                std::process::exit(130);
+            }
+            cancel.cancel();
+        })
+        .context("Failed to install Ctrl-C handler")?;
+    }
+    let (mut torrent, timings) = builder.with_cancellation(cancel).build_timed()?;
 
-    // Serialize to bencode
-    let bencode_data =
-        serde_bencode::to_bytes(&torrent).context("Failed to serialize torrent to bencode")?;
+    // Trackers live outside the info dict too, so this doesn't change the info hash
+    if args.announce_list_flatten {
+        torrent.flatten_trackers();
+    }
 
-    // Write to file or stdout
-    if is_stdout {
-        let mut stdout = std::io::stdout();
-        stdout
-            .write_all(&bencode_data)
-            .context("Failed to write torrent to stdout")?;
+    // The comment lives outside the info dict, so filling it in doesn't change the info hash
+    if let Some(template) = &args.comment_template {
+        torrent.comment = Some(expand_comment_template(template, &torrent));
+    }
+    if let Some(comment) = &torrent.comment {
+        torrent.comment = Some(escape_comment(comment, args.escape_comment));
+    }
+
+    if args.date_comment
+        && let Some(stamp) = torrent
+            .creation_date
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+    {
+        let line = format!("Created {} (UTC)", stamp.format("%Y-%m-%dT%H:%M:%SZ"));
+        torrent.comment = Some(match torrent.comment.take() {
+            Some(comment) if !comment.is_empty() => format!("{}\n{}", comment, line),
+            _ => line,
+        });
+    }
+
+    let output_path = match &args.output_pattern {
+        Some(pattern) => {
+            let path = expand_output_pattern(pattern, &torrent);
+            if let Some(parent) = path.parent()
+                && !parent.as_os_str().is_empty()
+            {
+                std::fs::create_dir_all(parent).with_context(|| {
+                    format!("Failed to create output directory: {}", parent.display())
+                })?;
+            }
+            path
+        }
+        None => output_path,
+    };
+
+    // Piece layers sit outside the info dict, so moving them out keeps the info hash
+    let external_layers = if args.piece_layers_external {
+        let layers = torrent.split_piece_layers();
+        if layers.is_none() {
+            log::warn!("--piece-layers-external: this torrent has no V2 piece layers to split off");
+        }
+        layers
     } else {
-        if verbose && !use_json {
-            eprintln!("Writing to: {}", output_path.display());
+        None
+    };
+
+    // JSON is built up front; bencode is streamed straight to the output
+    let json_payload = match args.emit {
+        Emit::Torrent => None,
+        Emit::Json => {
+            let json = torrent
+                .to_json()
+                .context("Failed to convert torrent to JSON")?;
+            let mut data = serde_json::to_vec_pretty(&json)?;
+            data.push(b'\n');
+            Some(data)
+        }
+    };
+    let write_payload = |mut writer: Box<dyn Write>| -> Result<()> {
+        match &json_payload {
+            Some(data) => {
+                writer.write_all(data)?;
+                writer.flush()?;
+                Ok(())
+            }
+            None => torrent.write_to(writer),
+        }
+    };
+
+    // Side files sit next to the torrent, or are named after it when writing to stdout
+    let side_path = |extension: &str| {
+        if is_stdout {
+            PathBuf::from(format!("{}.{}", torrent.info.name, extension))
+        } else {
+            output_path.with_extension(extension)
         }
+    };
+    let layers_path = external_layers.as_ref().map(|_| side_path("layers"));
+    let magnet_path = args.write_magnet.then(|| side_path("magnet"));
+    let summary_path = args.write_summary.then(|| {
+        if is_stdout {
+            PathBuf::from(format!("{}.torrent.txt", torrent.info.name))
+        } else {
+            let mut path = output_path.clone().into_os_string();
+            path.push(".txt");
+            PathBuf::from(path)
+        }
+    });
+
+    // Everything is opened before anything is written, so an existing side file
+    // without -f can't fail the run after the torrent is already on disk
+    let mut paths: Vec<&Path> = Vec::new();
+    if !is_stdout {
+        paths.push(&output_path);
+    }
+    paths.extend(
+        [&layers_path, &magnet_path, &summary_path]
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path),
+    );
+    let mut files = create_output_files(&paths, force)?.into_iter();
+    let torrent_file = if is_stdout { None } else { files.next() };
+    let layers_file = layers_path.as_ref().and_then(|_| files.next());
+    let magnet_file = magnet_path.as_ref().and_then(|_| files.next());
+    let summary_file = summary_path.as_ref().and_then(|_| files.next());
 
-        let mut output_file = if force {
-            File::create(&output_path).context("Failed to create output file")?
+    // Write to file or stdout
+    match torrent_file {
+        None => write_payload(Box::new(std::io::stdout().lock()))
+            .context("Failed to write torrent to stdout")?,
+        Some(file) => {
+            if verbose && !use_json {
+                eprintln!("Writing to: {}", output_path.display());
+            }
+            // A failed write would leave a truncated torrent behind, along with
+            // the side files that were opened for it but never written
+            if let Err(err) = write_payload(Box::new(BufWriter::new(file))) {
+                for path in &paths {
+                    let _ = std::fs::remove_file(path);
+                }
+                return Err(err.context("Failed to write torrent file"));
+            }
+        }
+    }
+
+    if args.rehash_verify {
+        if is_stdout || args.emit != Emit::Torrent {
+            log::warn!("--rehash-verify only checks .torrent files written to disk; skipped");
         } else {
-            File::options()
-                .write(true)
-                .create_new(true)
-                .open(&output_path)
-                .with_context(|| {
-                    format!(
-                        "Failed to create output file (use -f to overwrite): {}",
-                        output_path.display()
-                    )
-                })?
-        };
+            check_written_torrent(&output_path, &torrent)?;
+            if verbose && !use_json {
+                eprintln!("Self-check passed: {}", output_path.display());
+            }
+        }
+    }
 
-        output_file
-            .write_all(&bencode_data)
-            .context("Failed to write torrent file")?;
+    if let (Some(layers), Some(path), Some(mut file)) =
+        (&external_layers, &layers_path, layers_file)
+    {
+        if verbose && !use_json {
+            eprintln!("Writing piece layers to: {}", path.display());
+        }
+        let data = serde_bencode::to_bytes(layers).context("Failed to serialize piece layers")?;
+        file.write_all(&data)
+            .context("Failed to write piece layers file")?;
     }
 
+    if let (Some(path), Some(mut file)) = (&magnet_path, magnet_file) {
+        if verbose && !use_json {
+            eprintln!("Writing magnet link to: {}", path.display());
+        }
+        file.write_all(format!("{}\n", torrent.magnet_link()).as_bytes())
+            .context("Failed to write magnet file")?;
+    }
+
+    if let (Some(path), Some(mut file)) = (&summary_path, summary_file) {
+        if verbose && !use_json {
+            eprintln!("Writing summary to: {}", path.display());
+        }
+        file.write_all(inspect::summary(&torrent, true).as_bytes())
+            .context("Failed to write summary file")?;
+    }
+
+    let duplicates = if args.report_duplicates {
+        match &torrent.info.file_tree {
+            Some(tree) => Some(find_duplicates(tree)),
+            None => {
+                log::warn!("--report-duplicates needs V2 hashes; use --v2 or --hybrid");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     if use_json {
         let summary = TorrentSummary {
             name: torrent.info.name.clone(),
@@ -311,7 +682,10 @@ fn cmd_create(mut args: CreateArgs, config: &Config) -> Result<()> {
             comment: torrent.comment.clone(),
             info_hash_v1: torrent.info_hash_v1().map(hex::encode),
             info_hash_v2: torrent.info_hash_v2().map(hex::encode),
+            info_hash_v2_short: torrent.info_hash_v2_short().map(hex::encode),
             magnet_link: torrent.magnet_link(),
+            duplicates: duplicates.clone(),
+            padding: padding.clone(),
         };
         println!("{}", serde_json::to_string_pretty(&summary)?);
     } else if !is_stdout {
@@ -358,5 +732,268 @@ fn cmd_create(mut args: CreateArgs, config: &Config) -> Result<()> {
         eprintln!("{}", style(torrent.magnet_link()).underlined());
     }
 
+    if !use_json && let Some(groups) = &duplicates {
+        print_duplicates(groups);
+    }
+
+    if !use_json && let Some(report) = &padding {
+        print_padding(report);
+    }
+
+    if args.stats {
+        print_stats(&timings);
+    }
+
     Ok(())
 }
+
+fn print_stats(timings: &BuildTimings) {
+    eprintln!(
+        "\n{} hashed {} ({} bytes) in {:.2?} ({:.1} MiB/s)",
+        style("Stats:").bold(),
+        HumanBytes(timings.bytes_hashed),
+        timings.bytes_hashed,
+        timings.total,
+        timings.mib_per_sec()
+    );
+    eprintln!(
+        "  scan {:.2?}, V1 hashing {:.2?}, V2 hashing {:.2?}",
+        timings.scan, timings.hash_v1, timings.hash_v2
+    );
+}
+
+fn print_duplicates(groups: &[DuplicateGroup]) {
+    if groups.is_empty() {
+        eprintln!("\n{}", style("No duplicate files found").dim());
+        return;
+    }
+
+    let wasted: u64 = groups.iter().map(DuplicateGroup::wasted).sum();
+    eprintln!(
+        "\n{} {} group(s), {} wasted",
+        style("Duplicates:").bold(),
+        groups.len(),
+        HumanBytes(wasted)
+    );
+    for group in groups {
+        eprintln!(
+            "  {} x {}",
+            group.paths.len(),
+            style(HumanBytes(group.length)).dim()
+        );
+        for path in &group.paths {
+            eprintln!("    {}", path);
+        }
+    }
+}
+
+fn print_padding(report: &PaddingReport) {
+    eprintln!(
+        "\n{} {} ({} bytes) across {} file(s), {:.2}% over {} of content at {} pieces",
+        style("Padding:").bold(),
+        HumanBytes(report.padding_size),
+        report.padding_size,
+        report.files.len(),
+        report.overhead_percent,
+        HumanBytes(report.content_size),
+        HumanBytes(report.piece_length)
+    );
+    for file in &report.files {
+        eprintln!(
+            "  {} {}",
+            style(format!("+{}", HumanBytes(file.padding))).dim(),
+            file.path.display()
+        );
+    }
+}
+
+/// Write through a temporary file beside `path` and rename it into place, so a
+/// failed write never leaves a truncated torrent, which matters when `path` is the
+/// input being updated in place
+fn write_replacing(path: &Path, write: impl FnOnce(BufWriter<File>) -> Result<()>) -> Result<()> {
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or(path.as_os_str()));
+    tmp_name.push(".part");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = File::create(&tmp_path)
+        .with_context(|| format!("Failed to create torrent file: {}", tmp_path.display()))
+        .and_then(|file| write(BufWriter::new(file)))
+        .and_then(|()| {
+            std::fs::rename(&tmp_path, path)
+                .with_context(|| format!("Failed to replace torrent file: {}", path.display()))
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Create several output files, removing the ones already created if a later one
+/// can't be, e.g. because it exists and `force` isn't set
+fn create_output_files(paths: &[&Path], force: bool) -> Result<Vec<File>> {
+    let mut files = Vec::with_capacity(paths.len());
+    for (i, path) in paths.iter().enumerate() {
+        match create_output_file(path, force) {
+            Ok(file) => files.push(file),
+            Err(err) => {
+                for created in &paths[..i] {
+                    let _ = std::fs::remove_file(created);
+                }
+                return Err(err);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Create an output file, refusing to overwrite an existing one unless `force` is set
+fn create_output_file(path: &Path, force: bool) -> Result<File> {
+    if force {
+        File::create(path).context("Failed to create output file")
+    } else {
+        File::options()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .with_context(|| {
+                format!(
+                    "Failed to create output file (use -f to overwrite): {}",
+                    path.display()
+                )
+            })
+    }
+}
+
+/// Route library log records to stderr. `RUST_LOG` overrides the default level,
+/// which is `debug` for torrite with `--verbose` and `warn` otherwise.
+fn init_logging(verbose: bool) {
+    let default_filter = if verbose { "torrite=debug" } else { "warn" };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_filter))
+        .format(|buf, record| match record.level() {
+            log::Level::Error => writeln!(buf, "Error: {}", record.args()),
+            log::Level::Warn => writeln!(buf, "Warning: {}", record.args()),
+            _ => writeln!(buf, "{}", record.args()),
+        })
+        .init();
+}
+
+/// V1 info hash as hex, or the V2 one for V2-only torrents
+fn primary_info_hash(torrent: &Torrent) -> String {
+    torrent
+        .info_hash_v1()
+        .map(hex::encode)
+        .or_else(|| torrent.info_hash_v2().map(hex::encode))
+        .unwrap_or_default()
+}
+
+/// Creation date as `YYYY-MM-DD`, falling back to today when `--no-date` is set
+fn creation_date_string(torrent: &Torrent) -> String {
+    torrent
+        .creation_date
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .unwrap_or_else(chrono::Utc::now)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// Number and total size of the content files, ignoring padding files
+fn content_stats(torrent: &Torrent) -> (usize, u64) {
+    fn count_tree(node: &Node) -> usize {
+        match node {
+            Node::File(_) => 1,
+            Node::Directory(children) => children.values().map(count_tree).sum(),
+        }
+    }
+
+    if let Some(files) = &torrent.info.files {
+        let content = files
+            .iter()
+            .filter(|f| !f.attr.as_deref().is_some_and(|a| a.contains('p')));
+        content.fold((0, 0), |(count, size), f| (count + 1, size + f.length))
+    } else if let Some(tree) = &torrent.info.file_tree {
+        (tree.values().map(count_tree).sum(), torrent.total_size())
+    } else {
+        (1, torrent.total_size())
+    }
+}
+
+/// Escape markup in a comment so trackers that render it show the text as written
+fn escape_comment(comment: &str, syntax: CommentEscape) -> String {
+    match syntax {
+        CommentEscape::None => comment.to_string(),
+        // BBCode has no escape character; entities survive its conversion to HTML
+        CommentEscape::Bbcode => comment
+            .replace('&', "&amp;")
+            .replace('[', "&#91;")
+            .replace(']', "&#93;"),
+        CommentEscape::Markdown => {
+            let mut escaped = String::with_capacity(comment.len());
+            for c in comment.chars() {
+                if "\\`*_{}[]()<>#+-!|~".contains(c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        }
+    }
+}
+
+/// Resolve `--comment-template` placeholders against the built torrent
+fn expand_comment_template(template: &str, torrent: &Torrent) -> String {
+    let (files, size) = content_stats(torrent);
+    template
+        .replace("{date}", &creation_date_string(torrent))
+        .replace("{name}", &torrent.info.name)
+        .replace("{size}", &HumanBytes(size).to_string())
+        .replace("{files}", &files.to_string())
+        .replace("{infohash}", &primary_info_hash(torrent))
+}
+
+/// Resolve `--output-pattern` placeholders against the built torrent
+fn expand_output_pattern(pattern: &str, torrent: &Torrent) -> PathBuf {
+    let info_hash = primary_info_hash(torrent);
+    let tracker = torrent
+        .announce
+        .as_deref()
+        .and_then(tracker_host)
+        .unwrap_or("no-tracker");
+    let date = creation_date_string(torrent);
+
+    let expanded = pattern
+        .replace("{name}", &torrent.info.name)
+        .replace("{infohash8}", &info_hash[..info_hash.len().min(8)])
+        .replace("{infohash}", &info_hash)
+        .replace("{tracker}", tracker)
+        .replace("{date}", &date);
+    PathBuf::from(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_comment_bbcode() {
+        assert_eq!(
+            escape_comment("[b]Release[/b] notes", CommentEscape::Bbcode),
+            "&#91;b&#93;Release&#91;/b&#93; notes"
+        );
+        // A literal entity isn't mistaken for an escaped bracket
+        assert_eq!(escape_comment("&#91;", CommentEscape::Bbcode), "&amp;#91;");
+        assert_eq!(
+            escape_comment("[b]as is[/b]", CommentEscape::None),
+            "[b]as is[/b]"
+        );
+    }
+
+    #[test]
+    fn test_escape_comment_markdown() {
+        assert_eq!(
+            escape_comment("*bold* [link](url) 1.0", CommentEscape::Markdown),
+            "\\*bold\\* \\[link\\]\\(url\\) 1.0"
+        );
+        assert_eq!(escape_comment("a\\b", CommentEscape::Markdown), "a\\\\b");
+    }
+}
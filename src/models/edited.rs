@@ -0,0 +1,352 @@
+use serde_bencode::value::Value;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use super::torrent::{Info, Torrent, write_bytes, write_piece_layers};
+
+/// Top-level keys the `Torrent` model reads and writes; any others are carried over
+/// from the original file when saving an edit
+const KNOWN_KEYS: &[&str] = &[
+    "announce",
+    "announce-list",
+    "comment",
+    "created by",
+    "creation date",
+    "info",
+    "url-list",
+    "piece layers",
+    "publisher",
+    "publisher-url",
+];
+
+/// Info dictionary keys the `Info` model reads and writes
+const KNOWN_INFO_KEYS: &[&str] = &[
+    "piece length",
+    "pieces",
+    "name",
+    "private",
+    "files",
+    "length",
+    "source",
+    "x_cross_seed",
+    "meta version",
+    "file tree",
+    "similar",
+    "collections",
+];
+
+/// A top-level value of the torrent being written
+enum Entry<'a> {
+    Value(Value),
+    /// Bencoded bytes copied from the original file
+    Raw(&'a [u8]),
+    PieceLayers,
+}
+
+impl Torrent {
+    /// Write an edited copy of the torrent read from `original`, like [`Torrent::write_to`]
+    /// but keeping keys the model doesn't know about, at any depth, so they aren't
+    /// silently dropped. An info dictionary the edit didn't touch is copied from
+    /// `original` byte for byte, so its info hash can't change.
+    pub fn write_edited(&self, original: &[u8], mut writer: impl Write) -> anyhow::Result<()> {
+        let Ok(original_value) = serde_bencode::from_bytes::<Value>(original) else {
+            return self.write_to(writer);
+        };
+
+        // Everything but the piece layers, which are streamed from `self` instead
+        let head = Torrent {
+            announce: self.announce.clone(),
+            announce_list: self.announce_list.clone(),
+            comment: self.comment.clone(),
+            created_by: self.created_by.clone(),
+            creation_date: self.creation_date,
+            info: self.info.clone(),
+            url_list: self.url_list.clone(),
+            piece_layers: None,
+            publisher: self.publisher.clone(),
+            publisher_url: self.publisher_url.clone(),
+        };
+        let mut merged: Value = serde_bencode::from_bytes(&serde_bencode::to_bytes(&head)?)?;
+        carry_unknown_keys(&original_value, &mut merged, Level::Root);
+        let Value::Dict(merged) = merged else {
+            return self.write_to(writer);
+        };
+
+        let mut entries: BTreeMap<Vec<u8>, Entry> = merged
+            .into_iter()
+            .map(|(key, value)| (key, Entry::Value(value)))
+            .collect();
+        if let Some(raw) = raw_info(original).filter(|raw| self.info_matches(raw)) {
+            entries.insert(b"info".to_vec(), Entry::Raw(raw));
+        }
+        if self.piece_layers.is_some() {
+            entries.insert(b"piece layers".to_vec(), Entry::PieceLayers);
+        }
+
+        writer.write_all(b"d")?;
+        for (key, entry) in entries {
+            write_bytes(&mut writer, &key)?;
+            match entry {
+                Entry::Value(value) => writer.write_all(&serde_bencode::to_bytes(&value)?)?,
+                Entry::Raw(raw) => writer.write_all(raw)?,
+                Entry::PieceLayers => {
+                    if let Some(layers) = &self.piece_layers {
+                        write_piece_layers(&mut writer, layers)?;
+                    }
+                }
+            }
+        }
+        writer.write_all(b"e")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Whether the bencoded info dictionary `raw` reads as the same `Info` as ours
+    fn info_matches(&self, raw: &[u8]) -> bool {
+        let original =
+            serde_bencode::from_bytes::<Info>(raw).and_then(|info| serde_bencode::to_bytes(&info));
+        matches!((original, serde_bencode::to_bytes(&self.info)), (Ok(a), Ok(b)) if a == b)
+    }
+}
+
+/// Where a dictionary sits in the metainfo, which decides the keys the model owns
+#[derive(Clone, Copy)]
+enum Level {
+    Root,
+    Info,
+    FileEntry,
+    /// A V2 file tree directory, keyed by file names rather than field names
+    FileTree,
+    FileNode,
+    FileMetadata,
+}
+
+impl Level {
+    fn known_keys(self) -> &'static [&'static str] {
+        match self {
+            Level::Root => KNOWN_KEYS,
+            Level::Info => KNOWN_INFO_KEYS,
+            Level::FileEntry => &["length", "path", "attr", "mtime"],
+            Level::FileTree => &[],
+            Level::FileNode => &[""],
+            Level::FileMetadata => &["length", "pieces root"],
+        }
+    }
+
+    /// Whether a key missing from the edited dictionary is one the model never read
+    fn is_unknown(self, key: &[u8]) -> bool {
+        // A file or directory missing from the edited tree was renamed away
+        !matches!(self, Level::FileTree) && !self.known_keys().iter().any(|k| k.as_bytes() == key)
+    }
+
+    /// Level of the value under `key`, or of each element if it is a list
+    fn child(self, key: &[u8], value: &Value) -> Option<Level> {
+        match (self, key) {
+            (Level::Root, b"info") => Some(Level::Info),
+            (Level::Info, b"files") => Some(Level::FileEntry),
+            (Level::Info, b"file tree") => Some(Level::FileTree),
+            (Level::FileTree, _) => match value {
+                Value::Dict(node) if node.contains_key(&b""[..]) => Some(Level::FileNode),
+                _ => Some(Level::FileTree),
+            },
+            (Level::FileNode, b"") => Some(Level::FileMetadata),
+            _ => None,
+        }
+    }
+}
+
+/// Copy the keys of `from` the model doesn't know about into `to`, at every depth
+fn carry_unknown_keys(from: &Value, to: &mut Value, level: Level) {
+    let (Value::Dict(from), Value::Dict(to)) = (from, to) else {
+        return;
+    };
+    for (key, value) in from {
+        let Some(edited) = to.get_mut(key) else {
+            if level.is_unknown(key) {
+                to.insert(key.clone(), value.clone());
+            }
+            continue;
+        };
+        let Some(child) = level.child(key, value) else {
+            continue;
+        };
+        match (value, edited) {
+            (Value::List(from), Value::List(to)) => {
+                for (from, to) in from.iter().zip(to) {
+                    carry_unknown_keys(from, to, child);
+                }
+            }
+            (from, to) => carry_unknown_keys(from, to, child),
+        }
+    }
+}
+
+/// The bytes of the `info` value in a bencoded torrent, exactly as stored
+fn raw_info(data: &[u8]) -> Option<&[u8]> {
+    if data.first() != Some(&b'd') {
+        return None;
+    }
+    let mut pos = 1;
+    while *data.get(pos)? != b'e' {
+        let key_end = bencode_end(data, pos)?;
+        let value_end = bencode_end(data, key_end)?;
+        if &data[pos..key_end] == b"4:info" {
+            return Some(&data[key_end..value_end]);
+        }
+        pos = value_end;
+    }
+    None
+}
+
+/// Offset just past the bencoded value starting at `start`
+fn bencode_end(data: &[u8], start: usize) -> Option<usize> {
+    match *data.get(start)? {
+        b'i' => Some(start + data[start..].iter().position(|&b| b == b'e')? + 1),
+        b'l' | b'd' => {
+            let mut pos = start + 1;
+            while *data.get(pos)? != b'e' {
+                pos = bencode_end(data, pos)?;
+            }
+            Some(pos + 1)
+        }
+        b'0'..=b'9' => {
+            let colon = start + data[start..].iter().position(|&b| b == b':')?;
+            let len: usize = std::str::from_utf8(&data[start..colon])
+                .ok()?
+                .parse()
+                .ok()?;
+            let end = colon.checked_add(1)?.checked_add(len)?;
+            (end <= data.len()).then_some(end)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FileEntry;
+    use sha1::{Digest, Sha1};
+
+    fn multi_file_torrent() -> Torrent {
+        Torrent {
+            announce: None,
+            announce_list: None,
+            comment: None,
+            created_by: "test".to_string(),
+            creation_date: None,
+            info: Info {
+                piece_length: 1024,
+                pieces: Some(serde_bytes::ByteBuf::from(vec![0u8; 20])),
+                name: "test".to_string(),
+                private: None,
+                files: Some(vec![FileEntry {
+                    length: 100,
+                    path: vec!["a.bin".to_string()],
+                    attr: None,
+                    mtime: None,
+                }]),
+                length: None,
+                source: None,
+                x_cross_seed: None,
+                meta_version: None,
+                file_tree: None,
+                similar: None,
+                collections: None,
+            },
+            url_list: None,
+            piece_layers: None,
+            publisher: None,
+            publisher_url: None,
+        }
+    }
+
+    fn as_dict(value: &Value) -> &std::collections::HashMap<Vec<u8>, Value> {
+        match value {
+            Value::Dict(dict) => dict,
+            _ => panic!("Expected a dictionary"),
+        }
+    }
+
+    fn as_dict_mut(value: &mut Value) -> &mut std::collections::HashMap<Vec<u8>, Value> {
+        match value {
+            Value::Dict(dict) => dict,
+            _ => panic!("Expected a dictionary"),
+        }
+    }
+
+    /// `torrent` as read from a file with extra keys at the top level, in the info
+    /// dict and in its first file entry
+    fn original_with_unknown_keys(torrent: &Torrent) -> Vec<u8> {
+        let mut original: Value =
+            serde_bencode::from_bytes(&serde_bencode::to_bytes(torrent).unwrap()).unwrap();
+        let root = as_dict_mut(&mut original);
+        root.insert(b"encoding".to_vec(), Value::Bytes(b"UTF-8".to_vec()));
+        let info = as_dict_mut(root.get_mut(&b"info"[..]).unwrap());
+        info.insert(b"x_extra".to_vec(), Value::Int(7));
+        let Some(Value::List(files)) = info.get_mut(&b"files"[..]) else {
+            panic!("Expected a file list");
+        };
+        as_dict_mut(&mut files[0]).insert(
+            b"md5sum".to_vec(),
+            Value::Bytes(b"0123456789abcdef".to_vec()),
+        );
+        serde_bencode::to_bytes(&original).unwrap()
+    }
+
+    fn write_edited(torrent: &Torrent, original: &[u8]) -> Vec<u8> {
+        let mut written = Vec::new();
+        torrent.write_edited(original, &mut written).unwrap();
+        written
+    }
+
+    #[test]
+    fn test_write_edited_keeps_info_hash() {
+        let mut torrent = multi_file_torrent();
+        let original = original_with_unknown_keys(&torrent);
+        let info_hash = |data: &[u8]| Sha1::digest(raw_info(data).unwrap());
+
+        torrent.comment = Some("Edited".to_string());
+        let saved = write_edited(&torrent, &original);
+        assert_eq!(info_hash(&saved), info_hash(&original));
+
+        let saved: Value = serde_bencode::from_bytes(&saved).unwrap();
+        let saved = as_dict(&saved);
+        assert_eq!(saved[&b"comment"[..]], Value::Bytes(b"Edited".to_vec()));
+        assert_eq!(saved[&b"encoding"[..]], Value::Bytes(b"UTF-8".to_vec()));
+    }
+
+    #[test]
+    fn test_write_edited_merges_nested_unknown_keys() {
+        let mut torrent = multi_file_torrent();
+        let original = original_with_unknown_keys(&torrent);
+
+        torrent.info.private = Some(1);
+        let saved: Value = serde_bencode::from_bytes(&write_edited(&torrent, &original)).unwrap();
+        let info = as_dict(&as_dict(&saved)[&b"info"[..]]);
+        assert_eq!(info[&b"private"[..]], Value::Int(1));
+        assert_eq!(info[&b"x_extra"[..]], Value::Int(7));
+        let Value::List(files) = &info[&b"files"[..]] else {
+            panic!("Expected a file list");
+        };
+        assert_eq!(
+            as_dict(&files[0])[&b"md5sum"[..]],
+            Value::Bytes(b"0123456789abcdef".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_write_edited_without_unknown_keys_matches_write_to() {
+        let mut torrent = multi_file_torrent();
+        let original = serde_bencode::to_bytes(&torrent).unwrap();
+        torrent.url_list = Some(vec!["http://seed/".to_string()]);
+        torrent.piece_layers = Some(BTreeMap::from([(
+            serde_bytes::ByteBuf::from(vec![1u8; 32]),
+            serde_bytes::ByteBuf::from(vec![8u8; 64]),
+        )]));
+
+        let mut expected = Vec::new();
+        torrent.write_to(&mut expected).unwrap();
+        assert_eq!(write_edited(&torrent, &original), expected);
+    }
+}
@@ -14,6 +14,8 @@ pub struct FileInfo {
     pub start_offset: u64,
     /// Whether this is a padding file (virtual)
     pub is_padding: bool,
+    /// Byte offset of the file's data within `full_path` (non-zero for archive entries)
+    pub data_offset: u64,
 }
 
 /// File entry in multi-file mode
@@ -23,4 +25,8 @@ pub struct FileEntry {
     pub path: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attr: Option<String>,
+    /// Modification time in seconds since the Unix epoch. Non-standard, only written
+    /// with `--store-mtime`, and part of the info hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<i64>,
 }
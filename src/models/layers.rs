@@ -0,0 +1,72 @@
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use std::collections::{BTreeMap, HashSet};
+
+use super::torrent::Torrent;
+use crate::tree::tree_files;
+
+/// A V2 torrent's `piece layers`, stored in a companion `.layers` file
+///
+/// This is a torrite convention, not part of BEP 52: clients expect the layers
+/// inside the `.torrent`, so split torrents must be merged back with
+/// `torrite recalc --layers` (or [`Torrent::merge_piece_layers`]) before use.
+/// The layers sit outside the info dict, so neither form changes the info hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PieceLayersFile {
+    /// The full V2 info hash of the torrent these layers were split from
+    #[serde(rename = "info hash")]
+    pub info_hash: ByteBuf,
+    #[serde(rename = "piece layers")]
+    pub piece_layers: BTreeMap<ByteBuf, ByteBuf>,
+}
+
+impl Torrent {
+    /// Move the `piece layers` out of the torrent, returning them with the V2
+    /// info hash they belong to. `None` if there's nothing to split off.
+    pub fn split_piece_layers(&mut self) -> Option<PieceLayersFile> {
+        let info_hash = self.info_hash_v2()?;
+        let piece_layers = self.piece_layers.take().filter(|l| !l.is_empty())?;
+        Some(PieceLayersFile {
+            info_hash: ByteBuf::from(info_hash.to_vec()),
+            piece_layers,
+        })
+    }
+
+    /// Put split-off piece layers back. Fails if they were taken from a different
+    /// torrent or hold a layer for a pieces root that isn't in the file tree.
+    pub fn merge_piece_layers(&mut self, layers: PieceLayersFile) -> Result<()> {
+        let Some(info_hash) = self.info_hash_v2() else {
+            bail!("Only V2 and hybrid torrents have piece layers");
+        };
+        if layers.info_hash.as_ref() != info_hash.as_slice() {
+            bail!(
+                "Piece layers belong to info hash {}, not {}",
+                hex::encode(&layers.info_hash),
+                hex::encode(info_hash)
+            );
+        }
+
+        let roots: HashSet<&[u8]> = self
+            .info
+            .file_tree
+            .as_ref()
+            .map(|tree| {
+                tree_files(tree)
+                    .into_iter()
+                    .map(|(_, metadata)| metadata.pieces_root.as_ref())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(root) = layers
+            .piece_layers
+            .keys()
+            .find(|root| !roots.contains(root.as_slice()))
+        {
+            bail!("Piece layer for unknown pieces root {}", hex::encode(root));
+        }
+
+        self.piece_layers = Some(layers.piece_layers);
+        Ok(())
+    }
+}
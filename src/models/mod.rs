@@ -1,7 +1,16 @@
+mod edited;
 mod file;
+mod layers;
+mod options;
+mod padding;
+mod rename;
 mod torrent;
 
 pub use file::{FileEntry, FileInfo};
+pub use layers::PieceLayersFile;
+pub use options::TorrentOptionsBuilder;
+pub use padding::{FilePadding, PaddingReport};
 pub use torrent::{
     FileMetadata, FileNode, Info, Mode, Node, Torrent, TorrentOptions, TorrentSummary,
+    validate_torrent_name,
 };
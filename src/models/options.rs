@@ -0,0 +1,556 @@
+use anyhow::{Result, bail};
+use std::path::PathBuf;
+
+use super::torrent::{Mode, TorrentOptions, validate_torrent_name};
+use crate::config::check_piece_exp;
+use crate::trackers::{TRACKER_PRESETS, find_tracker_preset};
+
+impl TorrentOptions {
+    /// Reject a `meta version` override on a V1 torrent, which has no meta version
+    pub(crate) fn check_meta_version(&self) -> Result<()> {
+        if self.meta_version.is_some() && self.mode == Mode::V1 {
+            bail!("--meta-version needs a V2 or hybrid torrent; V1 torrents have no meta version");
+        }
+        Ok(())
+    }
+
+    /// Start building options fluently, validating them on [`build`](TorrentOptionsBuilder::build)
+    ///
+    /// ```
+    /// use torrite::{Mode, TorrentOptions};
+    ///
+    /// let options = TorrentOptions::builder()
+    ///     .mode(Mode::Hybrid)
+    ///     .private(true)
+    ///     .announce("https://tracker.example/announce")
+    ///     .comment("Release notes")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(options.mode, Mode::Hybrid);
+    /// assert!(options.private);
+    /// ```
+    pub fn builder() -> TorrentOptionsBuilder {
+        TorrentOptionsBuilder::default()
+    }
+}
+
+/// Fluent builder for [`TorrentOptions`]
+///
+/// Unset fields keep their [`Default`] values. Conflicting settings are rejected
+/// by [`build`](Self::build) rather than surfacing halfway through hashing:
+///
+/// ```
+/// use torrite::TorrentOptions;
+///
+/// assert!(TorrentOptions::builder().private(true).public(true).build().is_err());
+/// assert!(TorrentOptions::builder().name("a/b").build().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TorrentOptionsBuilder {
+    options: TorrentOptions,
+}
+
+impl TorrentOptionsBuilder {
+    /// Torrent format: V1, V2 or hybrid
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.options.mode = mode;
+        self
+    }
+
+    /// Piece length as a power of two exponent (`18` for 256 KiB)
+    pub fn piece_length(mut self, exponent: u32) -> Self {
+        self.options.piece_length = Some(exponent);
+        self
+    }
+
+    /// Lower bound on the final piece length exponent
+    pub fn min_piece_exp(mut self, exponent: u32) -> Self {
+        self.options.min_piece_exp = Some(exponent);
+        self
+    }
+
+    /// Lower an automatically chosen piece length until there are at least this many pieces
+    pub fn min_pieces(mut self, min_pieces: u64) -> Self {
+        self.options.min_pieces = Some(min_pieces);
+        self
+    }
+
+    /// Upper bound on the final piece length exponent
+    pub fn max_piece_exp(mut self, exponent: u32) -> Self {
+        self.options.max_piece_exp = Some(exponent);
+        self
+    }
+
+    /// Set the private flag (BEP 27), limiting peers to the trackers
+    pub fn private(mut self, private: bool) -> Self {
+        self.options.private = private;
+        self
+    }
+
+    /// Keep the torrent public even when a tracker requires the private flag
+    pub fn public(mut self, public: bool) -> Self {
+        self.options.public = public;
+        self
+    }
+
+    /// The torrent's `comment`
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.options.comment = Some(comment.into());
+        self
+    }
+
+    /// Add an announce tier; URLs within a tier are comma-separated
+    pub fn announce(mut self, tier: impl Into<String>) -> Self {
+        self.options.announce.push(tier.into());
+        self
+    }
+
+    /// Only write the first tracker, leaving out `announce-list`
+    pub fn no_announce_list(mut self, no_announce_list: bool) -> Self {
+        self.options.no_announce_list = no_announce_list;
+        self
+    }
+
+    /// Shuffle the URLs within each tracker tier
+    pub fn announce_tier_shuffle(mut self, shuffle: bool) -> Self {
+        self.options.announce_tier_shuffle = shuffle;
+        self
+    }
+
+    /// Use a fixed seed for random choices so builds are reproducible
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.options.deterministic = deterministic;
+        self
+    }
+
+    /// Apply a tracker's policy by short name (see [`TRACKER_PRESETS`])
+    pub fn tracker_preset(mut self, preset: impl Into<String>) -> Self {
+        self.options.tracker_preset = Some(preset.into());
+        self
+    }
+
+    /// Add a web seed URL
+    pub fn web_seed(mut self, url: impl Into<String>) -> Self {
+        self.options.web_seed.push(url.into());
+        self
+    }
+
+    /// The `source` string stored in the info dictionary
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.options.source_string = Some(source.into());
+        self
+    }
+
+    /// Add a random `x_cross_seed` key so the info hash is unique
+    pub fn cross_seed(mut self, cross_seed: bool) -> Self {
+        self.options.cross_seed = cross_seed;
+        self
+    }
+
+    /// Override the `created by` string; an empty one is left out
+    pub fn created_by(mut self, created_by: impl Into<String>) -> Self {
+        self.options.created_by = Some(created_by.into());
+        self
+    }
+
+    /// Set the top-level `publisher`
+    pub fn publisher(mut self, publisher: impl Into<String>) -> Self {
+        self.options.publisher = Some(publisher.into());
+        self
+    }
+
+    /// Set the top-level `publisher-url`
+    pub fn publisher_url(mut self, url: impl Into<String>) -> Self {
+        self.options.publisher_url = Some(url.into());
+        self
+    }
+
+    /// Leave out the creation date
+    pub fn no_date(mut self, no_date: bool) -> Self {
+        self.options.no_date = no_date;
+        self
+    }
+
+    /// Creation date as a Unix timestamp, instead of the current time
+    pub fn creation_date(mut self, timestamp: i64) -> Self {
+        self.options.creation_date = Some(timestamp);
+        self
+    }
+
+    /// Torrent name, instead of the source's basename
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.options.name = Some(name.into());
+        self
+    }
+
+    /// Add an exclude glob pattern
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.options.exclude.push(pattern.into());
+        self
+    }
+
+    /// Add an include glob pattern; once any are set, only matching files are kept
+    pub fn include_only(mut self, pattern: impl Into<String>) -> Self {
+        self.options.include_only.push(pattern.into());
+        self
+    }
+
+    /// Keep dotfiles and dot-directories, which are skipped by default
+    pub fn include_hidden(mut self, include: bool) -> Self {
+        self.options.include_hidden = include;
+        self
+    }
+
+    /// Don't warn about `.torrent` files among the content
+    pub fn allow_torrent_files(mut self, allow: bool) -> Self {
+        self.options.allow_torrent_files = allow;
+        self
+    }
+
+    /// Leave zero-byte files out of the torrent
+    pub fn skip_empty_files(mut self, skip: bool) -> Self {
+        self.options.skip_empty_files = skip;
+        self
+    }
+
+    /// Write each file's modification time into its file entry (non-standard)
+    pub fn store_mtime(mut self, store: bool) -> Self {
+        self.options.store_mtime = store;
+        self
+    }
+
+    /// Record empty directories as zero-length `.keep` files
+    pub fn keep_empty_dirs(mut self, keep: bool) -> Self {
+        self.options.keep_empty_dirs = keep;
+        self
+    }
+
+    /// Only include the source directory's top-level files
+    pub fn no_recursion(mut self, no_recursion: bool) -> Self {
+        self.options.no_recursion = no_recursion;
+        self
+    }
+
+    /// Make a single-file torrent when the source directory holds exactly one file
+    pub fn flatten_single(mut self, flatten: bool) -> Self {
+        self.options.flatten_single = flatten;
+        self
+    }
+
+    /// Drop the single top-level directory inside the source from stored paths
+    pub fn strip_root(mut self, strip: bool) -> Self {
+        self.options.strip_root = strip;
+        self
+    }
+
+    /// Store file paths relative to this directory
+    pub fn relative_to(mut self, base: impl Into<PathBuf>) -> Self {
+        self.options.relative_to = Some(base.into());
+        self
+    }
+
+    /// Skip padding files in hybrid torrents (breaks BEP 52 piece alignment)
+    pub fn no_padding(mut self, no_padding: bool) -> Self {
+        self.options.no_padding = no_padding;
+        self
+    }
+
+    /// Write a nonstandard `meta version`, for client testing (V2 and hybrid only)
+    pub fn meta_version(mut self, version: u8) -> Self {
+        self.options.meta_version = Some(version);
+        self
+    }
+
+    /// Add a BEP 38 `similar` info hash (20 or 32 raw bytes)
+    pub fn similar(mut self, info_hash: impl Into<Vec<u8>>) -> Self {
+        self.options.similar.push(info_hash.into());
+        self
+    }
+
+    /// Add a BEP 38 collection name
+    pub fn collection(mut self, name: impl Into<String>) -> Self {
+        self.options.collections.push(name.into());
+        self
+    }
+
+    /// Treat the source as a zip/tar archive and hash its entries in place
+    pub fn from_archive(mut self, from_archive: bool) -> Self {
+        self.options.from_archive = from_archive;
+        self
+    }
+
+    /// Treat the source as an HTTP(S) URL and hash the file as it downloads
+    pub fn from_url(mut self, from_url: bool) -> Self {
+        self.options.from_url = from_url;
+        self
+    }
+
+    /// Hash exactly this many bytes of a single-file source, e.g. a block device
+    pub fn length(mut self, length: u64) -> Self {
+        self.options.length = Some(length);
+        self
+    }
+
+    /// Turn tracker requirement and portability warnings into errors
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
+        self
+    }
+
+    /// Re-check that a hybrid torrent's V1 file list and V2 file tree agree
+    pub fn force_v1_name_compat(mut self, force: bool) -> Self {
+        self.options.force_v1_name_compat = force;
+        self
+    }
+
+    /// Pick the piece length and report the layout without hashing
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.options.dry_run = dry_run;
+        self
+    }
+
+    /// Check the options for conflicts and return them
+    pub fn build(self) -> Result<TorrentOptions> {
+        let options = self.options;
+
+        if options.private && options.public {
+            bail!("A torrent can't be both private and public");
+        }
+        for exp in [
+            options.piece_length,
+            options.min_piece_exp,
+            options.max_piece_exp,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            check_piece_exp(exp).map_err(anyhow::Error::msg)?;
+        }
+        if let (Some(min), Some(max)) = (options.min_piece_exp, options.max_piece_exp)
+            && min > max
+        {
+            bail!(
+                "Minimum piece length exponent {} is above the maximum {}",
+                min,
+                max
+            );
+        }
+        if let Some(preset) = &options.tracker_preset
+            && find_tracker_preset(preset).is_none()
+        {
+            let known: Vec<&str> = TRACKER_PRESETS.iter().map(|(name, _)| *name).collect();
+            bail!(
+                "Unknown tracker preset '{}' (known: {})",
+                preset,
+                known.join(", ")
+            );
+        }
+        if let Some(name) = &options.name {
+            validate_torrent_name(name.trim())?;
+        }
+        if let Some(hash) = options
+            .similar
+            .iter()
+            .find(|h| ![20, 32].contains(&h.len()))
+        {
+            bail!(
+                "A similar info hash must be 20 or 32 bytes, not {}",
+                hash.len()
+            );
+        }
+        // The same pairs the command line refuses
+        let length = options.length.is_some();
+        let relative_to = options.relative_to.is_some();
+        let from_url = options.from_url;
+        for (first, second, conflict) in [
+            ("--length", "--from-archive", length && options.from_archive),
+            ("--length", "--from-url", length && from_url),
+            ("--length", "--relative-to", length && relative_to),
+            ("--length", "--store-mtime", length && options.store_mtime),
+            (
+                "--from-url",
+                "--from-archive",
+                from_url && options.from_archive,
+            ),
+            ("--from-url", "--relative-to", from_url && relative_to),
+            ("--from-url", "--dry-run", from_url && options.dry_run),
+            (
+                "--from-url",
+                "--store-mtime",
+                from_url && options.store_mtime,
+            ),
+        ] {
+            if conflict {
+                bail!("{} can't be combined with {}", first, second);
+            }
+        }
+        options.check_meta_version()?;
+
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_sets_every_field() {
+        let options = TorrentOptions::builder()
+            .mode(Mode::V2)
+            .piece_length(20)
+            .min_piece_exp(16)
+            .min_pieces(500)
+            .max_piece_exp(24)
+            .private(true)
+            .comment("comment")
+            .announce("https://a.example/announce")
+            .announce("https://b.example/announce,https://c.example/announce")
+            .no_announce_list(true)
+            .announce_tier_shuffle(true)
+            .deterministic(true)
+            .tracker_preset("ptp")
+            .web_seed("https://seed.example/")
+            .source("SRC")
+            .cross_seed(true)
+            .created_by("mktorrent 1.1")
+            .publisher("Group")
+            .publisher_url("https://group.example/")
+            .no_date(true)
+            .creation_date(1_700_000_000)
+            .name("Release")
+            .exclude("*.nfo")
+            .include_only("*.mkv")
+            .include_hidden(true)
+            .allow_torrent_files(true)
+            .skip_empty_files(true)
+            .keep_empty_dirs(true)
+            .no_recursion(true)
+            .flatten_single(true)
+            .strip_root(true)
+            .relative_to("/data")
+            .no_padding(true)
+            .meta_version(3)
+            .similar([7u8; 20])
+            .collection("Series")
+            .from_archive(true)
+            .strict(true)
+            .force_v1_name_compat(true)
+            .dry_run(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(options.mode, Mode::V2);
+        assert_eq!(options.piece_length, Some(20));
+        assert_eq!(options.min_piece_exp, Some(16));
+        assert_eq!(options.min_pieces, Some(500));
+        assert_eq!(options.max_piece_exp, Some(24));
+        assert!(options.private && !options.public);
+        assert_eq!(options.comment.as_deref(), Some("comment"));
+        assert_eq!(options.announce.len(), 2);
+        assert!(options.no_announce_list);
+        assert!(options.announce_tier_shuffle && options.deterministic);
+        assert_eq!(options.tracker_preset.as_deref(), Some("ptp"));
+        assert_eq!(options.web_seed, vec!["https://seed.example/"]);
+        assert_eq!(options.source_string.as_deref(), Some("SRC"));
+        assert!(options.cross_seed && options.no_date);
+        assert_eq!(options.created_by.as_deref(), Some("mktorrent 1.1"));
+        assert_eq!(options.publisher.as_deref(), Some("Group"));
+        assert_eq!(
+            options.publisher_url.as_deref(),
+            Some("https://group.example/")
+        );
+        assert_eq!(options.creation_date, Some(1_700_000_000));
+        assert_eq!(options.name.as_deref(), Some("Release"));
+        assert_eq!(options.exclude, vec!["*.nfo"]);
+        assert_eq!(options.include_only, vec!["*.mkv"]);
+        assert!(options.include_hidden && options.allow_torrent_files);
+        assert!(options.skip_empty_files);
+        assert!(options.keep_empty_dirs && options.no_recursion && options.no_padding);
+        assert!(options.flatten_single && options.strip_root);
+        assert_eq!(options.relative_to, Some(PathBuf::from("/data")));
+        assert_eq!(options.meta_version, Some(3));
+        assert_eq!(options.similar, vec![vec![7u8; 20]]);
+        assert_eq!(options.collections, vec!["Series"]);
+        assert!(options.from_archive && options.strict && options.dry_run);
+        assert!(options.force_v1_name_compat);
+
+        // Fields that conflict with the ones above, each on its own
+        let options = TorrentOptions::builder()
+            .public(true)
+            .store_mtime(true)
+            .build()
+            .unwrap();
+        assert!(options.public && options.store_mtime);
+        assert!(
+            TorrentOptions::builder()
+                .from_url(true)
+                .build()
+                .unwrap()
+                .from_url
+        );
+        assert_eq!(
+            TorrentOptions::builder()
+                .length(4096)
+                .build()
+                .unwrap()
+                .length,
+            Some(4096)
+        );
+    }
+
+    #[test]
+    fn test_builder_defaults_match_default() {
+        let built = TorrentOptions::builder().build().unwrap();
+        let default = TorrentOptions::default();
+        assert_eq!(built.mode, default.mode);
+        assert_eq!(built.piece_length, default.piece_length);
+        assert_eq!(built.private, default.private);
+        assert!(built.announce.is_empty() && built.name.is_none());
+    }
+
+    #[test]
+    fn test_builder_rejects_conflicts() {
+        assert!(
+            TorrentOptions::builder()
+                .min_piece_exp(20)
+                .max_piece_exp(18)
+                .build()
+                .is_err()
+        );
+        assert!(
+            TorrentOptions::builder()
+                .tracker_preset("nope")
+                .build()
+                .is_err()
+        );
+        assert!(TorrentOptions::builder().name("").build().is_err());
+        assert!(
+            TorrentOptions::builder()
+                .mode(Mode::V1)
+                .meta_version(3)
+                .build()
+                .is_err()
+        );
+        assert!(
+            TorrentOptions::builder()
+                .similar([0u8; 16])
+                .build()
+                .is_err()
+        );
+        let err = TorrentOptions::builder()
+            .length(4096)
+            .from_archive(true)
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("--length"), "{err:#}");
+        assert!(
+            TorrentOptions::builder()
+                .from_url(true)
+                .relative_to("/data")
+                .build()
+                .is_err()
+        );
+    }
+}
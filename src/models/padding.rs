@@ -0,0 +1,62 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+use super::file::FileInfo;
+
+/// Padding a hybrid build inserts to align each file to a piece boundary (see
+/// [`TorrentBuilder::padding_report`](crate::builder::TorrentBuilder::padding_report))
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PaddingReport {
+    pub piece_length: u64,
+    /// Bytes of real content
+    pub content_size: u64,
+    /// Bytes of padding files in total
+    pub padding_size: u64,
+    /// `padding_size` as a percentage of `content_size`
+    pub overhead_percent: f64,
+    /// Files followed by padding, in torrent order
+    pub files: Vec<FilePadding>,
+}
+
+/// A padded file in a [`PaddingReport`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FilePadding {
+    /// Path relative to the torrent root
+    pub path: PathBuf,
+    pub length: u64,
+    /// Padding after the file, up to the next piece boundary
+    pub padding: u64,
+}
+
+impl PaddingReport {
+    /// Summarize a file list padded by [`add_padding_files`](crate::scanner::add_padding_files), where each padding
+    /// file directly follows the file it pads
+    pub fn from_files(files: &[FileInfo], piece_length: u64) -> Self {
+        let mut report = Self {
+            piece_length,
+            content_size: 0,
+            padding_size: 0,
+            overhead_percent: 0.0,
+            files: Vec::new(),
+        };
+        for (i, file) in files.iter().enumerate() {
+            if file.is_padding {
+                report.padding_size += file.len;
+                continue;
+            }
+            report.content_size += file.len;
+            if let Some(pad) = files.get(i + 1).filter(|f| f.is_padding) {
+                report.files.push(FilePadding {
+                    path: file.path.clone(),
+                    length: file.len,
+                    padding: pad.len,
+                });
+            }
+        }
+        if report.content_size > 0 {
+            report.overhead_percent =
+                report.padding_size as f64 * 100.0 / report.content_size as f64;
+        }
+        report
+    }
+}
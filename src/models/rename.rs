@@ -0,0 +1,224 @@
+use anyhow::{Result, bail};
+use log::warn;
+use std::path::PathBuf;
+
+use super::torrent::Torrent;
+use crate::tree::{hybrid_layout_mismatch, insert_into_tree, remove_from_tree, tree_files};
+
+impl Torrent {
+    /// Rename or move a file, given as a `/`-separated path below the torrent root,
+    /// in both the V1 file list and the V2 file tree. This changes the info hash.
+    ///
+    /// V1 pieces run through the files in list order, so the entry keeps its place
+    /// there, while the file tree re-sorts itself and drops directories left empty.
+    /// A hybrid torrent can't be renamed in a way that reorders its files, since the
+    /// two halves would then disagree until the content is rehashed.
+    pub fn rename_file(&mut self, old_path: &str, new_path: &str) -> Result<()> {
+        let old = split_path(old_path)?;
+        let new = split_path(new_path)?;
+        if old == new {
+            return Ok(());
+        }
+
+        if self.info.is_single_file() {
+            if old != [self.info.name.as_str()] {
+                bail!("No file named {} in the torrent", old_path);
+            }
+            let [name] = new.as_slice() else {
+                bail!("The file of a single-file torrent can't be moved into a directory");
+            };
+            if let Some(tree) = &mut self.info.file_tree
+                && let Some(node) = tree.remove(&self.info.name)
+            {
+                tree.insert(name.clone(), node);
+            }
+            self.info.name = name.clone();
+        } else {
+            let was_consistent = hybrid_layout_mismatch(&self.info).is_none();
+            self.move_entry(&old, &new)?;
+            if was_consistent && hybrid_layout_mismatch(&self.info).is_some() {
+                self.move_entry(&new, &old)?;
+                bail!(
+                    "Renaming {} to {} would reorder the files of a hybrid torrent; \
+                     recreate it instead",
+                    old_path,
+                    new_path
+                );
+            }
+        }
+
+        warn!(
+            "Renamed {} to {}; the torrent's info hash has changed",
+            old_path, new_path
+        );
+        Ok(())
+    }
+
+    /// Move a file entry in the V1 list and the V2 tree of a multi-file torrent
+    fn move_entry(&mut self, old: &[String], new: &[String]) -> Result<()> {
+        let existing: Vec<Vec<String>> = match (&self.info.files, &self.info.file_tree) {
+            (Some(files), _) => files
+                .iter()
+                .filter(|f| !f.attr.as_deref().is_some_and(|attr| attr.contains('p')))
+                .map(|f| f.path.clone())
+                .collect(),
+            (None, Some(tree)) => tree_files(tree)
+                .into_iter()
+                .map(|(path, _)| path.split('/').map(str::to_string).collect())
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+        if !existing.iter().any(|path| path == old) {
+            bail!("No file named {} in the torrent", old.join("/"));
+        }
+        // The new path must not be a file already, or lie on either side of one
+        if let Some(taken) = existing
+            .iter()
+            .filter(|path| path.as_slice() != old)
+            .find(|path| path.starts_with(new) || new.starts_with(path))
+        {
+            bail!(
+                "Can't rename to {}: it clashes with {}",
+                new.join("/"),
+                taken.join("/")
+            );
+        }
+
+        if let Some(files) = &mut self.info.files
+            && let Some(entry) = files.iter_mut().find(|f| f.path == old)
+        {
+            entry.path = new.to_vec();
+        }
+        if let Some(tree) = &mut self.info.file_tree
+            && let Some(metadata) = remove_from_tree(tree, old)
+        {
+            let path: PathBuf = new.iter().collect();
+            insert_into_tree(
+                tree,
+                &path,
+                metadata.length,
+                metadata.pieces_root.into_vec(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Split a `/`-separated torrent path into its components, rejecting ones that
+/// clients would refuse or that escape the torrent root
+fn split_path(path: &str) -> Result<Vec<String>> {
+    let components: Vec<String> = path.split('/').map(str::to_string).collect();
+    if components
+        .iter()
+        .any(|c| c.is_empty() || c == "." || c == "..")
+    {
+        bail!("Invalid path in torrent: {:?}", path);
+    }
+    Ok(components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FileEntry, Info, Node};
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    /// A hybrid torrent of `a/b/c.txt`, `a/d.txt` and `e.txt`, without padding
+    fn hybrid_torrent() -> Torrent {
+        let layout = [("a/b/c.txt", 10u64), ("a/d.txt", 20), ("e.txt", 30)];
+        let mut tree = BTreeMap::new();
+        for (i, (path, length)) in layout.iter().enumerate() {
+            insert_into_tree(&mut tree, Path::new(path), *length, vec![i as u8; 32]);
+        }
+        let files = layout
+            .iter()
+            .map(|(path, length)| FileEntry {
+                length: *length,
+                path: path.split('/').map(str::to_string).collect(),
+                attr: None,
+                mtime: None,
+            })
+            .collect();
+        Torrent {
+            announce: None,
+            announce_list: None,
+            comment: None,
+            created_by: String::new(),
+            creation_date: None,
+            info: Info {
+                piece_length: 16384,
+                pieces: Some(serde_bytes::ByteBuf::from(vec![0u8; 20])),
+                name: "set".to_string(),
+                private: None,
+                files: Some(files),
+                length: None,
+                source: None,
+                x_cross_seed: None,
+                meta_version: Some(2),
+                file_tree: Some(tree),
+                similar: None,
+                collections: None,
+            },
+            url_list: None,
+            piece_layers: None,
+            publisher: None,
+            publisher_url: None,
+        }
+    }
+
+    #[test]
+    fn test_rename_nested_file_updates_tree() {
+        let mut torrent = hybrid_torrent();
+        let before = torrent.info_hash_v1();
+        torrent.rename_file("a/b/c.txt", "a/c.txt").unwrap();
+
+        let tree = torrent.info.file_tree.as_ref().unwrap();
+        let Some(Node::Directory(a)) = tree.get("a") else {
+            panic!("a/ should still be a directory");
+        };
+        // The emptied a/b/ is gone and c.txt sits directly in a/
+        assert_eq!(a.keys().collect::<Vec<_>>(), ["c.txt", "d.txt"]);
+        assert_eq!(
+            tree_files(tree)
+                .into_iter()
+                .map(|(path, metadata)| (path, metadata.length))
+                .collect::<Vec<_>>(),
+            [
+                ("a/c.txt".to_string(), 10),
+                ("a/d.txt".to_string(), 20),
+                ("e.txt".to_string(), 30)
+            ]
+        );
+        assert_eq!(torrent.info.files.as_ref().unwrap()[0].path, ["a", "c.txt"]);
+        assert!(hybrid_layout_mismatch(&torrent.info).is_none());
+        assert_ne!(torrent.info_hash_v1(), before);
+    }
+
+    #[test]
+    fn test_rename_rejects_reorder_and_clashes() {
+        let mut torrent = hybrid_torrent();
+        let err = torrent.rename_file("a/b/c.txt", "z.txt").unwrap_err();
+        assert!(err.to_string().contains("reorder"));
+        // The failed rename is rolled back
+        assert_eq!(
+            torrent.info.files.as_ref().unwrap()[0].path,
+            ["a", "b", "c.txt"]
+        );
+        assert!(hybrid_layout_mismatch(&torrent.info).is_none());
+
+        assert!(torrent.rename_file("a/b/c.txt", "a/d.txt").is_err());
+        assert!(torrent.rename_file("a/b/c.txt", "e.txt/c.txt").is_err());
+        assert!(torrent.rename_file("a/b/c.txt", "../c.txt").is_err());
+        assert!(torrent.rename_file("missing.txt", "x.txt").is_err());
+
+        // V2-only torrents have no list order to keep, so anything goes
+        torrent.info.files = None;
+        torrent.rename_file("a/b/c.txt", "z.txt").unwrap();
+        let paths: Vec<_> = tree_files(torrent.info.file_tree.as_ref().unwrap())
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(paths, ["a/d.txt", "e.txt", "z.txt"]);
+    }
+}
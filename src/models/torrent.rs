@@ -1,9 +1,16 @@
+use indicatif::HumanBytes;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
-use sha1::{Sha1, Digest};
+use sha1::{Digest, Sha1};
 use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
 
 use super::file::FileEntry;
+use super::padding::PaddingReport;
+use crate::config::BLOCK_SIZE;
+use crate::trackers::replace_tracker_host;
+use crate::tree::{DuplicateGroup, hybrid_layout_mismatch};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Mode {
@@ -71,6 +78,14 @@ pub struct Info {
 
     #[serde(rename = "file tree", skip_serializing_if = "Option::is_none")]
     pub file_tree: Option<BTreeMap<String, Node>>,
+
+    // BEP 38: info hashes of torrents likely to share files with this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similar: Option<Vec<serde_bytes::ByteBuf>>,
+
+    // BEP 38: collection names this torrent belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collections: Option<Vec<String>>,
 }
 
 /// Torrent metainfo structure
@@ -85,7 +100,12 @@ pub struct Torrent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
 
-    #[serde(rename = "created by")]
+    /// Left out of the file when empty
+    #[serde(
+        rename = "created by",
+        default,
+        skip_serializing_if = "String::is_empty"
+    )]
     pub created_by: String,
 
     #[serde(rename = "creation date", skip_serializing_if = "Option::is_none")]
@@ -99,6 +119,151 @@ pub struct Torrent {
 
     #[serde(rename = "piece layers", skip_serializing_if = "Option::is_none")]
     pub piece_layers: Option<BTreeMap<serde_bytes::ByteBuf, serde_bytes::ByteBuf>>,
+
+    /// Who released the content. Kept outside the info dict, so it can be set or
+    /// edited without changing the info hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+
+    #[serde(
+        rename = "publisher-url",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub publisher_url: Option<String>,
+}
+
+/// Reject torrent names that would produce an invalid or unsafe `info.name`
+///
+/// Clients use the name as a file or directory name when saving, so it must be
+/// non-empty and a single path component.
+pub fn validate_torrent_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty() {
+        anyhow::bail!("Torrent name is empty; pass a non-empty --name");
+    }
+    if name.contains(['/', '\\']) {
+        anyhow::bail!("Torrent name '{}' contains a path separator", name);
+    }
+    if name == "." || name == ".." || name.contains('\0') {
+        anyhow::bail!("Torrent name '{}' is not a valid file name", name);
+    }
+    Ok(())
+}
+
+impl Info {
+    /// Whether this is a single-file torrent: V1 `length`, or a V2 file tree
+    /// holding exactly one file at the top level
+    pub fn is_single_file(&self) -> bool {
+        if self.length.is_some() {
+            return true;
+        }
+        match (&self.files, &self.file_tree) {
+            (None, Some(tree)) => {
+                tree.len() == 1 && matches!(tree.values().next(), Some(Node::File(_)))
+            }
+            _ => false,
+        }
+    }
+
+    /// Content size in bytes, from the V1 fields or else the V2 file tree
+    pub fn total_size(&self) -> u64 {
+        if let Some(len) = self.length {
+            return len;
+        }
+
+        if let Some(ref files) = self.files {
+            return files.iter().map(|f| f.length).sum();
+        }
+
+        if let Some(ref tree) = self.file_tree {
+            return tree.values().map(|node| node.total_size()).sum();
+        }
+
+        0
+    }
+
+    /// Describe how the V1 `pieces` disagree with the size the file list claims, if they
+    /// do. A mismatch means the torrent is malformed or its file list was edited.
+    pub fn piece_count_mismatch(&self) -> Option<String> {
+        let pieces = self.pieces.as_ref()?;
+        if pieces.len() % 20 != 0 {
+            return Some(format!(
+                "pieces is {} bytes, not a multiple of 20",
+                pieces.len()
+            ));
+        }
+        if self.piece_length == 0 {
+            return Some("piece length is 0".to_string());
+        }
+        let stored = pieces.len() as u64 / 20;
+        let expected = self.total_size().div_ceil(self.piece_length);
+        (stored != expected).then(|| {
+            format!(
+                "{} piece hashes stored, but {} at this piece size would need {}",
+                stored,
+                HumanBytes(self.total_size()),
+                expected
+            )
+        })
+    }
+
+    /// Problems that would make clients reject the torrent or misread its content,
+    /// one message each. Empty when the info dictionary is well formed.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if let Err(e) = validate_torrent_name(&self.name) {
+            problems.push(e.to_string());
+        }
+        if self.piece_length == 0 {
+            problems.push("piece length is 0".to_string());
+        } else if self.file_tree.is_some()
+            && (!self.piece_length.is_power_of_two() || self.piece_length < BLOCK_SIZE as u64)
+        {
+            problems.push(format!(
+                "piece length {} must be a power of two of at least 16 KiB for V2",
+                self.piece_length
+            ));
+        }
+
+        match (self.length.is_some(), &self.files) {
+            (true, Some(_)) => problems.push("both length and files are set".to_string()),
+            (false, None) if self.file_tree.is_none() => {
+                problems.push("no length, files or file tree".to_string())
+            }
+            _ => {}
+        }
+        if self.pieces.is_none() && (self.length.is_some() || self.files.is_some()) {
+            problems.push("V1 file list without pieces".to_string());
+        }
+        if self.piece_length > 0
+            && let Some(problem) = self.piece_count_mismatch()
+        {
+            problems.push(problem);
+        }
+
+        for file in self.files.iter().flatten() {
+            let unsafe_component = file
+                .path
+                .iter()
+                .any(|c| c.is_empty() || c == "." || c == ".." || c.contains(['/', '\\']));
+            if file.path.is_empty() || unsafe_component {
+                problems.push(format!("invalid file path {:?}", file.path));
+            }
+        }
+
+        match (self.meta_version, &self.file_tree) {
+            (Some(2), Some(_)) | (None, None) => {}
+            (Some(2), None) => problems.push("meta version 2 without a file tree".to_string()),
+            (None, Some(_)) => problems.push("file tree without meta version 2".to_string()),
+            (Some(version), _) => problems.push(format!("unsupported meta version {}", version)),
+        }
+        if let Some(mismatch) = hybrid_layout_mismatch(self) {
+            problems.push(format!("hybrid layout mismatch: {}", mismatch));
+        }
+
+        problems
+    }
 }
 
 impl Torrent {
@@ -122,6 +287,15 @@ impl Torrent {
         Some(hasher.finalize().into())
     }
 
+    /// V2 info hash truncated to its first 20 bytes, which BEP 52 uses wherever a
+    /// 20-byte hash is expected (tracker announces, DHT, peer handshakes)
+    pub fn info_hash_v2_short(&self) -> Option<[u8; 20]> {
+        let hash = self.info_hash_v2()?;
+        let mut short = [0u8; 20];
+        short.copy_from_slice(&hash[..20]);
+        Some(short)
+    }
+
     pub fn magnet_link(&self) -> String {
         let mut link = format!("magnet:?dn={}", urlencoding::encode(&self.info.name));
 
@@ -149,19 +323,228 @@ impl Torrent {
     }
 
     pub fn total_size(&self) -> u64 {
-        if let Some(len) = self.info.length {
-            return len;
+        self.info.total_size()
+    }
+
+    /// Tracker tiers as clients see them: `announce-list` if present, else `announce`
+    pub fn trackers(&self) -> Vec<Vec<String>> {
+        if let Some(ref list) = self.announce_list {
+            return list.clone();
+        }
+        match self.announce {
+            Some(ref url) => vec![vec![url.clone()]],
+            None => Vec::new(),
         }
+    }
 
-        if let Some(ref files) = self.info.files {
-            return files.iter().map(|f| f.length).sum();
+    /// Replace all trackers with the given tiers.
+    ///
+    /// Empty URLs and tiers are dropped. `announce` mirrors the first URL of the first tier.
+    pub fn set_trackers(&mut self, tiers: Vec<Vec<String>>) {
+        let tiers: Vec<Vec<String>> = tiers
+            .into_iter()
+            .map(|tier| {
+                tier.into_iter()
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|tier| !tier.is_empty())
+            .collect();
+
+        if tiers.is_empty() {
+            self.announce = None;
+            self.announce_list = None;
+        } else {
+            self.announce = Some(tiers[0][0].clone());
+            self.announce_list = Some(tiers);
         }
+    }
 
-        if let Some(ref tree) = self.info.file_tree {
-            return tree.values().map(|node| node.total_size()).sum();
+    /// Append a tier of backup trackers after the existing ones
+    pub fn add_tracker_tier(&mut self, tier: Vec<String>) {
+        let mut tiers = self.trackers();
+        tiers.push(tier);
+        self.set_trackers(tiers);
+    }
+
+    /// Remove a tracker URL from every tier. Returns whether anything was removed.
+    pub fn remove_tracker(&mut self, url: &str) -> bool {
+        let tiers = self.trackers();
+        let before: usize = tiers.iter().map(Vec::len).sum();
+        let tiers: Vec<Vec<String>> = tiers
+            .into_iter()
+            .map(|tier| tier.into_iter().filter(|u| u != url).collect())
+            .collect();
+        let after: usize = tiers.iter().map(Vec::len).sum();
+
+        if before == after {
+            return false;
         }
+        self.set_trackers(tiers);
+        true
+    }
 
-        0
+    /// Merge all tiers into one, keeping the first occurrence of each URL, so clients
+    /// announce to every tracker instead of stopping at the first tier that answers.
+    /// Returns whether the tiers changed.
+    pub fn flatten_trackers(&mut self) -> bool {
+        let tiers = self.trackers();
+        let mut flat: Vec<String> = Vec::new();
+        for url in tiers.iter().flatten() {
+            if !flat.contains(url) {
+                flat.push(url.clone());
+            }
+        }
+
+        if tiers.len() <= 1 && tiers.iter().flatten().count() == flat.len() {
+            return false;
+        }
+        self.set_trackers(vec![flat]);
+        true
+    }
+
+    /// Point every tracker on `old_host` at `new_host`, keeping paths and passkeys.
+    /// Returns how many URLs were rewritten.
+    pub fn replace_tracker_host(&mut self, old_host: &str, new_host: &str) -> usize {
+        let mut replaced = 0;
+        let tiers = self
+            .trackers()
+            .into_iter()
+            .map(|tier| {
+                tier.into_iter()
+                    .map(|url| match replace_tracker_host(&url, old_host, new_host) {
+                        Some(new_url) => {
+                            replaced += 1;
+                            new_url
+                        }
+                        None => url,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        if replaced > 0 {
+            self.set_trackers(tiers);
+        }
+        replaced
+    }
+
+    /// Write the torrent as bencode, byte-for-byte what `serde_bencode::to_bytes`
+    /// produces. The piece layers, the bulk of a large V2 torrent, are written entry
+    /// by entry instead of being buffered along with everything else.
+    pub fn write_to(&self, mut writer: impl Write) -> anyhow::Result<()> {
+        /// The keys that sort before `piece layers`
+        #[derive(Serialize)]
+        struct Head<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            announce: Option<&'a String>,
+            #[serde(rename = "announce-list", skip_serializing_if = "Option::is_none")]
+            announce_list: Option<&'a Vec<Vec<String>>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            comment: Option<&'a String>,
+            #[serde(rename = "created by", skip_serializing_if = "str::is_empty")]
+            created_by: &'a str,
+            #[serde(rename = "creation date", skip_serializing_if = "Option::is_none")]
+            creation_date: Option<i64>,
+            info: &'a Info,
+        }
+
+        let head = serde_bencode::to_bytes(&Head {
+            announce: self.announce.as_ref(),
+            announce_list: self.announce_list.as_ref(),
+            comment: self.comment.as_ref(),
+            created_by: &self.created_by,
+            creation_date: self.creation_date,
+            info: &self.info,
+        })?;
+        // Leave the dictionary open for the remaining keys
+        writer.write_all(&head[..head.len() - 1])?;
+
+        if let Some(layers) = &self.piece_layers {
+            write_bytes(&mut writer, b"piece layers")?;
+            write_piece_layers(&mut writer, layers)?;
+        }
+        for (key, value) in [
+            (&b"publisher"[..], &self.publisher),
+            (b"publisher-url", &self.publisher_url),
+        ] {
+            if let Some(value) = value {
+                write_bytes(&mut writer, key)?;
+                write_bytes(&mut writer, value.as_bytes())?;
+            }
+        }
+        if let Some(url_list) = &self.url_list {
+            write_bytes(&mut writer, b"url-list")?;
+            writer.write_all(&serde_bencode::to_bytes(url_list)?)?;
+        }
+        writer.write_all(b"e")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// JSON view of the metainfo for inspection and debugging. Binary fields (piece
+    /// hashes, pieces roots, piece layers, BEP 38 hashes) are hex-encoded, so the
+    /// result can't be turned back into a .torrent.
+    pub fn to_json(&self) -> Result<serde_json::Value, serde_bencode::Error> {
+        let bytes = serde_bencode::to_bytes(self)?;
+        let value: serde_bencode::value::Value = serde_bencode::from_bytes(&bytes)?;
+        Ok(bencode_to_json(value, false))
+    }
+}
+
+/// Write a bencoded byte string
+pub(super) fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> std::io::Result<()> {
+    write!(writer, "{}:", bytes.len())?;
+    writer.write_all(bytes)
+}
+
+/// Write the piece layers dictionary entry by entry rather than buffering it
+pub(super) fn write_piece_layers(
+    writer: &mut impl Write,
+    layers: &BTreeMap<serde_bytes::ByteBuf, serde_bytes::ByteBuf>,
+) -> std::io::Result<()> {
+    writer.write_all(b"d")?;
+    for (root, layer) in layers {
+        write_bytes(writer, root)?;
+        write_bytes(writer, layer)?;
+    }
+    writer.write_all(b"e")
+}
+
+/// Keys whose byte-string values are raw hashes rather than text
+const BINARY_KEYS: &[&str] = &["pieces", "pieces root", "similar", "piece layers"];
+
+fn bencode_to_json(value: serde_bencode::value::Value, binary: bool) -> serde_json::Value {
+    use serde_bencode::value::Value;
+
+    match value {
+        Value::Int(n) => n.into(),
+        Value::Bytes(bytes) if binary => hex::encode(bytes).into(),
+        Value::Bytes(bytes) => match String::from_utf8(bytes) {
+            Ok(text) => text.into(),
+            Err(err) => hex::encode(err.into_bytes()).into(),
+        },
+        Value::List(items) => items
+            .into_iter()
+            .map(|item| bencode_to_json(item, binary))
+            .collect(),
+        Value::Dict(entries) => entries
+            .into_iter()
+            .map(|(key, value)| {
+                let key = if binary {
+                    hex::encode(key)
+                } else {
+                    String::from_utf8_lossy(&key).into_owned()
+                };
+                // Directories in the file tree can be named "pieces" too
+                let child_binary = binary
+                    || (BINARY_KEYS.contains(&key.as_str())
+                        && (key == "piece layers" || !matches!(value, Value::Dict(_))));
+                (key, bencode_to_json(value, child_binary))
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
     }
 }
 
@@ -190,7 +573,14 @@ pub struct TorrentSummary {
     pub info_hash_v1: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub info_hash_v2: Option<String>,
+    /// First 20 bytes of the V2 info hash, as announced to trackers and the DHT
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info_hash_v2_short: Option<String>,
     pub magnet_link: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicates: Option<Vec<DuplicateGroup>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding: Option<PaddingReport>,
 }
 
 /// Configuration options for building a torrent
@@ -198,17 +588,90 @@ pub struct TorrentSummary {
 pub struct TorrentOptions {
     pub mode: Mode,
     pub piece_length: Option<u32>,
+    /// Lower bound on the final piece length exponent, applied after all other logic
+    pub min_piece_exp: Option<u32>,
+    /// Lower an automatically chosen piece length until there are at least this many
+    /// pieces (down to 16 KiB)
+    pub min_pieces: Option<u64>,
+    /// Upper bound on the final piece length exponent, applied after all other logic
+    pub max_piece_exp: Option<u32>,
     pub private: bool,
+    /// Keep the torrent public even when a tracker requires the private flag
+    pub public: bool,
     pub comment: Option<String>,
     pub announce: Vec<String>,
+    /// Write only the first tracker as `announce`, with no `announce-list`, for
+    /// clients that can't parse BEP 12
+    pub no_announce_list: bool,
+    /// Shuffle the URLs within each tier (tiers keep their order), spreading the
+    /// load of clients that try a tier's trackers in the listed order
+    pub announce_tier_shuffle: bool,
+    /// Seed random choices such as `announce_tier_shuffle` with a fixed value, so
+    /// the same inputs always give the same torrent
+    pub deterministic: bool,
+    /// Apply this tracker's policy (see [`TRACKER_PRESETS`](crate::trackers::TRACKER_PRESETS))
+    /// regardless of the announce URLs
+    pub tracker_preset: Option<String>,
     pub web_seed: Vec<String>,
     pub source_string: Option<String>,
     pub cross_seed: bool,
     pub no_date: bool,
     pub creation_date: Option<i64>,
+    /// Replace the `created by` string (`torrite <version>`); empty leaves it out.
+    /// Meant for reproducing another tool's output byte for byte.
+    pub created_by: Option<String>,
+    /// Top-level `publisher` and `publisher-url`; neither affects the info hash
+    pub publisher: Option<String>,
+    pub publisher_url: Option<String>,
     pub name: Option<String>,
     pub exclude: Vec<String>,
+    /// When non-empty, only files matching one of these globs are kept (after excludes)
+    pub include_only: Vec<String>,
+    /// Keep files and directories whose name starts with `.` (e.g. `.git/`,
+    /// `.DS_Store`), which are skipped by default
+    pub include_hidden: bool,
+    /// Don't warn about `.torrent` files among the content
+    pub allow_torrent_files: bool,
+    /// Leave zero-byte files out of the torrent
+    pub skip_empty_files: bool,
+    /// Record each file's modification time as a non-standard `mtime` key in its V1
+    /// file entry. The key is inside the info dict, so it changes the info hash.
+    pub store_mtime: bool,
+    /// Record empty directories as zero-length `.keep` files
+    pub keep_empty_dirs: bool,
+    /// Only include the source directory's top-level files
+    pub no_recursion: bool,
+    /// Make a single-file torrent when the source directory holds exactly one file
+    pub flatten_single: bool,
+    /// Drop the single top-level directory inside the source from stored paths and
+    /// name the torrent after it
+    pub strip_root: bool,
+    /// Store file paths relative to this directory (forces a multi-file layout)
+    pub relative_to: Option<PathBuf>,
+    /// Skip BEP 47 padding files in hybrid torrents (breaks BEP 52 piece alignment)
+    pub no_padding: bool,
+    /// Write this nonstandard `meta version` instead of 2, for testing how clients
+    /// cope with it. Changes the info hash; V2 and hybrid torrents only.
+    pub meta_version: Option<u8>,
+    /// BEP 38 `similar` info hashes (raw bytes)
+    pub similar: Vec<Vec<u8>>,
+    /// BEP 38 `collections` names
+    pub collections: Vec<String>,
     pub dry_run: bool,
+    /// Treat the source as a zip/tar archive and hash its entries in place
+    pub from_archive: bool,
+    /// Treat the source as an HTTP(S) URL, hash the file it serves as it downloads,
+    /// and add the URL as a web seed
+    pub from_url: bool,
+    /// Hash exactly this many bytes of a single-file source (e.g. a block device)
+    /// instead of trusting its reported size
+    pub length: Option<u64>,
+    /// Turn tracker requirement and portability warnings (e.g. a missing private flag,
+    /// case-colliding paths) into errors
+    pub strict: bool,
+    /// Re-check that a hybrid torrent's V1 file list and V2 file tree agree before
+    /// writing it (always done in debug builds)
+    pub force_v1_name_compat: bool,
 }
 
 impl Default for TorrentOptions {
@@ -216,17 +679,47 @@ impl Default for TorrentOptions {
         Self {
             mode: Mode::V1,
             piece_length: None,
+            min_piece_exp: None,
+            min_pieces: None,
+            max_piece_exp: None,
             private: false,
+            public: false,
             comment: None,
             announce: Vec::new(),
+            no_announce_list: false,
+            announce_tier_shuffle: false,
+            deterministic: false,
+            tracker_preset: None,
             web_seed: Vec::new(),
             source_string: None,
             cross_seed: false,
             no_date: false,
             creation_date: None,
+            created_by: None,
+            publisher: None,
+            publisher_url: None,
             name: None,
             exclude: Vec::new(),
+            include_only: Vec::new(),
+            include_hidden: false,
+            allow_torrent_files: false,
+            skip_empty_files: false,
+            store_mtime: false,
+            keep_empty_dirs: false,
+            no_recursion: false,
+            flatten_single: false,
+            strip_root: false,
+            no_padding: false,
+            meta_version: None,
+            relative_to: None,
+            similar: Vec::new(),
+            collections: Vec::new(),
             dry_run: false,
+            from_archive: false,
+            from_url: false,
+            length: None,
+            strict: false,
+            force_v1_name_compat: false,
         }
     }
 }
@@ -236,6 +729,15 @@ mod tests {
     use super::*;
     use crate::models::FileEntry;
 
+    #[test]
+    fn test_validate_torrent_name() {
+        assert!(validate_torrent_name("Album (2024)").is_ok());
+        assert!(validate_torrent_name("").is_err());
+        assert!(validate_torrent_name("a/b").is_err());
+        assert!(validate_torrent_name("a\\b").is_err());
+        assert!(validate_torrent_name("..").is_err());
+    }
+
     #[test]
     fn test_total_size_single_file() {
         let info = Info {
@@ -249,6 +751,8 @@ mod tests {
             x_cross_seed: None,
             meta_version: None,
             file_tree: None,
+            similar: None,
+            collections: None,
         };
         let torrent = Torrent {
             announce: None,
@@ -259,6 +763,8 @@ mod tests {
             info,
             url_list: None,
             piece_layers: None,
+            publisher: None,
+            publisher_url: None,
         };
         assert_eq!(torrent.total_size(), 12345);
     }
@@ -271,14 +777,26 @@ mod tests {
             name: "test_dir".to_string(),
             private: None,
             files: Some(vec![
-                FileEntry { length: 100, path: vec!["a.txt".into()], attr: None },
-                FileEntry { length: 200, path: vec!["b.txt".into()], attr: None },
+                FileEntry {
+                    length: 100,
+                    path: vec!["a.txt".into()],
+                    attr: None,
+                    mtime: None,
+                },
+                FileEntry {
+                    length: 200,
+                    path: vec!["b.txt".into()],
+                    attr: None,
+                    mtime: None,
+                },
             ]),
             length: None,
             source: None,
             x_cross_seed: None,
             meta_version: None,
             file_tree: None,
+            similar: None,
+            collections: None,
         };
         let torrent = Torrent {
             announce: None,
@@ -289,10 +807,137 @@ mod tests {
             info,
             url_list: None,
             piece_layers: None,
+            publisher: None,
+            publisher_url: None,
         };
         assert_eq!(torrent.total_size(), 300);
     }
 
+    fn tracker_torrent(announce: Option<&str>, announce_list: Option<Vec<Vec<&str>>>) -> Torrent {
+        Torrent {
+            announce: announce.map(str::to_string),
+            announce_list: announce_list.map(|list| {
+                list.into_iter()
+                    .map(|tier| tier.into_iter().map(str::to_string).collect())
+                    .collect()
+            }),
+            comment: None,
+            created_by: "test".to_string(),
+            creation_date: None,
+            info: Info {
+                piece_length: 1024,
+                pieces: None,
+                name: "test".to_string(),
+                private: None,
+                files: None,
+                length: Some(100),
+                source: None,
+                x_cross_seed: None,
+                meta_version: None,
+                file_tree: None,
+                similar: None,
+                collections: None,
+            },
+            url_list: None,
+            piece_layers: None,
+            publisher: None,
+            publisher_url: None,
+        }
+    }
+
+    /// `announce` must always be the first URL of the first tier
+    fn assert_announce_mirrors_list(torrent: &Torrent) {
+        match &torrent.announce_list {
+            Some(list) => {
+                assert!(!list.is_empty() && list.iter().all(|tier| !tier.is_empty()));
+                assert_eq!(torrent.announce.as_deref(), Some(list[0][0].as_str()));
+            }
+            None => assert!(torrent.announce.is_none()),
+        }
+    }
+
+    #[test]
+    fn test_set_trackers() {
+        let mut torrent = tracker_torrent(Some("http://old"), None);
+
+        torrent.set_trackers(vec![
+            vec!["http://a".into(), " ".into()],
+            vec![],
+            vec!["http://b".into()],
+        ]);
+        assert_announce_mirrors_list(&torrent);
+        assert_eq!(
+            torrent.announce_list,
+            Some(vec![
+                vec!["http://a".to_string()],
+                vec!["http://b".to_string()]
+            ])
+        );
+
+        torrent.set_trackers(vec![]);
+        assert_announce_mirrors_list(&torrent);
+        assert!(torrent.announce.is_none());
+    }
+
+    #[test]
+    fn test_add_tracker_tier_keeps_single_announce() {
+        // A lone `announce` without a list must stay as the first tier
+        let mut torrent = tracker_torrent(Some("http://a"), None);
+        torrent.add_tracker_tier(vec!["http://b".into()]);
+        assert_announce_mirrors_list(&torrent);
+        assert_eq!(torrent.trackers(), vec![vec!["http://a"], vec!["http://b"]]);
+
+        let mut empty = tracker_torrent(None, None);
+        empty.add_tracker_tier(vec!["http://c".into()]);
+        assert_announce_mirrors_list(&empty);
+        assert_eq!(empty.announce.as_deref(), Some("http://c"));
+    }
+
+    #[test]
+    fn test_remove_tracker() {
+        let mut torrent = tracker_torrent(
+            Some("http://a"),
+            Some(vec![vec!["http://a", "http://b"], vec!["http://c"]]),
+        );
+
+        // Removing the primary promotes the next URL
+        assert!(torrent.remove_tracker("http://a"));
+        assert_announce_mirrors_list(&torrent);
+        assert_eq!(torrent.announce.as_deref(), Some("http://b"));
+
+        // Emptied tiers are dropped
+        assert!(torrent.remove_tracker("http://b"));
+        assert_announce_mirrors_list(&torrent);
+        assert_eq!(torrent.trackers(), vec![vec!["http://c"]]);
+
+        assert!(!torrent.remove_tracker("http://missing"));
+
+        assert!(torrent.remove_tracker("http://c"));
+        assert_announce_mirrors_list(&torrent);
+        assert!(torrent.announce_list.is_none());
+    }
+
+    #[test]
+    fn test_flatten_trackers() {
+        let mut torrent = tracker_torrent(
+            Some("http://a"),
+            Some(vec![
+                vec!["http://a", "http://b"],
+                vec!["http://c", "http://a"],
+            ]),
+        );
+
+        assert!(torrent.flatten_trackers());
+        assert_announce_mirrors_list(&torrent);
+        assert_eq!(
+            torrent.trackers(),
+            vec![vec!["http://a", "http://b", "http://c"]]
+        );
+
+        assert!(!torrent.flatten_trackers());
+        assert!(!tracker_torrent(None, None).flatten_trackers());
+    }
+
     #[test]
     fn test_magnet_link() {
         let info = Info {
@@ -306,6 +951,8 @@ mod tests {
             x_cross_seed: None,
             meta_version: None,
             file_tree: None,
+            similar: None,
+            collections: None,
         };
         let torrent = Torrent {
             announce: Some("http://tracker.com/announce".to_string()),
@@ -316,6 +963,8 @@ mod tests {
             info,
             url_list: None,
             piece_layers: None,
+            publisher: None,
+            publisher_url: None,
         };
         
         let magnet = torrent.magnet_link();
@@ -324,4 +973,58 @@ mod tests {
         assert!(magnet.contains("tr=http%3A%2F%2Ftracker.com%2Fannounce"));
         assert!(magnet.contains("xt=urn:btih:"));
     }
+
+    #[test]
+    fn test_write_to_matches_to_bytes() {
+        let mut torrent = tracker_torrent(
+            Some("http://a/announce"),
+            Some(vec![vec!["http://a/announce"], vec!["http://b/announce"]]),
+        );
+        torrent.comment = Some("comment".to_string());
+        torrent.creation_date = Some(1_700_000_000);
+        torrent.url_list = Some(vec!["http://seed/".to_string()]);
+        torrent.publisher = Some("Publisher".to_string());
+        torrent.publisher_url = Some("https://publisher.example/".to_string());
+        torrent.info.meta_version = Some(2);
+        torrent.piece_layers = Some(BTreeMap::from([
+            (
+                serde_bytes::ByteBuf::from(vec![2u8; 32]),
+                serde_bytes::ByteBuf::from(vec![9u8; 64]),
+            ),
+            (
+                serde_bytes::ByteBuf::from(vec![1u8; 32]),
+                serde_bytes::ByteBuf::from(vec![8u8; 96]),
+            ),
+        ]));
+
+        for torrent in [tracker_torrent(None, None), torrent] {
+            let mut written = Vec::new();
+            torrent.write_to(&mut written).unwrap();
+            assert_eq!(written, serde_bencode::to_bytes(&torrent).unwrap());
+
+            let parsed: Torrent = serde_bencode::from_bytes(&written).unwrap();
+            assert_eq!(parsed.trackers(), torrent.trackers());
+            assert_eq!(parsed.url_list, torrent.url_list);
+            assert_eq!(parsed.piece_layers, torrent.piece_layers);
+            assert_eq!(parsed.publisher, torrent.publisher);
+            assert_eq!(parsed.publisher_url, torrent.publisher_url);
+            assert_eq!(parsed.info_hash_v1(), torrent.info_hash_v1());
+        }
+    }
+
+    #[test]
+    fn test_info_hash_v2_short() {
+        let mut torrent = tracker_torrent(None, None);
+        torrent.info.length = None;
+        torrent.info.meta_version = Some(2);
+        torrent.info.file_tree = Some(BTreeMap::new());
+
+        // V2-only: no V1 hash, and the short hash is the V2 hash's first 20 bytes
+        assert!(torrent.info_hash_v1().is_none());
+        let full = torrent.info_hash_v2().unwrap();
+        assert_eq!(torrent.info_hash_v2_short().unwrap(), full[..20]);
+
+        torrent.info.meta_version = None;
+        assert!(torrent.info_hash_v2_short().is_none());
+    }
 }
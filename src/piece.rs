@@ -15,7 +15,21 @@ pub fn calculate_piece_length(total_size: u64) -> u32 {
 
 /// Calculate the number of pieces for a given total size and piece length
 pub fn calculate_num_pieces(total_size: u64, piece_length: u64) -> u64 {
-    (total_size + piece_length - 1) / piece_length
+    total_size.div_ceil(piece_length)
+}
+
+/// Smallest piece length exponent [`apply_min_pieces`] lowers to (16 KiB, one V2 block)
+pub const MIN_PIECES_FLOOR_EXP: u32 = 14;
+
+/// Halve the piece length until `total_size` spans at least `min_pieces` pieces,
+/// stopping at [`MIN_PIECES_FLOOR_EXP`]. Exponents already below the floor are kept.
+pub fn apply_min_pieces(total_size: u64, mut power: u32, min_pieces: u64) -> u32 {
+    while power > MIN_PIECES_FLOOR_EXP
+        && calculate_num_pieces(total_size, 1u64 << power) < min_pieces
+    {
+        power -= 1;
+    }
+    power
 }
 
 #[cfg(test)]
@@ -50,4 +64,17 @@ mod tests {
         assert_eq!(calculate_num_pieces(2048, 1024), 2);
         assert_eq!(calculate_num_pieces(2049, 1024), 3);
     }
+
+    #[test]
+    fn test_apply_min_pieces() {
+        // 200 KiB at the default 32 KiB is 7 pieces; 16 KiB gives 13
+        assert_eq!(apply_min_pieces(200 * 1024, 15, 8), 14);
+        // Already enough pieces
+        assert_eq!(apply_min_pieces(200 * 1024, 15, 7), 15);
+        // A tiny file can't go below the floor
+        assert_eq!(apply_min_pieces(1000, 15, 8), MIN_PIECES_FLOOR_EXP);
+        assert_eq!(apply_min_pieces(1000, 12, 8), 12);
+        // Large pieces are halved as often as needed
+        assert_eq!(apply_min_pieces(64 * MB, 24, 8), 23);
+    }
 }
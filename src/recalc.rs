@@ -0,0 +1,131 @@
+use anyhow::{Context, Result, anyhow, bail};
+use console::{Emoji, style};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use torrite::cli::RecalcArgs;
+use torrite::hashing::hash_v2_files;
+use torrite::models::{Node, PieceLayersFile, Torrent};
+use torrite::tree::tree_files;
+
+use crate::verify::{build_file_list, check_files_exist};
+use crate::write_replacing;
+
+static SUCCESS: Emoji<'_, '_> = Emoji("✅ ", "OK");
+
+/// Re-hash a V2 torrent's content to restore its `piece layers`
+///
+/// The layers live outside the info dict, so the info hash is unchanged. The data
+/// must reproduce every pieces root in the file tree, or nothing is written.
+/// With `--layers`, they're read from a `.layers` file instead of re-hashed.
+pub fn recalc_torrent(args: RecalcArgs) -> Result<()> {
+    let content = fs::read(&args.torrent).context("Failed to read torrent file")?;
+    let mut torrent: Torrent =
+        serde_bencode::from_bytes(&content).context("Invalid torrent file")?;
+    if let Some(layers_path) = &args.layers {
+        return merge_layers(torrent, layers_path, args.output.unwrap_or(args.torrent));
+    }
+    let info = &torrent.info;
+
+    if info.meta_version != Some(2) {
+        bail!(
+            "{} is a V1 torrent; only V2 and hybrid torrents have piece layers",
+            args.torrent.display()
+        );
+    }
+    let expected_tree = info
+        .file_tree
+        .as_ref()
+        .context("Missing file tree for V2 torrent")?;
+
+    let content_root = match args.path {
+        Some(path) => path,
+        None => std::env::current_dir()?.join(&info.name),
+    };
+    println!("Recalculating piece layers: {}", style(&info.name).bold());
+    println!("Content path: {}", style(content_root.display()).cyan());
+
+    let files = build_file_list(info, &content_root)?;
+    if files.is_empty() {
+        return Err(anyhow!("No files found in torrent info"));
+    }
+    check_files_exist(&files, false)?;
+
+    let total_size: u64 = files.iter().filter(|f| !f.is_padding).map(|f| f.len).sum();
+    let pb = ProgressBar::new(total_size);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {bytes}/{total_bytes} Hashing V2")?
+            .progress_chars("#>- "),
+    );
+
+    let is_single_file = info.is_single_file();
+    let result = hash_v2_files(&files, info.piece_length, is_single_file, Some(pb))?;
+
+    // Older torrite versions keyed the lone file of a single-file tree by "" instead of its name
+    let expected_tree: BTreeMap<String, Node> = if is_single_file {
+        expected_tree
+            .values()
+            .map(|node| (info.name.clone(), node.clone()))
+            .collect()
+    } else {
+        expected_tree.clone()
+    };
+
+    let actual: HashMap<String, _> = tree_files(&result.file_tree).into_iter().collect();
+    let mismatched: Vec<String> = tree_files(&expected_tree)
+        .into_iter()
+        .filter(|(path, expected)| actual.get(path) != Some(expected))
+        .map(|(path, _)| path)
+        .collect();
+    if !mismatched.is_empty() {
+        bail!(
+            "Data doesn't match the torrent's file tree, not writing piece layers: {}",
+            mismatched.join(", ")
+        );
+    }
+
+    let restored = result.piece_layers.len();
+    torrent.piece_layers = Some(result.piece_layers);
+
+    let output_path = args.output.unwrap_or(args.torrent);
+    write_torrent(&torrent, &output_path)?;
+
+    println!(
+        "{} Restored piece layers for {} file(s). Saved to: {}",
+        SUCCESS,
+        restored,
+        style(output_path.display()).cyan()
+    );
+    Ok(())
+}
+
+/// Recombine a torrent split by `--piece-layers-external` with its `.layers` file
+fn merge_layers(mut torrent: Torrent, layers_path: &Path, output_path: PathBuf) -> Result<()> {
+    let data = fs::read(layers_path)
+        .with_context(|| format!("Failed to read piece layers: {}", layers_path.display()))?;
+    let layers: PieceLayersFile =
+        serde_bencode::from_bytes(&data).context("Invalid piece layers file")?;
+    let merged = layers.piece_layers.len();
+    torrent.merge_piece_layers(layers)?;
+
+    write_torrent(&torrent, &output_path)?;
+
+    println!(
+        "{} Merged piece layers for {} file(s). Saved to: {}",
+        SUCCESS,
+        merged,
+        style(output_path.display()).cyan()
+    );
+    Ok(())
+}
+
+fn write_torrent(torrent: &Torrent, path: &Path) -> Result<()> {
+    write_replacing(path, |writer| {
+        torrent
+            .write_to(writer)
+            .context("Failed to write torrent file")
+    })
+}
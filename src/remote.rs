@@ -0,0 +1,56 @@
+//! Reading torrent content from a file served over HTTP(S).
+//!
+//! The response body is hashed as it arrives (see [`hash_reader`](crate::hashing::hash_reader)),
+//! so nothing is written to disk. The server must report a `Content-Length`, since the
+//! piece length has to be chosen before the first byte is hashed.
+
+use anyhow::{Context, Result, anyhow};
+use std::io::Read;
+
+/// Start downloading `url`, returning the response body and its advertised size
+pub fn open_url(url: &str) -> Result<(impl Read + use<>, u64)> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(None)
+        .build()
+        .context("Failed to start HTTP client")?;
+    let response = client
+        .get(url)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to fetch {}", url))?;
+    let length = response.content_length().ok_or_else(|| {
+        anyhow!(
+            "{} did not report a Content-Length, which is needed to pick a piece length",
+            url
+        )
+    })?;
+    Ok((response, length))
+}
+
+/// Default torrent name for a URL: the last segment of its path, percent-decoded
+pub fn url_file_name(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.split(['?', '#']).next()?;
+    let (_, path) = rest.split_once('/')?;
+    let segment = path.rsplit('/').find(|s| !s.is_empty())?;
+    urlencoding::decode(segment).ok().map(|s| s.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_file_name() {
+        assert_eq!(
+            url_file_name("https://example.com/files/My%20Album.flac?token=1").unwrap(),
+            "My Album.flac"
+        );
+        assert_eq!(
+            url_file_name("http://example.com/dir/file.bin/").unwrap(),
+            "file.bin"
+        );
+        assert_eq!(url_file_name("http://example.com/"), None);
+        assert_eq!(url_file_name("http://example.com"), None);
+    }
+}
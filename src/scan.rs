@@ -0,0 +1,86 @@
+use anyhow::Result;
+use console::{Emoji, style};
+use indicatif::HumanBytes;
+use serde::Serialize;
+
+use torrite::cli::ScanArgs;
+use torrite::piece::{calculate_num_pieces, calculate_piece_length};
+use torrite::scanner::{WalkOptions, scan_files};
+
+static FILES: Emoji<'_, '_> = Emoji("📁 ", "f ");
+
+#[derive(Debug, Serialize)]
+struct ScannedFile {
+    path: String,
+    length: u64,
+}
+
+/// Scan results for JSON output
+#[derive(Debug, Serialize)]
+struct ScanReport {
+    files: Vec<ScannedFile>,
+    file_count: usize,
+    total_size: u64,
+    piece_length: u64,
+    piece_exp: u32,
+    num_pieces: u64,
+}
+
+pub fn scan_path(args: ScanArgs) -> Result<()> {
+    let walk = WalkOptions {
+        keep_empty_dirs: args.keep_empty_dirs,
+        no_recursion: args.no_recursion,
+        skip_empty_files: args.skip_empty_files,
+        include_hidden: args.include_hidden,
+    };
+    let (files, total_size) =
+        scan_files(&args.path, None, &args.exclude, &args.include_only, walk)?;
+
+    let piece_exp = calculate_piece_length(total_size);
+    let piece_length = 1u64 << piece_exp;
+
+    let report = ScanReport {
+        files: files
+            .iter()
+            .map(|f| ScannedFile {
+                path: f.path.to_string_lossy().into_owned(),
+                length: f.len,
+            })
+            .collect(),
+        file_count: files.len(),
+        total_size,
+        piece_length,
+        piece_exp,
+        num_pieces: calculate_num_pieces(total_size, piece_length),
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("{} {}", FILES, style("Files:").bold());
+    for file in &report.files {
+        println!(
+            "  {:<40} {}",
+            file.path,
+            style(HumanBytes(file.length)).dim()
+        );
+    }
+    println!();
+    println!("{:<15} {}", style("File Count:").bold(), report.file_count);
+    println!(
+        "{:<15} {}",
+        style("Total Size:").bold(),
+        style(HumanBytes(report.total_size)).green()
+    );
+    println!(
+        "{:<15} {} (2^{})",
+        style("Piece Length:").bold(),
+        style(HumanBytes(report.piece_length)).yellow(),
+        report.piece_exp
+    );
+    println!("{:<15} {}", style("Piece Count:").bold(), report.num_pieces);
+
+    Ok(())
+}
@@ -1,154 +1,586 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use glob::Pattern;
 use jwalk::WalkDir;
+use log::{debug, info, warn};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 
 use crate::models::FileInfo;
 
+/// Name of the zero-length placeholder recorded for an empty directory
+pub const EMPTY_DIR_MARKER: &str = ".keep";
+
+/// How a source directory is walked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Record every empty directory below the source as a zero-length
+    /// [`EMPTY_DIR_MARKER`] file so it survives in the torrent
+    pub keep_empty_dirs: bool,
+    /// Only take the source's immediate files, ignoring subdirectories entirely
+    pub no_recursion: bool,
+    /// Leave zero-byte files out of the walk. They're dropped as they're found, so a
+    /// source with huge numbers of them doesn't hold them in memory. Empty directory
+    /// markers are still added with `keep_empty_dirs`.
+    pub skip_empty_files: bool,
+    /// Keep files and directories whose name starts with `.` (e.g. `.git/`,
+    /// `.DS_Store`), which are left out by default. Without it, an include pattern
+    /// that spells out the dot, such as `.env`, still picks a hidden file.
+    pub include_hidden: bool,
+}
+
 /// Scans the source path and collects file information
 pub fn scan_files(
     source: &Path,
     output_file: Option<&Path>,
     exclude_patterns: &[String],
-    verbose: bool,
+    include_patterns: &[String],
+    walk: WalkOptions,
 ) -> Result<(Vec<FileInfo>, u64)> {
-    let source = source
-        .canonicalize()
-        .context("Failed to resolve source path")?;
+    Scanner::new(source, walk)?.files(output_file, exclude_patterns, include_patterns)
+}
 
-    let output_canonical = output_file.and_then(|p| p.canonicalize().ok());
+/// Characters that make a source path a glob pattern
+const GLOB_CHARS: [char; 3] = ['*', '?', '['];
 
-    let mut files = Vec::new();
-    let mut total_size = 0u64;
+/// Whether `source` should be expanded as a glob, e.g. `videos/*.mkv`. A path that
+/// exists is always taken literally, even if its name contains `*` or `[`.
+pub fn is_glob_source(source: &Path) -> bool {
+    !source.exists()
+        && source
+            .to_str()
+            .is_some_and(|s| s.contains(GLOB_CHARS.as_slice()))
+}
 
-    // Compile glob patterns
-    let mut patterns = Vec::new();
-    for pattern_str in exclude_patterns {
-        match Pattern::new(pattern_str) {
-            Ok(p) => patterns.push(p),
-            Err(e) => {
-                if verbose {
-                    eprintln!("Warning: Invalid glob pattern '{}': {}", pattern_str, e);
-                }
-            }
-        }
+/// The leading components of a glob up to the first wildcard, which may be empty
+fn glob_prefix(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|c| {
+            !c.as_os_str()
+                .to_string_lossy()
+                .contains(GLOB_CHARS.as_slice())
+        })
+        .collect()
+}
+
+/// Directory a glob's matches are stored relative to: everything before the first
+/// component with a wildcard, or the current directory
+pub fn glob_base(pattern: &Path) -> PathBuf {
+    let prefix = glob_prefix(pattern);
+    if prefix.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        prefix
     }
+}
 
-    if source.is_file() {
-        // Single file mode
-        let metadata = source.metadata().context("Failed to read file metadata")?;
-        let len = metadata.len();
+/// Expand a glob source into the files it matches, stored relative to [`glob_base`].
+/// Matched directories are walked like a directory source. As in a shell, wildcards
+/// don't match a leading `.`, so hidden files need an explicit `.*`; hidden files
+/// inside a matched directory follow [`WalkOptions::include_hidden`].
+pub fn scan_glob(
+    pattern: &Path,
+    output_file: Option<&Path>,
+    exclude_patterns: &[String],
+    include_patterns: &[String],
+    walk: WalkOptions,
+) -> Result<(Vec<FileInfo>, u64)> {
+    let pattern_str = pattern
+        .to_str()
+        .context("Glob pattern is not valid UTF-8")?;
+    let options = glob::MatchOptions {
+        require_literal_leading_dot: true,
+        ..Default::default()
+    };
+    let matches = glob::glob_with(pattern_str, options)
+        .with_context(|| format!("Invalid glob pattern: {}", pattern_str))?
+        .collect::<Result<Vec<PathBuf>, _>>()
+        .context("Failed to list glob matches")?;
+    if matches.is_empty() {
+        bail!("No files match {}", pattern_str);
+    }
+    info!("Glob {} matched {} paths", pattern_str, matches.len());
 
+    let prefix = glob_prefix(pattern);
+    let filter = FileFilter::new(
+        output_file,
+        exclude_patterns,
+        include_patterns,
+        walk.include_hidden,
+    );
+
+    let mut files = Vec::new();
+    for matched in matches {
+        let relative = matched
+            .strip_prefix(&prefix)
+            .with_context(|| format!("Glob match outside its base: {}", matched.display()))?
+            .to_path_buf();
+        // Files inside a matched directory are filtered by their path from the base
+        if matched.is_dir() {
+            files.extend(Scanner::new(&matched, walk)?.filtered(&filter, &relative)?);
+            continue;
+        }
+
+        let full_path = std::path::absolute(&matched)
+            .with_context(|| format!("Failed to resolve {}", matched.display()))?;
+        // The pattern itself named any hidden component of a direct match
+        if !filter.keep(&relative, &full_path, false, false) {
+            continue;
+        }
+        let len = std::fs::metadata(&full_path)
+            .with_context(|| format!("Failed to read file metadata: {}", full_path.display()))?
+            .len();
+        if len == 0 && walk.skip_empty_files {
+            continue;
+        }
+        debug!("  {} ({} bytes)", relative.display(), len);
         files.push(FileInfo {
-            path: source.file_name().context("Failed to get filename")?.into(),
-            full_path: source.clone(),
+            path: relative,
+            full_path,
             len,
             start_offset: 0,
             is_padding: false,
+            data_offset: 0,
         });
-        total_size = len;
+    }
 
-        if verbose {
-            eprintln!("Single file: {} ({} bytes)", source.display(), len);
-        }
-    } else {
-        // Multi-file mode (directory)
-        // Use jwalk for parallel traversal
-        let base_path = &source;
+    // A `**` pattern can match a directory and the files inside it
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files.dedup_by(|a, b| a.path == b.path);
 
-        for entry in WalkDir::new(&source) {
-            let entry = entry.context("Failed to read directory entry")?;
-            let entry_path = entry.path();
+    let mut total_size = 0u64;
+    for file in &mut files {
+        file.start_offset = total_size;
+        total_size += file.len;
+    }
+    info!(
+        "Found {} files, total size: {} bytes",
+        files.len(),
+        total_size
+    );
+    Ok((files, total_size))
+}
 
-            // jwalk returns directories too, skip them
-            // entry.file_type() is typically available and cheap
-            if entry.file_type().is_dir() {
-                continue;
-            }
+/// A walk of the source kept in memory, so excludes can be re-applied without
+/// touching the disk again (e.g. while the interactive preview is being tweaked)
+#[derive(Debug)]
+pub struct Scanner {
+    source: PathBuf,
+    walk: WalkOptions,
+    single_file: bool,
+    entries: Vec<WalkEntry>,
+    walks: usize,
+}
 
-            // Skip the output file if it's inside the source directory
-            if let Some(ref output) = output_canonical {
-                if entry_path == output.as_path() {
-                    if verbose {
-                        eprintln!("Skipping output file: {}", entry_path.display());
+#[derive(Debug)]
+struct WalkEntry {
+    /// Relative to the source; for an empty directory, the directory itself
+    path: PathBuf,
+    full_path: PathBuf,
+    kind: EntryKind,
+}
+
+#[derive(Debug)]
+enum EntryKind {
+    File(u64),
+    EmptyDir,
+    /// Stat failed; only reported if the file isn't excluded
+    Unreadable(String),
+}
+
+impl Scanner {
+    /// Walk `source` once and cache what was found
+    pub fn new(source: &Path, walk: WalkOptions) -> Result<Self> {
+        let source = resolve_source(source)?;
+        let mut scanner = Self {
+            single_file: false,
+            source,
+            walk,
+            entries: Vec::new(),
+            walks: 0,
+        };
+        scanner.rescan()?;
+        Ok(scanner)
+    }
+
+    /// The canonicalized source path
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// How many times the source has been walked
+    pub fn walk_count(&self) -> usize {
+        self.walks
+    }
+
+    /// Walk the source again to pick up changes on disk, including the source
+    /// turning from a file into a directory or back
+    pub fn rescan(&mut self) -> Result<()> {
+        let mut entries = Vec::new();
+        let mut skipped_empty = 0usize;
+        self.single_file = self.source.is_file();
+
+        if self.single_file {
+            let metadata = self
+                .source
+                .metadata()
+                .context("Failed to read file metadata")?;
+            entries.push(WalkEntry {
+                path: self
+                    .source
+                    .file_name()
+                    .context("Failed to get filename")?
+                    .into(),
+                full_path: self.source.clone(),
+                kind: EntryKind::File(metadata.len()),
+            });
+        } else {
+            let max_depth = if self.walk.no_recursion {
+                1
+            } else {
+                usize::MAX
+            };
+            // jwalk traverses in parallel; `source` is canonical, so entry paths are absolute.
+            // Hidden entries are kept here and left to the filter, so an include can pick them.
+            for entry in WalkDir::new(&self.source)
+                .max_depth(max_depth)
+                .skip_hidden(false)
+            {
+                let entry = entry.context("Failed to read directory entry")?;
+                let entry_path = entry.path();
+
+                let kind = if entry.file_type().is_dir() {
+                    if self.walk.no_recursion
+                        || !(self.walk.keep_empty_dirs
+                            && entry.depth() > 0
+                            && is_empty_dir(&entry_path))
+                    {
+                        continue;
+                    }
+                    EntryKind::EmptyDir
+                } else {
+                    match entry.metadata() {
+                        Ok(metadata) => EntryKind::File(metadata.len()),
+                        Err(e) => EntryKind::Unreadable(e.to_string()),
                     }
+                };
+
+                let relative_path = entry_path
+                    .strip_prefix(&self.source)
+                    .context("Failed to create relative path")?
+                    .to_path_buf();
+                if self.walk.skip_empty_files && matches!(kind, EntryKind::File(0)) {
+                    skipped_empty += 1;
                     continue;
                 }
+                entries.push(WalkEntry {
+                    path: relative_path,
+                    full_path: entry_path,
+                    kind,
+                });
             }
+        }
 
-            // Get relative path from base
-            // entry_path from jwalk is PathBuf (absolute if source is absolute?)
-            // jwalk docs: "The path is relative to the current working directory, unless root path was absolute."
-            // We canonicalized `source` above, so it is absolute. So `entry_path` is absolute.
-            let relative_path = entry_path
-                .strip_prefix(base_path)
-                .context("Failed to create relative path")?;
-
-            // Check exclude patterns
-            let file_name = entry.file_name().to_string_lossy();
-            let relative_path_str = relative_path.to_string_lossy();
-
-            let should_exclude = patterns
-                .iter()
-                .any(|p| p.matches(&file_name) || p.matches(&relative_path_str));
-
-            if should_exclude {
-                if verbose {
-                    eprintln!("Excluding: {}", entry_path.display());
-                }
+        if skipped_empty > 0 {
+            info!("Skipped {} empty file(s)", skipped_empty);
+        }
+        self.entries = entries;
+        self.walks += 1;
+        Ok(())
+    }
+
+    /// Apply exclude patterns to the cached walk, skipping `output_file` if it lies
+    /// inside the source. When `include_patterns` is non-empty, only files matching
+    /// one of them survive the excludes. Files come back sorted with their offsets
+    /// filled in.
+    pub fn files(
+        &self,
+        output_file: Option<&Path>,
+        exclude_patterns: &[String],
+        include_patterns: &[String],
+    ) -> Result<(Vec<FileInfo>, u64)> {
+        let filter = FileFilter::new(
+            output_file,
+            exclude_patterns,
+            include_patterns,
+            self.walk.include_hidden,
+        );
+        let mut files = self.filtered(&filter, Path::new(""))?;
+        let total_size = files.iter().map(|f| f.len).sum();
+
+        if self.single_file {
+            info!(
+                "Single file: {} ({} bytes)",
+                self.source.display(),
+                total_size
+            );
+        } else {
+            info!(
+                "Found {} files, total size: {} bytes",
+                files.len(),
+                total_size
+            );
+        }
+
+        // Sort files by path (critical for consistent info hash)
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // Calculate start offsets strictly after sorting
+        let mut current_offset = 0u64;
+        for file in &mut files {
+            file.start_offset = current_offset;
+            current_offset += file.len;
+        }
+
+        Ok((files, total_size))
+    }
+
+    /// The cached walk's files that pass `filter`, in walk order and without
+    /// offsets, with paths (as matched and as stored) below `prefix`
+    fn filtered(&self, filter: &FileFilter, prefix: &Path) -> Result<Vec<FileInfo>> {
+        let mut files = Vec::new();
+        for entry in &self.entries {
+            // The walk already dropped empty files below a directory, but not a
+            // single-file source
+            if self.walk.skip_empty_files && matches!(entry.kind, EntryKind::File(0)) {
+                continue;
+            }
+            let path = prefix.join(&entry.path);
+            // A single-file source is taken as-is
+            if !self.single_file
+                && !filter.keep(
+                    &path,
+                    &entry.full_path,
+                    matches!(entry.kind, EntryKind::EmptyDir),
+                    is_hidden(&entry.path),
+                )
+            {
                 continue;
             }
 
-            let metadata = entry
-                .metadata()
-                .context("Failed to read file metadata")?;
-            let len = metadata.len();
+            let (path, full_path, len) = match &entry.kind {
+                EntryKind::File(len) => {
+                    debug!("  {} ({} bytes)", path.display(), len);
+                    (path, entry.full_path.clone(), *len)
+                }
+                EntryKind::EmptyDir => {
+                    debug!("  {}/ (empty directory)", path.display());
+                    (
+                        path.join(EMPTY_DIR_MARKER),
+                        entry.full_path.join(EMPTY_DIR_MARKER),
+                        0,
+                    )
+                }
+                EntryKind::Unreadable(e) => bail!(
+                    "Failed to read file metadata: {}: {}",
+                    entry.full_path.display(),
+                    e
+                ),
+            };
 
             files.push(FileInfo {
-                path: relative_path.to_path_buf(),
-                full_path: entry_path.to_path_buf(),
+                path,
+                full_path,
                 len,
                 start_offset: 0, // Placeholder
                 is_padding: false,
+                data_offset: 0,
             });
+        }
+        Ok(files)
+    }
+}
 
-            total_size += len;
+/// The per-file checks shared by directory and glob sources: the output file, then
+/// the exclude patterns, hidden files and the include patterns. Archive entries go
+/// through it too, so an archive hashes like its extracted tree.
+pub(crate) struct FileFilter {
+    output: Option<PathBuf>,
+    excludes: Vec<Pattern>,
+    includes: Vec<Pattern>,
+    include_hidden: bool,
+}
 
-            if verbose {
-                eprintln!("  {} ({} bytes)", relative_path.display(), len);
-            }
+impl FileFilter {
+    pub(crate) fn new(
+        output_file: Option<&Path>,
+        excludes: &[String],
+        includes: &[String],
+        include_hidden: bool,
+    ) -> Self {
+        Self {
+            output: output_file.and_then(canonical_output_path),
+            excludes: compile_exclude_patterns(excludes),
+            includes: compile_exclude_patterns(includes),
+            include_hidden,
         }
+    }
 
-        if verbose {
-            eprintln!(
-                "Found {} files, total size: {} bytes",
-                files.len(),
-                total_size
-            );
+    /// Whether the file at `full_path`, stored as `path`, goes in the torrent.
+    /// `hidden` is whether the part of the path the user didn't name has a dotted
+    /// component. Empty directory markers aren't files the user picked, so includes
+    /// don't apply to them.
+    pub(crate) fn keep(
+        &self,
+        path: &Path,
+        full_path: &Path,
+        is_empty_dir: bool,
+        hidden: bool,
+    ) -> bool {
+        if let Some(ref output) = self.output
+            && is_output_file(full_path, output)
+        {
+            debug!("Skipping output file: {}", full_path.display());
+            return false;
+        }
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        let relative = path.to_string_lossy();
+        if is_excluded(&self.excludes, &file_name, &relative) {
+            debug!("Excluding: {}", full_path.display());
+            return false;
+        }
+        if hidden && !self.include_hidden && !is_explicitly_included(&self.includes, path) {
+            debug!("Skipping hidden: {}", full_path.display());
+            return false;
         }
+        if !is_empty_dir && !is_included(&self.includes, &file_name, &relative) {
+            debug!("Not included: {}", full_path.display());
+            return false;
+        }
+        true
     }
+}
 
-    // Sort files by path (critical for consistent info hash)
-    files.sort_by(|a, b| a.path.cmp(&b.path));
+/// Canonicalize the source, telling a missing path apart from a symlink whose
+/// target is gone and from one that can't be read
+pub(crate) fn resolve_source(source: &Path) -> Result<PathBuf> {
+    let err = match source.canonicalize() {
+        Ok(path) => return Ok(path),
+        Err(err) => err,
+    };
+    let is_symlink = std::fs::symlink_metadata(source).is_ok_and(|m| m.file_type().is_symlink());
+    match err.kind() {
+        std::io::ErrorKind::NotFound if is_symlink => match std::fs::read_link(source) {
+            Ok(target) => bail!(
+                "Source {} is a broken symlink: its target {} does not exist",
+                source.display(),
+                target.display()
+            ),
+            Err(_) => bail!("Source {} is a broken symlink", source.display()),
+        },
+        std::io::ErrorKind::NotFound => bail!("Source {} does not exist", source.display()),
+        std::io::ErrorKind::PermissionDenied => bail!(
+            "Permission denied while resolving source {}",
+            source.display()
+        ),
+        _ => {
+            Err(err).with_context(|| format!("Failed to resolve source path {}", source.display()))
+        }
+    }
+}
 
-    // Calculate start offsets strictly after sorting
-    let mut current_offset = 0u64;
-    for file in &mut files {
-        file.start_offset = current_offset;
-        current_offset += file.len;
+/// Resolve where the output torrent lives, or will live once written. A missing
+/// file can't be canonicalized, so its parent directory is resolved instead.
+fn canonical_output_path(output: &Path) -> Option<PathBuf> {
+    if let Ok(path) = output.canonicalize() {
+        return Some(path);
     }
+    let file_name = output.file_name()?;
+    let parent = match output.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    parent.canonicalize().ok().map(|p| p.join(file_name))
+}
 
-    // Sanity check
-    if current_offset != total_size {
-        if verbose {
-            eprintln!(
-                "Warning: Size mismatch after sorting? {} vs {}",
-                current_offset, total_size
-            );
+/// Whether a walked file is the output torrent, also when either path goes
+/// through a symlink. Only same-named files pay for the extra `canonicalize`.
+fn is_output_file(full_path: &Path, output_canonical: &Path) -> bool {
+    full_path == output_canonical
+        || (full_path.file_name() == output_canonical.file_name()
+            && full_path
+                .canonicalize()
+                .is_ok_and(|path| path == output_canonical))
+}
+
+/// Groups of paths that differ only in letter case, e.g. `README` and `readme`.
+/// They overwrite each other when extracted on a case-insensitive filesystem.
+/// Directories count too, so a file `Foo` clashes with `foo/bar`.
+pub fn find_case_collisions(files: &[FileInfo]) -> Vec<Vec<PathBuf>> {
+    let mut by_folded: BTreeMap<String, BTreeSet<&Path>> = BTreeMap::new();
+    for file in files.iter().filter(|f| !f.is_padding) {
+        for path in file.path.ancestors().filter(|p| !p.as_os_str().is_empty()) {
+            by_folded
+                .entry(path.to_string_lossy().to_lowercase())
+                .or_default()
+                .insert(path);
         }
     }
+    by_folded
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .map(|paths| paths.into_iter().map(Path::to_path_buf).collect())
+        .collect()
+}
 
-    Ok((files, total_size))
+/// Whether any component of a source-relative path starts with `.`
+pub fn is_hidden(path: &Path) -> bool {
+    path.components()
+        .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+}
+
+/// Whether an include pattern spells out the dots of a hidden path, e.g. `.env` or
+/// `.github/**`, rather than reaching it through a wildcard. A bare file name only
+/// counts when no directory above the file is hidden.
+fn is_explicitly_included(patterns: &[Pattern], path: &Path) -> bool {
+    let options = glob::MatchOptions {
+        require_literal_leading_dot: true,
+        ..Default::default()
+    };
+    let name_only = path.parent().is_none_or(|parent| !is_hidden(parent));
+    patterns.iter().any(|pattern| {
+        pattern.matches_path_with(path, options)
+            || (name_only
+                && path
+                    .file_name()
+                    .is_some_and(|name| pattern.matches_with(&name.to_string_lossy(), options)))
+    })
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    std::fs::read_dir(path)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(false)
+}
+
+/// Compile exclude or include globs, skipping (and warning about) invalid ones
+pub(crate) fn compile_exclude_patterns(exclude_patterns: &[String]) -> Vec<Pattern> {
+    let mut patterns = Vec::new();
+    for pattern_str in exclude_patterns {
+        match Pattern::new(pattern_str) {
+            Ok(p) => patterns.push(p),
+            Err(e) => {
+                warn!("Invalid glob pattern '{}': {}", pattern_str, e);
+            }
+        }
+    }
+    patterns
+}
+
+/// A file is excluded if any pattern matches its name or its path relative to the source
+pub(crate) fn is_excluded(patterns: &[Pattern], file_name: &str, relative_path: &str) -> bool {
+    patterns
+        .iter()
+        .any(|p| p.matches(file_name) || p.matches(relative_path))
+}
+
+/// With no include patterns every file is included; otherwise one must match
+pub(crate) fn is_included(patterns: &[Pattern], file_name: &str, relative_path: &str) -> bool {
+    patterns.is_empty() || is_excluded(patterns, file_name, relative_path)
 }
 
 /// Add padding files to align file boundaries with piece boundaries
@@ -176,6 +608,7 @@ pub fn add_padding_files(files: Vec<FileInfo>, piece_length: u64) -> Vec<FileInf
                 len: padding_len,
                 start_offset: current_offset,
                 is_padding: true,
+                data_offset: 0,
             };
             current_offset += padding_len;
             new_files.push(padding_file);
@@ -201,6 +634,244 @@ pub fn generate_cross_seed_id() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    /// Logger that records every message so tests can assert on diagnostics
+    struct CapturingLogger {
+        records: Mutex<Vec<(log::Level, String)>>,
+    }
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            self.records
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger {
+        records: Mutex::new(Vec::new()),
+    };
+
+    #[test]
+    fn test_is_hidden() {
+        assert!(is_hidden(Path::new(".DS_Store")));
+        assert!(is_hidden(Path::new(".git/config")));
+        assert!(is_hidden(Path::new("docs/.hidden/file.txt")));
+        assert!(!is_hidden(Path::new("docs/file.txt")));
+        assert!(!is_hidden(Path::new("")));
+    }
+
+    #[test]
+    fn test_invalid_glob_logs_warning() {
+        // Other tests may have installed it already; only one logger per process
+        if log::set_logger(&LOGGER).is_ok() {
+            log::set_max_level(log::LevelFilter::Trace);
+        }
+
+        let patterns = compile_exclude_patterns(&["*.nfo".to_string(), "[unclosed".to_string()]);
+        assert_eq!(patterns.len(), 1);
+
+        let records = LOGGER.records.lock().unwrap();
+        assert!(records.iter().any(|(level, msg)| *level == log::Level::Warn
+            && msg.contains("Invalid glob pattern '[unclosed'")));
+    }
+
+    #[test]
+    fn test_glob_excludes_match_paths_from_the_base() {
+        let dir = tempfile::tempdir().unwrap();
+        for show in ["A", "B"] {
+            std::fs::create_dir(dir.path().join(show)).unwrap();
+            std::fs::write(dir.path().join(show).join("ep1.mkv"), "video").unwrap();
+            std::fs::write(dir.path().join(show).join("sample.mkv"), "sample").unwrap();
+        }
+
+        // Only A's sample: the pattern names the path below the glob base, not below
+        // the matched directory
+        let (files, _) = scan_glob(
+            &dir.path().join("*"),
+            None,
+            &["A/sample.mkv".to_string()],
+            &[],
+            WalkOptions::default(),
+        )
+        .unwrap();
+        let paths: Vec<_> = files.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["A/ep1.mkv", "B/ep1.mkv", "B/sample.mkv"]);
+    }
+
+    #[test]
+    fn test_scanner_refilters_without_rewalking() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("movie.mkv"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("movie.nfo"), "info").unwrap();
+        std::fs::create_dir(dir.path().join("extras")).unwrap();
+        std::fs::write(dir.path().join("extras/sample.mkv"), vec![0u8; 30]).unwrap();
+
+        let mut scanner = Scanner::new(dir.path(), WalkOptions::default()).unwrap();
+        assert_eq!(scanner.walk_count(), 1);
+
+        let (all, total) = scanner.files(None, &[], &[]).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(total, 334);
+
+        // Files added after the walk stay invisible until an explicit rescan
+        std::fs::write(dir.path().join("late.txt"), "late").unwrap();
+        let (filtered, total) = scanner
+            .files(None, &["*.nfo".to_string(), "extras/*".to_string()], &[])
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].path, PathBuf::from("movie.mkv"));
+        assert_eq!(total, 300);
+        assert_eq!(scanner.walk_count(), 1);
+
+        scanner.rescan().unwrap();
+        assert_eq!(scanner.walk_count(), 2);
+        assert_eq!(scanner.files(None, &[], &[]).unwrap().0.len(), 4);
+    }
+
+    #[test]
+    fn test_scanner_rescan_follows_the_source_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("release");
+        std::fs::write(&source, "").unwrap();
+        let walk = WalkOptions {
+            skip_empty_files: true,
+            ..WalkOptions::default()
+        };
+
+        // An empty single-file source is dropped like any other empty file
+        let mut scanner = Scanner::new(&source, walk).unwrap();
+        assert!(scanner.files(None, &[], &[]).unwrap().0.is_empty());
+
+        // Replaced by a directory, it's walked as one
+        std::fs::remove_file(&source).unwrap();
+        std::fs::create_dir(&source).unwrap();
+        std::fs::write(source.join("a.bin"), "data").unwrap();
+        std::fs::write(source.join("b.nfo"), "info").unwrap();
+        std::fs::write(source.join("empty"), "").unwrap();
+        scanner.rescan().unwrap();
+        let (files, _) = scanner.files(None, &["*.nfo".to_string()], &[]).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, PathBuf::from("a.bin"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_source_errors() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+        let dangling = dir.path().join("dangling");
+        symlink(&missing, &dangling).unwrap();
+
+        let err = Scanner::new(&dangling, WalkOptions::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Source {} is a broken symlink: its target {} does not exist",
+                dangling.display(),
+                missing.display()
+            )
+        );
+        let err = resolve_source(&missing).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Source {} does not exist", missing.display())
+        );
+
+        std::fs::write(&missing, "now it exists").unwrap();
+        assert_eq!(
+            resolve_source(&dangling).unwrap(),
+            missing.canonicalize().unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_is_skipped_through_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        let elsewhere = dir.path().join("elsewhere");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::create_dir_all(&elsewhere).unwrap();
+        std::fs::write(source.join("data.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(source.join("release.torrent"), "old output").unwrap();
+        symlink(&source, dir.path().join("link")).unwrap();
+
+        let scanner = Scanner::new(&source, WalkOptions::default()).unwrap();
+        let names = |output: &Path| -> Vec<PathBuf> {
+            let (files, _) = scanner.files(Some(output), &[], &[]).unwrap();
+            files.into_iter().map(|f| f.path).collect()
+        };
+
+        // Output named through a symlinked directory into the source
+        let via_link = dir.path().join("link").join("release.torrent");
+        assert_eq!(names(&via_link), vec![PathBuf::from("data.bin")]);
+
+        // Output that doesn't exist yet still resolves through the link
+        assert_eq!(
+            canonical_output_path(&dir.path().join("link").join("new.torrent")),
+            Some(source.canonicalize().unwrap().join("new.torrent"))
+        );
+
+        // A symlink inside the source pointing at an output stored elsewhere
+        std::fs::remove_file(source.join("release.torrent")).unwrap();
+        let output = elsewhere.join("out.torrent");
+        std::fs::write(&output, "old output").unwrap();
+        symlink(&output, source.join("out.torrent")).unwrap();
+        let scanner = Scanner::new(&source, WalkOptions::default()).unwrap();
+        let (files, _) = scanner.files(Some(&output), &[], &[]).unwrap();
+        assert_eq!(
+            files.into_iter().map(|f| f.path).collect::<Vec<_>>(),
+            vec![PathBuf::from("data.bin")]
+        );
+    }
+
+    #[test]
+    fn test_find_case_collisions() {
+        let file = |path: &str| FileInfo {
+            path: PathBuf::from(path),
+            full_path: PathBuf::from("/src").join(path),
+            len: 1,
+            start_offset: 0,
+            is_padding: false,
+            data_offset: 0,
+        };
+        let files = vec![
+            file("README"),
+            file("Sub/a.txt"),
+            file("readme"),
+            file("sub/A.TXT"),
+            file("unique.txt"),
+        ];
+
+        assert_eq!(
+            find_case_collisions(&files),
+            vec![
+                vec![PathBuf::from("README"), PathBuf::from("readme")],
+                vec![PathBuf::from("Sub"), PathBuf::from("sub")],
+                vec![PathBuf::from("Sub/a.txt"), PathBuf::from("sub/A.TXT")],
+            ]
+        );
+        assert!(find_case_collisions(&files[..2]).is_empty());
+
+        // A file and a directory that differ only in case
+        assert_eq!(
+            find_case_collisions(&[file("Foo"), file("foo/bar")]),
+            vec![vec![PathBuf::from("Foo"), PathBuf::from("foo")]]
+        );
+    }
 
     #[test]
     fn test_add_padding_files() {
@@ -211,6 +882,7 @@ mod tests {
                 len: 100,
                 start_offset: 0,
                 is_padding: false,
+                data_offset: 0,
             },
             FileInfo {
                 path: PathBuf::from("b.txt"),
@@ -218,6 +890,7 @@ mod tests {
                 len: 200,
                 start_offset: 0,
                 is_padding: false,
+                data_offset: 0,
             },
         ];
         let piece_length = 50;
@@ -1,5 +1,7 @@
 //! Tracker-specific configurations and defaults.
 
+use std::ops::Range;
+
 /// Configuration for a specific tracker or group of trackers.
 pub struct TrackerConfig {
     /// List of tracker URLs (partial matches) that share this config.
@@ -14,6 +16,8 @@ pub struct TrackerConfig {
     pub max_torrent_size: Option<u64>,
     /// Whether to use default piece size ranges when content size is outside custom ranges.
     pub use_default_ranges: bool,
+    /// Whether uploads are rejected unless the private flag is set.
+    pub requires_private: bool,
 }
 
 /// Defines a range of content sizes and their corresponding piece size exponent.
@@ -36,6 +40,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: None,
         max_torrent_size: Some(250 * KIB),
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["nebulance.io"],
@@ -44,6 +49,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: None,
         max_torrent_size: Some(1024 * KIB),
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["hdbits.org", "superbits.org", "sptracker.cc"],
@@ -52,6 +58,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(24),
         max_torrent_size: None,
         use_default_ranges: true,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["beyond-hd.me"],
@@ -60,6 +67,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(24),
         max_torrent_size: None,
         use_default_ranges: true,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["passthepopcorn.me"],
@@ -78,6 +86,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(24),
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["morethantv.me"],
@@ -86,6 +95,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(23),
         max_torrent_size: None,
         use_default_ranges: true,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["empornium.sx"],
@@ -94,6 +104,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(23),
         max_torrent_size: None,
         use_default_ranges: true,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["gazellegames.net"],
@@ -113,8 +124,9 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
             PieceSizeRange { max_size: u64::MAX, piece_exp: 26 },    // 64 MiB
         ],
         max_piece_length: Some(26),
-        max_torrent_size: Some(1 * MIB),
+        max_torrent_size: Some(MIB),
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["tracker.alpharatio.cc"],
@@ -136,6 +148,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(26),
         max_torrent_size: Some(2 * MIB),
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["seedpool.org"],
@@ -158,6 +171,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(27),
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["norbits.net"],
@@ -173,6 +187,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(24),
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["landof.tv"],
@@ -192,6 +207,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(24),
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["torrent-syndikat.org", "tee-stube.org"],
@@ -207,6 +223,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(24),
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["lst.gg"],
@@ -221,6 +238,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: Some(24),
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["aither.cc"],
@@ -229,6 +247,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: None,
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["upload.cx"],
@@ -237,6 +256,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: None,
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["capybarabr.com"],
@@ -245,6 +265,7 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: None,
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
     TrackerConfig {
         urls: &["hawke.uno"],
@@ -253,9 +274,71 @@ pub static TRACKER_CONFIGS: &[TrackerConfig] = &[
         max_piece_length: None,
         max_torrent_size: None,
         use_default_ranges: false,
+        requires_private: true,
     },
 ];
 
+/// Short tracker names accepted by `--tracker-preset`, mapped to a URL their config matches.
+pub static TRACKER_PRESETS: &[(&str, &str)] = &[
+    ("ant", "anthelion.me"),
+    ("nbl", "nebulance.io"),
+    ("hdb", "hdbits.org"),
+    ("bhd", "beyond-hd.me"),
+    ("ptp", "passthepopcorn.me"),
+    ("mtv", "morethantv.me"),
+    ("emp", "empornium.sx"),
+    ("ggn", "gazellegames.net"),
+    ("ar", "tracker.alpharatio.cc"),
+    ("sp", "seedpool.org"),
+    ("nb", "norbits.net"),
+    ("ltv", "landof.tv"),
+    ("tsy", "torrent-syndikat.org"),
+    ("lst", "lst.gg"),
+    ("aither", "aither.cc"),
+    ("ulcx", "upload.cx"),
+    ("cbr", "capybarabr.com"),
+    ("hawke", "hawke.uno"),
+];
+
+/// Hosts of well-known open trackers. Anyone can announce to them, so listing one in
+/// a private torrent defeats the point of the private flag.
+pub static PUBLIC_TRACKER_HOSTS: &[&str] = &[
+    "opentrackr.org",
+    "openbittorrent.com",
+    "publicbt.com",
+    "torrent.eu.org",
+    "stealth.si",
+    "exodus.desync.com",
+    "explodie.org",
+    "demonii.com",
+    "coppersurfer.tk",
+    "leechers-paradise.org",
+    "moeking.me",
+    "dler.org",
+];
+
+/// Whether a tracker URL points at one of the [`PUBLIC_TRACKER_HOSTS`] or a subdomain of one
+pub fn is_public_tracker(tracker_url: &str) -> bool {
+    let Some(host) = tracker_host(tracker_url) else {
+        return false;
+    };
+    let host = host.to_ascii_lowercase();
+    PUBLIC_TRACKER_HOSTS.iter().any(|public| {
+        host == *public
+            || host
+                .strip_suffix(public)
+                .is_some_and(|sub| sub.ends_with('.'))
+    })
+}
+
+/// Returns the config for a short tracker name such as `ptp` (case-insensitive).
+pub fn find_tracker_preset(name: &str) -> Option<&'static TrackerConfig> {
+    TRACKER_PRESETS
+        .iter()
+        .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+        .and_then(|(_, url)| find_tracker_config(url))
+}
+
 /// Returns the config for a given tracker URL.
 pub fn find_tracker_config(tracker_url: &str) -> Option<&'static TrackerConfig> {
     for config in TRACKER_CONFIGS {
@@ -268,10 +351,112 @@ pub fn find_tracker_config(tracker_url: &str) -> Option<&'static TrackerConfig>
     None
 }
 
+/// Extracts the host part of a tracker URL (no scheme, credentials or port).
+pub fn tracker_host(tracker_url: &str) -> Option<&str> {
+    host_range(tracker_url).map(|range| &tracker_url[range])
+}
+
+/// Swap the host of a tracker URL, keeping scheme, credentials, port, path and query.
+///
+/// Returns `None` if the URL's host isn't `old_host` (compared case-insensitively).
+pub fn replace_tracker_host(tracker_url: &str, old_host: &str, new_host: &str) -> Option<String> {
+    let range = host_range(tracker_url)?;
+    if !tracker_url[range.clone()].eq_ignore_ascii_case(old_host) {
+        return None;
+    }
+    let mut replaced = tracker_url.to_string();
+    replaced.replace_range(range, new_host);
+    Some(replaced)
+}
+
+/// Byte range of the host within a tracker URL
+fn host_range(tracker_url: &str) -> Option<Range<usize>> {
+    let authority_start = tracker_url.find("://").map_or(0, |i| i + 3);
+    let rest = &tracker_url[authority_start..];
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_start = authority.rfind('@').map_or(0, |i| i + 1);
+    let host_port = &authority[host_start..];
+    let (offset, host) = if let Some(v6) = host_port.strip_prefix('[') {
+        (1, v6.split(']').next()?)
+    } else {
+        (0, host_port.split(':').next()?)
+    };
+    if host.is_empty() {
+        return None;
+    }
+    let start = authority_start + host_start + offset;
+    Some(start..start + host.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_public_tracker() {
+        assert!(is_public_tracker(
+            "udp://tracker.opentrackr.org:1337/announce"
+        ));
+        assert!(is_public_tracker("https://OPEN.STEALTH.SI/announce"));
+        assert!(is_public_tracker("http://opentrackr.org/announce"));
+        assert!(!is_public_tracker("https://notopentrackr.org/announce"));
+        assert!(!is_public_tracker("https://passthepopcorn.me/announce"));
+        assert!(!is_public_tracker("not a url"));
+    }
+
+    #[test]
+    fn test_every_preset_resolves() {
+        for (name, _) in TRACKER_PRESETS {
+            assert!(find_tracker_preset(name).is_some(), "{}", name);
+        }
+        assert_eq!(
+            find_tracker_preset("PTP").and_then(|c| c.default_source),
+            Some("PTP")
+        );
+        assert!(find_tracker_preset("nope").is_none());
+    }
+
+    #[test]
+    fn test_tracker_host() {
+        assert_eq!(
+            tracker_host("https://tracker.example.org:8443/abc/announce"),
+            Some("tracker.example.org")
+        );
+        assert_eq!(
+            tracker_host("udp://user:pw@open.tracker:1337"),
+            Some("open.tracker")
+        );
+        assert_eq!(tracker_host("http://[::1]:6969/announce"), Some("::1"));
+        assert_eq!(tracker_host("https:///announce"), None);
+    }
+
+    #[test]
+    fn test_replace_tracker_host() {
+        assert_eq!(
+            replace_tracker_host(
+                "https://Old.example:8443/abc123/announce?x=1",
+                "old.example",
+                "new.example"
+            )
+            .as_deref(),
+            Some("https://new.example:8443/abc123/announce?x=1")
+        );
+        assert_eq!(
+            replace_tracker_host(
+                "udp://user@old.example/old.example",
+                "old.example",
+                "new.tld"
+            )
+            .as_deref(),
+            Some("udp://user@new.tld/old.example")
+        );
+        // Only exact host matches, not subdomains
+        assert_eq!(
+            replace_tracker_host("https://tracker.old.example/announce", "old.example", "x"),
+            None
+        );
+    }
+
     #[test]
     fn test_find_tracker_config() {
         // Known trackers
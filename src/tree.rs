@@ -1,7 +1,26 @@
+use serde::Serialize;
 use std::collections::BTreeMap;
 use std::path::Path;
 
-use crate::models::{FileMetadata, FileNode, Node};
+use crate::models::{FileMetadata, FileNode, Info, Node};
+
+/// Files in a V2 file tree that share a pieces root, i.e. have identical content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateGroup {
+    /// Hex-encoded pieces root shared by every file in the group
+    pub pieces_root: String,
+    /// Size of each file
+    pub length: u64,
+    /// Paths relative to the torrent root, sorted
+    pub paths: Vec<String>,
+}
+
+impl DuplicateGroup {
+    /// Bytes spent on copies beyond the first
+    pub fn wasted(&self) -> u64 {
+        self.length * (self.paths.len() as u64 - 1)
+    }
+}
 
 /// Insert a file into the V2 file tree structure
 pub fn insert_into_tree(
@@ -49,11 +68,233 @@ fn insert_recursive(
     }
 }
 
+/// Take the file at `components` out of the tree, dropping directories it leaves empty
+pub(crate) fn remove_from_tree(
+    tree: &mut BTreeMap<String, Node>,
+    components: &[String],
+) -> Option<FileMetadata> {
+    let (name, rest) = components.split_first()?;
+    if rest.is_empty() {
+        return match tree.remove(name)? {
+            Node::File(file) => Some(file.metadata),
+            directory => {
+                tree.insert(name.clone(), directory);
+                None
+            }
+        };
+    }
+    let Some(Node::Directory(children)) = tree.get_mut(name) else {
+        return None;
+    };
+    let removed = remove_from_tree(children, rest);
+    if children.is_empty() {
+        tree.remove(name);
+    }
+    removed
+}
+
+/// Group identical files by pieces root, largest waste first
+///
+/// Uses the roots already computed by V2 hashing, so it costs nothing beyond a tree
+/// walk. Empty files are ignored since they all share the same root.
+pub fn find_duplicates(tree: &BTreeMap<String, Node>) -> Vec<DuplicateGroup> {
+    let mut by_root: BTreeMap<&[u8], (u64, Vec<String>)> = BTreeMap::new();
+    collect_files(tree, "", &mut |path, metadata| {
+        if metadata.length > 0 {
+            by_root
+                .entry(metadata.pieces_root.as_ref())
+                .or_insert_with(|| (metadata.length, Vec::new()))
+                .1
+                .push(path);
+        }
+    });
+
+    let mut groups: Vec<DuplicateGroup> = by_root
+        .into_iter()
+        .filter(|(_, (_, paths))| paths.len() > 1)
+        .map(|(root, (length, paths))| DuplicateGroup {
+            pieces_root: hex::encode(root),
+            length,
+            paths,
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.wasted()
+            .cmp(&a.wasted())
+            .then_with(|| a.paths.cmp(&b.paths))
+    });
+    groups
+}
+
+/// Every file in a V2 file tree with its `/`-separated path, in tree order
+pub fn tree_files(tree: &BTreeMap<String, Node>) -> Vec<(String, &FileMetadata)> {
+    let mut files = Vec::new();
+    collect_files(tree, "", &mut |path, metadata| files.push((path, metadata)));
+    files
+}
+
+/// Describe the first place a hybrid torrent's V1 file list and V2 file tree disagree
+///
+/// Both halves must list the same files, padding aside, in the same order and with
+/// the same lengths, or clients will map one of the two info hashes onto the wrong
+/// bytes. Returns `None` when they agree or the torrent isn't hybrid.
+pub fn hybrid_layout_mismatch(info: &Info) -> Option<String> {
+    let tree = info.file_tree.as_ref()?;
+    let v1: Vec<(String, u64)> = match (&info.files, info.length) {
+        (Some(files), _) => files
+            .iter()
+            .filter(|f| !f.attr.as_deref().is_some_and(|attr| attr.contains('p')))
+            .map(|f| (f.path.join("/"), f.length))
+            .collect(),
+        (None, Some(length)) => vec![(info.name.clone(), length)],
+        (None, None) => return None,
+    };
+    let v2: Vec<(String, u64)> = tree_files(tree)
+        .into_iter()
+        .map(|(path, metadata)| (path, metadata.length))
+        .collect();
+
+    if v1.len() != v2.len() {
+        return Some(format!(
+            "V1 lists {} files but the V2 file tree has {}",
+            v1.len(),
+            v2.len()
+        ));
+    }
+    for (i, ((v1_path, v1_len), (v2_path, v2_len))) in v1.iter().zip(&v2).enumerate() {
+        if v1_path != v2_path {
+            return Some(format!(
+                "file {} is '{}' in V1 but '{}' in the V2 file tree",
+                i + 1,
+                v1_path,
+                v2_path
+            ));
+        }
+        if v1_len != v2_len {
+            return Some(format!(
+                "'{}' is {} bytes in V1 but {} bytes in the V2 file tree",
+                v1_path, v1_len, v2_len
+            ));
+        }
+    }
+    None
+}
+
+fn collect_files<'a>(
+    tree: &'a BTreeMap<String, Node>,
+    prefix: &str,
+    visit: &mut impl FnMut(String, &'a FileMetadata),
+) {
+    for (name, node) in tree {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        match node {
+            Node::File(file) => visit(path, &file.metadata),
+            Node::Directory(children) => collect_files(children, &path, visit),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::FileEntry;
     use std::path::PathBuf;
 
+    fn hybrid_info(files: &[(&str, u64)]) -> Info {
+        let mut tree = BTreeMap::new();
+        let mut entries = Vec::new();
+        for &(path, length) in files {
+            insert_into_tree(&mut tree, Path::new(path), length, vec![0; 32]);
+            entries.push(FileEntry {
+                length,
+                path: path.split('/').map(String::from).collect(),
+                attr: None,
+                mtime: None,
+            });
+        }
+        Info {
+            piece_length: 16384,
+            pieces: None,
+            name: "root".to_string(),
+            private: None,
+            files: Some(entries),
+            length: None,
+            source: None,
+            x_cross_seed: None,
+            meta_version: Some(2),
+            file_tree: Some(tree),
+            similar: None,
+            collections: None,
+        }
+    }
+
+    #[test]
+    fn test_hybrid_layout_mismatch() {
+        let mut info = hybrid_info(&[("a.txt", 10), ("dir/b.txt", 20)]);
+        assert_eq!(hybrid_layout_mismatch(&info), None);
+
+        // Padding entries only exist on the V1 side
+        let files = info.files.as_mut().unwrap();
+        files.insert(
+            1,
+            FileEntry {
+                length: 16374,
+                path: vec![".pad".to_string(), "16374".to_string()],
+                attr: Some("p".to_string()),
+                mtime: None,
+            },
+        );
+        assert_eq!(hybrid_layout_mismatch(&info), None);
+
+        // V1 order diverging from the tree's sorted order must be caught
+        info.files.as_mut().unwrap().swap(0, 2);
+        let mismatch = hybrid_layout_mismatch(&info).unwrap();
+        assert!(
+            mismatch.contains("'dir/b.txt' in V1 but 'a.txt'"),
+            "{}",
+            mismatch
+        );
+
+        let mut info = hybrid_info(&[("a.txt", 10)]);
+        info.files.as_mut().unwrap()[0].length = 11;
+        assert!(
+            hybrid_layout_mismatch(&info)
+                .unwrap()
+                .contains("11 bytes in V1")
+        );
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let mut tree = BTreeMap::new();
+        for (path, length, root) in [
+            ("a.mkv", 100, 1),
+            ("copy/a.mkv", 100, 1),
+            ("b.srt", 10, 2),
+            ("extras/b.srt", 10, 2),
+            ("more/b.srt", 10, 2),
+            ("unique.nfo", 5, 3),
+            ("empty1", 0, 4),
+            ("empty2", 0, 4),
+        ] {
+            insert_into_tree(&mut tree, Path::new(path), length, vec![root; 32]);
+        }
+
+        let groups = find_duplicates(&tree);
+        // Largest waste first; unique and empty files are left out
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].paths, ["a.mkv", "copy/a.mkv"]);
+        assert_eq!(groups[0].length, 100);
+        assert_eq!(groups[0].pieces_root, hex::encode([1u8; 32]));
+        assert_eq!(groups[0].wasted(), 100);
+        assert_eq!(groups[1].paths, ["b.srt", "extras/b.srt", "more/b.srt"]);
+        assert_eq!(groups[1].wasted(), 20);
+    }
+
     #[test]
     fn test_insert_into_tree_single_file() {
         let mut tree = BTreeMap::new();
@@ -4,16 +4,73 @@ use indicatif::{ProgressBar, ProgressStyle};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::collections::BTreeMap;
+use std::time::Instant;
+use serde::Serialize;
 
 use torrite::cli::VerifyArgs;
-use torrite::models::{Torrent, Info, FileInfo, Node};
-use torrite::hashing::{hash_v1_pieces, hash_v2_files};
+use serde_bytes::ByteBuf;
+use torrite::models::{Torrent, Info, FileInfo, FileMetadata, Node};
+use torrite::hashing::{
+    Cancellation, HashError, hash_v1_pieces_cancellable, hash_v2_files_cancellable,
+};
+use torrite::scanner::EMPTY_DIR_MARKER;
 
 static SUCCESS: Emoji<'_, '_> = Emoji("✅ ", "OK");
 static ERROR: Emoji<'_, '_> = Emoji("❌ ", "ERR");
 static WARN: Emoji<'_, '_> = Emoji("⚠️ ", "WARN");
 
+/// How many directory levels below the `--find` start to search
+const FIND_MAX_DEPTH: usize = 4;
+
+/// Human-readable output, left out when the report goes to stdout as JSON
+macro_rules! say {
+    ($quiet:expr, $($arg:tt)*) => {
+        if !$quiet {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Outcome of a `verify` run, printed by `verify --json`
+#[derive(Debug, Default, Serialize)]
+struct VerifyReport {
+    ok: bool,
+    /// Files in the torrent, not counting padding
+    files_checked: usize,
+    pieces_checked: usize,
+    pieces_total: usize,
+    /// Indices of V1 pieces whose hash doesn't match
+    bad_pieces: Vec<usize>,
+    /// Files that are missing or have the wrong size
+    missing: Vec<String>,
+    /// Files (or blocks of them) whose V2 hashes don't match
+    v2_mismatches: Vec<String>,
+    v1_ok: bool,
+    v2_ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
 pub fn verify_torrent(args: VerifyArgs) -> Result<()> {
+    let json = args.json;
+    let mut report = VerifyReport {
+        v1_ok: true,
+        v2_ok: true,
+        ..VerifyReport::default()
+    };
+    let result = run_verify(args, &mut report);
+
+    if json {
+        report.ok = result.is_ok();
+        report.error = result.as_ref().err().map(|e| format!("{:#}", e));
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    }
+    result
+}
+
+fn run_verify(args: VerifyArgs, report: &mut VerifyReport) -> Result<()> {
+    let quiet = args.json;
+
     // 1. Read torrent file
     let content = fs::read(&args.torrent).context("Failed to read torrent file")?;
     let torrent: Torrent = serde_bencode::from_bytes(&content).context("Invalid torrent file")?;
@@ -22,48 +79,126 @@ pub fn verify_torrent(args: VerifyArgs) -> Result<()> {
     // If path is provided, use it.
     // If not, use current directory + name (common behavior for creating/verifying)
     // However, for single file torrents, it's often the file itself in cwd.
-    let content_root = if let Some(path) = args.path {
+    let content_root = if let Some(raw) = args.raw.clone() {
+        raw
+    } else if let Some(path) = args.path {
         path
+    } else if let Some(start) = args.find {
+        let found = find_content_root(&torrent.info, &start, FIND_MAX_DEPTH)?.ok_or_else(|| {
+            anyhow!(
+                "Could not find '{}' within {} levels of {}",
+                torrent.info.name,
+                FIND_MAX_DEPTH,
+                start.display()
+            )
+        })?;
+        say!(quiet, "Found content at: {}", style(found.display()).green());
+        found
     } else {
         std::env::current_dir()?.join(&torrent.info.name)
     };
 
-    println!("Verifying torrent: {}", style(&torrent.info.name).bold());
-    println!("Content path: {}", style(content_root.display()).cyan());
+    say!(quiet, "Verifying torrent: {}", style(&torrent.info.name).bold());
+    say!(quiet, "Content path: {}", style(content_root.display()).cyan());
 
     // 3. Build File List
-    let files = build_file_list(&torrent.info, &content_root)?;
+    let mut files = build_file_list(&torrent.info, &content_root)?;
 
     if files.is_empty() {
         return Err(anyhow!("No files found in torrent info"));
     }
+    report.files_checked = files.iter().filter(|f| !f.is_padding).count();
 
     // 4. Check existence and size
-    check_files_exist(&files)?;
+    let raw = args.raw.is_some();
+    if raw {
+        if torrent.info.pieces.is_none() {
+            return Err(anyhow!(
+                "Raw image verification needs V1 piece hashes, but this is a V2-only torrent"
+            ));
+        }
+        files = vec![raw_image_file(&torrent.info, &files, &content_root)];
+    }
+    if quiet {
+        // The report lists every problem, as with --continue-on-error
+        for file in &files {
+            report.missing.extend(file_problem(file)?);
+        }
+        if !report.missing.is_empty() {
+            return Err(anyhow!("{} files missing or wrong size", report.missing.len()));
+        }
+    } else {
+        check_files_exist(&files, args.continue_on_error && !raw)?;
+    }
 
-    // 5. Verify
-    let mut v1_ok = true;
-    let mut v2_ok = true;
+    if torrent.info.pieces.is_none() && torrent.info.meta_version != Some(2) {
+        say!(quiet, "{}", style("No hash data found in torrent (neither V1 pieces nor V2 tree).").red());
+        return Err(anyhow!("Invalid torrent: no hash data"));
+    }
+
+    // 5. Verify, stopping cleanly on Ctrl-C
+    let cancel = Cancellation::new();
+    {
+        let cancel = cancel.clone();
+        ctrlc::set_handler(move || cancel.cancel()).context("Failed to install Ctrl-C handler")?;
+    }
+    let started = Instant::now();
+    let mut pieces = PieceCount::default();
 
     // V1 Verification
     if torrent.info.pieces.is_some() {
-        println!("\n{}", style("Verifying V1 data...").bold());
-        v1_ok = verify_v1(&torrent.info, &files)?;
+        say!(quiet, "\n{}", style("Verifying V1 data...").bold());
+        let check = verify_v1(&torrent.info, &files, &cancel, quiet)?;
+        report.v1_ok = check.passed;
+        report.bad_pieces = check.bad_pieces;
+        pieces = check.count;
     }
 
-    // V2 Verification
-    if torrent.info.meta_version == Some(2) {
-         println!("\n{}", style("Verifying V2 data...").bold());
-         v2_ok = verify_v2(&torrent.info, &files)?;
-    } else if torrent.info.pieces.is_none() {
-        println!("{}", style("No hash data found in torrent (neither V1 pieces nor V2 tree).").red());
-        return Err(anyhow!("Invalid torrent: no hash data"));
+    // V2 Verification (per-file Merkle roots don't apply to a raw image)
+    if raw && torrent.info.meta_version == Some(2) {
+        say!(quiet, "{} Skipping V2 hashes for a raw image.", WARN);
+    } else if torrent.info.meta_version == Some(2) && !cancel.is_cancelled() {
+        say!(quiet, "\n{}", style("Verifying V2 data...").bold());
+        let total = v2_piece_count(&files, torrent.info.piece_length);
+        match verify_v2(&torrent.info, torrent.piece_layers.as_ref(), &files, &cancel, quiet) {
+            Ok(check) => {
+                report.v2_ok = check.passed;
+                report.v2_mismatches = check.mismatches;
+                if torrent.info.pieces.is_none() {
+                    pieces = PieceCount { checked: total, total };
+                }
+            }
+            Err(e) if e.downcast_ref::<HashError>() == Some(&HashError::Cancelled) => {
+                say!(quiet, "{} V2 verification interrupted.", WARN);
+                if torrent.info.pieces.is_none() {
+                    pieces = PieceCount { checked: 0, total };
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    report.pieces_checked = pieces.checked;
+    report.pieces_total = pieces.total;
+
+    let elapsed = started.elapsed();
+    if cancel.is_cancelled() {
+        say!(
+            quiet,
+            "\n{} {} Checked {} of {} pieces in {:.1?}.",
+            WARN,
+            style("Verification cancelled.").yellow().bold(),
+            pieces.checked,
+            pieces.total,
+            elapsed
+        );
+        return Err(anyhow!("Verification cancelled"));
     }
+    say!(quiet, "\nChecked {} pieces in {:.1?}.", pieces.total, elapsed);
 
-    if v1_ok && v2_ok {
-        println!("\n{} {}", SUCCESS, style("Verification Successful!").green().bold());
+    if report.v1_ok && report.v2_ok {
+        say!(quiet, "\n{} {}", SUCCESS, style("Verification Successful!").green().bold());
     } else {
-        println!("\n{} {}", ERROR, style("Verification Failed!").red().bold());
+        say!(quiet, "\n{} {}", ERROR, style("Verification Failed!").red().bold());
         // We don't bail here to allow caller to handle it, or we can exit with error.
         // The cli usually expects Result::Ok if command finished (even if verification failed? No, typically non-zero exit).
         return Err(anyhow!("Verification failed"));
@@ -72,7 +207,62 @@ pub fn verify_torrent(args: VerifyArgs) -> Result<()> {
     Ok(())
 }
 
-fn build_file_list(info: &Info, content_root: &Path) -> Result<Vec<FileInfo>> {
+/// Replace the per-file layout with `image` as the whole V1 byte stream. Padding
+/// files are part of that stream, so the image must include their zero bytes.
+fn raw_image_file(info: &Info, files: &[FileInfo], image: &Path) -> FileInfo {
+    FileInfo {
+        path: PathBuf::from(&info.name),
+        full_path: image.to_path_buf(),
+        len: files.iter().map(|f| f.len).sum(),
+        start_offset: 0,
+        is_padding: false,
+        data_offset: 0,
+    }
+}
+
+/// Breadth-first search below `start` for an entry named like the torrent whose
+/// layout matches, checking the sizes of a few sample files
+fn find_content_root(info: &Info, start: &Path, max_depth: usize) -> Result<Option<PathBuf>> {
+    let mut queue = std::collections::VecDeque::from([(start.to_path_buf(), 0usize)]);
+
+    while let Some((dir, depth)) = queue.pop_front() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut subdirs = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy() == info.name && layout_matches(info, &path)? {
+                return Ok(Some(path));
+            }
+            if depth < max_depth && entry.file_type().is_ok_and(|t| t.is_dir()) {
+                subdirs.push(path);
+            }
+        }
+        subdirs.sort();
+        queue.extend(subdirs.into_iter().map(|d| (d, depth + 1)));
+    }
+
+    Ok(None)
+}
+
+/// Whether the first, middle and last files of the torrent exist under `root` with the right sizes
+fn layout_matches(info: &Info, root: &Path) -> Result<bool> {
+    let files: Vec<FileInfo> = build_file_list(info, root)?
+        .into_iter()
+        .filter(|f| !f.is_padding)
+        .collect();
+    if files.is_empty() {
+        return Ok(false);
+    }
+
+    let samples = [0, files.len() / 2, files.len() - 1];
+    Ok(samples.iter().all(|&i| {
+        fs::metadata(&files[i].full_path).is_ok_and(|m| m.is_file() && m.len() == files[i].len)
+    }))
+}
+
+pub(crate) fn build_file_list(info: &Info, content_root: &Path) -> Result<Vec<FileInfo>> {
     let mut files = Vec::new();
     let mut offset = 0;
 
@@ -94,18 +284,29 @@ fn build_file_list(info: &Info, content_root: &Path) -> Result<Vec<FileInfo>> {
                 len: f.length,
                 start_offset: offset,
                 is_padding: f.attr.as_deref() == Some("p"),
+                data_offset: 0,
             });
             offset += f.length;
         }
-    } else if let Some(length) = info.length {
-        // Single-file mode
+    } else if info.is_single_file() {
+        // Single-file mode (V1 `length` or a one-file V2 tree)
         // content_root is the file itself.
+        let length = match info.length {
+            Some(length) => length,
+            None => info
+                .file_tree
+                .iter()
+                .flat_map(|t| t.values())
+                .map(Node::total_size)
+                .sum(),
+        };
         files.push(FileInfo {
-            path: PathBuf::from(&info.name), // Relative path for V2 tree logic (will be ignored or used as root?)
+            path: PathBuf::from(&info.name), // Single-file V2 trees are keyed by the torrent name
             full_path: content_root.to_path_buf(),
             len: length,
             start_offset: 0,
             is_padding: false,
+            data_offset: 0,
         });
     } else if let Some(ref tree) = info.file_tree {
         // V2 Only mode (no info.files)
@@ -121,13 +322,13 @@ fn build_file_list(info: &Info, content_root: &Path) -> Result<Vec<FileInfo>> {
 
 fn flatten_tree(
     tree: &BTreeMap<String, Node>,
-    rel_path: &PathBuf,
+    rel_path: &Path,
     base_path: &Path,
     files: &mut Vec<FileInfo>,
     offset: &mut u64,
 ) {
     for (name, node) in tree {
-        let mut new_rel = rel_path.clone();
+        let mut new_rel = rel_path.to_path_buf();
         if !name.is_empty() {
             new_rel.push(name);
         }
@@ -145,6 +346,7 @@ fn flatten_tree(
                     len: f.metadata.length,
                     start_offset: *offset,
                     is_padding: false, // V2 doesn't use padding files usually
+                    data_offset: 0,
                 });
                 *offset += f.metadata.length;
             }
@@ -155,7 +357,15 @@ fn flatten_tree(
     }
 }
 
-fn check_files_exist(files: &[FileInfo]) -> Result<()> {
+fn is_empty_dir_marker(file: &FileInfo) -> bool {
+    file.len == 0
+        && file.path.file_name() == Some(EMPTY_DIR_MARKER.as_ref())
+        && file.full_path.parent().is_some_and(Path::is_dir)
+}
+
+/// Check every file exists with the expected size. Stops at the first problem
+/// unless `continue_on_error` is set, in which case all problems are listed.
+pub(crate) fn check_files_exist(files: &[FileInfo], continue_on_error: bool) -> Result<()> {
     let pb = ProgressBar::new(files.len() as u64);
     pb.set_style(
         ProgressStyle::default_bar()
@@ -163,39 +373,106 @@ fn check_files_exist(files: &[FileInfo]) -> Result<()> {
             .progress_chars("#>- ")
     );
 
+    let mut problems = Vec::new();
     for file in files {
-        if file.is_padding {
-            continue;
-        }
-        if !file.full_path.exists() {
-            pb.finish_and_clear();
-            return Err(anyhow!("Missing file: {}", file.full_path.display()));
-        }
-        let metadata = fs::metadata(&file.full_path)
-            .with_context(|| format!("Failed to stat file: {}", file.full_path.display()))?;
-        
-        if metadata.len() != file.len {
-             pb.finish_and_clear();
-             return Err(anyhow!(
-                 "Size mismatch for file: {}. Expected {}, found {}",
-                 file.full_path.display(),
-                 file.len,
-                 metadata.len()
-             ));
+        if let Some(problem) = file_problem(file)? {
+            if !continue_on_error {
+                pb.finish_and_clear();
+                return Err(anyhow!(problem));
+            }
+            pb.suspend(|| println!("{} {}", ERROR, problem));
+            problems.push(problem);
         }
         pb.inc(1);
     }
     pb.finish_and_clear();
+
+    if !problems.is_empty() {
+        println!(
+            "\n{} {} of {} files are missing or have the wrong size",
+            ERROR,
+            style(problems.len()).red().bold(),
+            files.iter().filter(|f| !f.is_padding).count()
+        );
+        return Err(anyhow!("{} files missing or wrong size", problems.len()));
+    }
     println!("{} All files found and sizes match.", SUCCESS);
     Ok(())
 }
 
-fn verify_v1(info: &Info, files: &[FileInfo]) -> Result<bool> {
+/// Describe what's wrong with a file on disk, if anything
+fn file_problem(file: &FileInfo) -> Result<Option<String>> {
+    if file.is_padding {
+        return Ok(None);
+    }
+    if !file.full_path.exists() {
+        // `--keep-empty-dirs` placeholders need not exist on disk, only their directory
+        if is_empty_dir_marker(file) {
+            return Ok(None);
+        }
+        return Ok(Some(format!("Missing file: {}", file.full_path.display())));
+    }
+    let metadata = fs::metadata(&file.full_path)
+        .with_context(|| format!("Failed to stat file: {}", file.full_path.display()))?;
+
+    if metadata.len() != file.len {
+        return Ok(Some(format!(
+            "Size mismatch for file: {}. Expected {}, found {}",
+            file.full_path.display(),
+            file.len,
+            metadata.len()
+        )));
+    }
+    Ok(None)
+}
+
+/// Pieces hashed so far, out of the torrent's total
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct PieceCount {
+    checked: usize,
+    total: usize,
+}
+
+/// Outcome of the V1 pass; `passed` is false if it was cancelled before the end
+#[derive(Debug)]
+struct V1Check {
+    passed: bool,
+    count: PieceCount,
+    /// Indices of the pieces that didn't match
+    bad_pieces: Vec<usize>,
+}
+
+/// Outcome of the V2 pass, with a line per corrupt file or block
+#[derive(Debug)]
+struct V2Check {
+    passed: bool,
+    mismatches: Vec<String>,
+}
+
+/// Number of V2 pieces: each file's pieces start on a piece boundary
+fn v2_piece_count(files: &[FileInfo], piece_length: u64) -> usize {
+    files
+        .iter()
+        .filter(|f| !f.is_padding)
+        .map(|f| f.len.div_ceil(piece_length) as usize)
+        .sum()
+}
+
+fn verify_v1(
+    info: &Info,
+    files: &[FileInfo],
+    cancel: &Cancellation,
+    quiet: bool,
+) -> Result<V1Check> {
     let piece_length = info.piece_length;
     let expected_pieces = info.pieces.as_ref().unwrap(); // Safe because checked caller
     
     // Hash
-    let pb = ProgressBar::new(expected_pieces.len() as u64);
+    let pb = if quiet {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(expected_pieces.len() as u64)
+    };
     pb.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {bytes}/{total_bytes} Verifying V1")? 
@@ -205,50 +482,88 @@ fn verify_v1(info: &Info, files: &[FileInfo]) -> Result<bool> {
     // Reuse existing hasher. It returns all hashes.
     // Note: this reads the whole file.
     // We pass pb to it for progress.
-    let computed_hashes = hash_v1_pieces(files, piece_length, false, Some(pb))?;
+    let computed_hashes = hash_v1_pieces_cancellable(files, piece_length, Some(pb), cancel)?;
+    let num_pieces = expected_pieces.len() / 20;
 
-    if computed_hashes.len() != expected_pieces.len() {
-        println!("{} Hash length mismatch! Expected {}, got {}", ERROR, expected_pieces.len(), computed_hashes.len());
-        return Ok(false);
+    if computed_hashes.len() != num_pieces {
+        say!(quiet, "{} Hash length mismatch! Expected {}, got {}", ERROR, expected_pieces.len(), computed_hashes.len() * 20);
+        return Ok(V1Check {
+            passed: false,
+            count: PieceCount { checked: 0, total: num_pieces },
+            bad_pieces: Vec::new(),
+        });
     }
 
-    let mut bad_pieces = 0;
-    let num_pieces = expected_pieces.len() / 20;
+    let mut bad_pieces = Vec::new();
+    let mut checked = 0;
 
-    for i in 0..num_pieces {
-        let start = i * 20;
-        let end = start + 20;
-        if computed_hashes[start..end] != expected_pieces[start..end] {
-            bad_pieces += 1;
+    let pairs = expected_pieces.chunks_exact(20).zip(&computed_hashes);
+    for (index, (expected, computed)) in pairs.enumerate() {
+        // Pieces skipped after a cancellation are neither good nor bad
+        let Some(computed) = computed else {
+            continue;
+        };
+        checked += 1;
+        if computed[..] != *expected {
+            bad_pieces.push(index);
         }
     }
+    let count = PieceCount { checked, total: num_pieces };
+
+    if checked < num_pieces {
+        say!(
+            quiet,
+            "{} Cancelled after {} of {} pieces ({} corrupt so far)",
+            WARN, checked, num_pieces, bad_pieces.len()
+        );
+        return Ok(V1Check { passed: false, count, bad_pieces });
+    }
 
-    if bad_pieces > 0 {
-        println!("{} {} pieces corrupt out of {}", WARN, bad_pieces, num_pieces);
-        return Ok(false);
+    if !bad_pieces.is_empty() {
+        say!(quiet, "{} {} pieces corrupt out of {}", WARN, bad_pieces.len(), num_pieces);
+        return Ok(V1Check { passed: false, count, bad_pieces });
     }
 
-    println!("{} V1 verification passed.", SUCCESS);
-    Ok(true)
+    say!(quiet, "{} V1 verification passed.", SUCCESS);
+    Ok(V1Check { passed: true, count, bad_pieces })
 }
 
-fn verify_v2(info: &Info, files: &[FileInfo]) -> Result<bool> {
+fn verify_v2(
+    info: &Info,
+    piece_layers: Option<&BTreeMap<ByteBuf, ByteBuf>>,
+    files: &[FileInfo],
+    cancel: &Cancellation,
+    quiet: bool,
+) -> Result<V2Check> {
     let piece_length = info.piece_length;
     let expected_tree = info.file_tree.as_ref().context("Missing file tree for V2 torrent")?;
 
     // Hash
     // Actually we can sum files len.
     let total_size: u64 = files.iter().map(|f| f.len).sum();
-    let pb = ProgressBar::new(total_size);
+    let pb = if quiet {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(total_size)
+    };
     pb.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {bytes}/{total_bytes} Verifying V2")? 
             .progress_chars("#>- ")
     );
 
-    let is_single_file = info.length.is_some() || (expected_tree.len() == 1 && expected_tree.contains_key(""));
+    let is_single_file = info.is_single_file();
+    // Older torrite versions keyed the lone file of a single-file tree by "" instead of its name
+    let expected_tree: BTreeMap<String, Node> = if is_single_file {
+        expected_tree
+            .values()
+            .map(|node| (info.name.clone(), node.clone()))
+            .collect()
+    } else {
+        expected_tree.clone()
+    };
 
-    let result = hash_v2_files(files, piece_length, false, is_single_file, Some(pb))?;
+    let result = hash_v2_files_cancellable(files, piece_length, is_single_file, Some(pb), cancel)?;
 
     // Compare trees
     // We can't simply compare BTreeMaps because result.file_tree is constructed from files.
@@ -257,46 +572,127 @@ fn verify_v2(info: &Info, files: &[FileInfo]) -> Result<bool> {
     // Since we built `files` from `info` (or compatible), the structure should match.
     
     // Using PartialEq we added to Node
-    if &result.file_tree == expected_tree {
-        println!("{} V2 verification passed.", SUCCESS);
-        Ok(true)
+    if result.file_tree == expected_tree {
+        say!(quiet, "{} V2 verification passed.", SUCCESS);
+        Ok(V2Check {
+            passed: true,
+            mismatches: Vec::new(),
+        })
     } else {
-        println!("{} V2 Merkle tree mismatch.", ERROR);
-        // We could traverse and find which file is bad, but for now just report failure.
-        // To be more helpful:
-        find_v2_mismatches(expected_tree, &result.file_tree, "");
-        Ok(false)
+        say!(quiet, "{} V2 Merkle tree mismatch.", ERROR);
+        let layers = LayerLookup {
+            expected: piece_layers,
+            actual: &result.piece_layers,
+            piece_length,
+        };
+        let mismatches = find_v2_mismatches(&expected_tree, &result.file_tree, "", &layers);
+        for mismatch in &mismatches {
+            say!(quiet, "  {} {}", ERROR, mismatch);
+        }
+        Ok(V2Check {
+            passed: false,
+            mismatches,
+        })
     }
 }
 
-fn find_v2_mismatches(expected: &BTreeMap<String, Node>, actual: &BTreeMap<String, Node>, prefix: &str) {
+/// Expected (from the torrent) and recomputed piece layers, used to narrow a
+/// corrupt file down to the pieces that differ
+struct LayerLookup<'a> {
+    expected: Option<&'a BTreeMap<ByteBuf, ByteBuf>>,
+    actual: &'a BTreeMap<ByteBuf, ByteBuf>,
+    piece_length: u64,
+}
+
+/// Most corrupt blocks listed per file before summarizing the rest
+const MAX_REPORTED_BLOCKS: usize = 10;
+
+fn find_v2_mismatches(
+    expected: &BTreeMap<String, Node>,
+    actual: &BTreeMap<String, Node>,
+    prefix: &str,
+    layers: &LayerLookup,
+) -> Vec<String> {
+    let mut mismatches = Vec::new();
     for (name, expected_node) in expected {
         let full_name: String = if prefix.is_empty() { name.clone() } else { format!("{}/{}", prefix, name) };
         if let Some(actual_node) = actual.get(name) {
             match (expected_node, actual_node) {
                 (Node::File(ef), Node::File(af)) => {
                     if ef != af {
-                         println!("  {} File corrupt: {}", ERROR, full_name);
+                        mismatches.extend(describe_corrupt_file(&full_name, &ef.metadata, &af.metadata, layers));
                     }
                 }
                 (Node::Directory(ed), Node::Directory(ad)) => {
-                    find_v2_mismatches(ed, ad, &full_name);
+                    mismatches.extend(find_v2_mismatches(ed, ad, &full_name, layers));
                 }
                 _ => {
-                    println!("  {} Type mismatch for {}", ERROR, full_name);
+                    mismatches.push(format!("Type mismatch for {}", full_name));
                 }
             }
         } else {
-            println!("  {} Missing in result: {}", ERROR, full_name);
+            mismatches.push(format!("Missing in result: {}", full_name));
+        }
+    }
+    mismatches
+}
+
+/// Report which blocks (piece-layer entries) of a file differ, falling back to a
+/// file-level message when the layers can't be compared
+fn describe_corrupt_file(
+    name: &str,
+    expected: &FileMetadata,
+    actual: &FileMetadata,
+    layers: &LayerLookup,
+) -> Vec<String> {
+    let block_count = expected.length.div_ceil(layers.piece_length).max(1) as usize;
+    let bad_blocks: Vec<usize> = if block_count == 1 {
+        // Files no larger than a piece have no layer; the root covers the one block
+        vec![0]
+    } else {
+        let expected_layer = layers
+            .expected
+            .and_then(|l| l.get(&expected.pieces_root));
+        let actual_layer = layers.actual.get(&actual.pieces_root);
+        match (expected_layer, actual_layer) {
+            (Some(e), Some(a)) if e.len() == a.len() => e
+                .chunks(32)
+                .zip(a.chunks(32))
+                .enumerate()
+                .filter(|(_, (eh, ah))| eh != ah)
+                .map(|(i, _)| i)
+                .collect(),
+            _ => return vec![format!("File corrupt: {}", name)],
         }
+    };
+
+    let mut lines: Vec<String> = bad_blocks
+        .iter()
+        .take(MAX_REPORTED_BLOCKS)
+        .map(|&i| {
+            let start = i as u64 * layers.piece_length;
+            let end = (start + layers.piece_length).min(expected.length);
+            format!(
+                "{}: block {} of {} corrupt (bytes {}-{})",
+                name, i, block_count, start, end
+            )
+        })
+        .collect();
+    if bad_blocks.len() > MAX_REPORTED_BLOCKS {
+        lines.push(format!(
+            "{}: ... and {} more corrupt blocks",
+            name,
+            bad_blocks.len() - MAX_REPORTED_BLOCKS
+        ));
     }
+    lines
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use torrite::models::{FileMetadata, FileNode};
-    use serde_bytes::ByteBuf;
+    use torrite::models::FileNode;
+    use torrite::{Mode, TorrentBuilder, TorrentOptions};
 
     #[test]
     fn test_flatten_tree() {
@@ -343,4 +739,107 @@ mod tests {
         
         assert_eq!(offset, 300);
     }
+
+    #[test]
+    fn test_v2_mismatch_reports_corrupt_block() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = tmp_dir.path().join("content");
+        fs::create_dir_all(&root).unwrap();
+        // 8 blocks of 16 KiB, plus an untouched small file
+        let data: Vec<u8> = (0..8 * 16384u32).map(|i| (i % 251) as u8).collect();
+        fs::write(root.join("big.bin"), &data).unwrap();
+        fs::write(root.join("small.txt"), b"intact").unwrap();
+
+        let options = TorrentOptions {
+            mode: Mode::V2,
+            piece_length: Some(14),
+            ..Default::default()
+        };
+        let torrent = TorrentBuilder::new(root.clone(), options).build().unwrap();
+
+        // Flip one byte inside block 5
+        let mut corrupted = data.clone();
+        corrupted[5 * 16384 + 100] ^= 0xFF;
+        fs::write(root.join("big.bin"), &corrupted).unwrap();
+
+        let files = build_file_list(&torrent.info, &root).unwrap();
+        let result = torrite::hashing::hash_v2_files(&files, torrent.info.piece_length, false, None).unwrap();
+        let layers = LayerLookup {
+            expected: torrent.piece_layers.as_ref(),
+            actual: &result.piece_layers,
+            piece_length: torrent.info.piece_length,
+        };
+        let mismatches = find_v2_mismatches(
+            torrent.info.file_tree.as_ref().unwrap(),
+            &result.file_tree,
+            "",
+            &layers,
+        );
+
+        assert_eq!(
+            mismatches,
+            vec!["big.bin: block 5 of 8 corrupt (bytes 81920-98304)".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_verify_v2_single_file_legacy_tree() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let file_path = tmp_dir.path().join("single.bin");
+        fs::write(&file_path, vec![9u8; 40_000]).unwrap();
+
+        let options = TorrentOptions {
+            mode: Mode::V2,
+            ..Default::default()
+        };
+        let mut torrent = TorrentBuilder::new(file_path.clone(), options)
+            .build()
+            .unwrap();
+
+        let files = build_file_list(&torrent.info, &file_path).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].full_path, file_path);
+        assert_eq!(files[0].len, 40_000);
+        assert!(verify_v2(&torrent.info, torrent.piece_layers.as_ref(), &files, &Cancellation::new(), false).unwrap().passed);
+
+        // Trees written before the BEP 52 fix used "" as the file's key
+        let tree = torrent.info.file_tree.take().unwrap();
+        torrent.info.file_tree = Some(
+            tree.into_values()
+                .map(|node| (String::new(), node))
+                .collect(),
+        );
+        assert!(verify_v2(&torrent.info, torrent.piece_layers.as_ref(), &files, &Cancellation::new(), false).unwrap().passed);
+    }
+
+    #[test]
+    fn test_cancelled_verify_reports_partial_count() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let file_path = tmp_dir.path().join("data.bin");
+        fs::write(&file_path, vec![3u8; 100_000]).unwrap();
+
+        let options = TorrentOptions {
+            mode: Mode::Hybrid,
+            piece_length: Some(15),
+            ..Default::default()
+        };
+        let torrent = TorrentBuilder::new(file_path.clone(), options)
+            .build()
+            .unwrap();
+        let files = build_file_list(&torrent.info, &file_path).unwrap();
+
+        let check = verify_v1(&torrent.info, &files, &Cancellation::new(), false).unwrap();
+        assert!(check.passed);
+        assert_eq!(check.count, PieceCount { checked: 4, total: 4 });
+
+        let cancel = Cancellation::new();
+        cancel.cancel();
+        let check = verify_v1(&torrent.info, &files, &cancel, false).unwrap();
+        assert!(!check.passed);
+        assert_eq!(check.count, PieceCount { checked: 0, total: 4 });
+
+        let err = verify_v2(&torrent.info, torrent.piece_layers.as_ref(), &files, &cancel, false).unwrap_err();
+        assert_eq!(err.downcast_ref::<HashError>(), Some(&HashError::Cancelled));
+        assert_eq!(v2_piece_count(&files, torrent.info.piece_length), 4);
+    }
 }
@@ -0,0 +1,216 @@
+#![cfg(feature = "archive")]
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use torrite::{Mode, Torrent, TorrentBuilder, TorrentOptions};
+
+/// (path, contents) pairs spanning several pieces, including an empty file
+fn sample_entries() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("a.bin", (0..70_000u32).map(|i| (i % 251) as u8).collect()),
+        ("empty.txt", Vec::new()),
+        ("sub/b.txt", b"small file in a subdirectory".to_vec()),
+        (
+            "sub/c.bin",
+            (0..40_000u32).map(|i| (i % 13) as u8).collect(),
+        ),
+    ]
+}
+
+fn build(source: &Path, from_archive: bool) -> Torrent {
+    let options = TorrentOptions {
+        mode: Mode::Hybrid,
+        piece_length: Some(15),
+        no_date: true,
+        from_archive,
+        ..Default::default()
+    };
+    TorrentBuilder::new(source.to_path_buf(), options)
+        .build()
+        .unwrap()
+}
+
+fn build_extracted(root: &Path) -> Torrent {
+    build(&extract(root, sample_entries()), false)
+}
+
+/// Write `entries` below `root/release` as if an archive had been extracted there
+fn extract(root: &Path, entries: Vec<(&str, Vec<u8>)>) -> PathBuf {
+    let content_dir = root.join("release");
+    for (path, data) in entries {
+        let full = content_dir.join(path);
+        std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+        File::create(full).unwrap().write_all(&data).unwrap();
+    }
+    content_dir
+}
+
+fn write_zip(path: &Path, method: zip::CompressionMethod) {
+    write_zip_entries(path, method, sample_entries());
+}
+
+fn write_zip_entries(path: &Path, method: zip::CompressionMethod, entries: Vec<(&str, Vec<u8>)>) {
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    let options = zip::write::SimpleFileOptions::default().compression_method(method);
+    zip.add_directory("sub/", options).unwrap();
+    for (path, data) in entries {
+        zip.start_file(path, options).unwrap();
+        zip.write_all(&data).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+/// Write the sample entries as a tarball, in the given order
+fn write_tar(writer: impl Write, entries: Vec<(&str, Vec<u8>)>) {
+    let mut tar = tar::Builder::new(writer);
+    for (path, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, path, data.as_slice()).unwrap();
+    }
+    tar.into_inner().unwrap();
+}
+
+#[test]
+fn test_zip_matches_extracted_info_hash() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let zip_path = tmp_dir.path().join("release.zip");
+    write_zip(&zip_path, zip::CompressionMethod::Stored);
+
+    let from_zip = build(&zip_path, true);
+    let extracted = build_extracted(tmp_dir.path());
+
+    assert_eq!(from_zip.info.name, "release");
+    assert_eq!(from_zip.info_hash_v1(), extracted.info_hash_v1());
+    assert_eq!(from_zip.info_hash_v2(), extracted.info_hash_v2());
+}
+
+#[test]
+fn test_tar_matches_extracted_info_hash() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let tar_path = tmp_dir.path().join("release.tar");
+
+    write_tar(File::create(&tar_path).unwrap(), sample_entries());
+
+    let from_tar = build(&tar_path, true);
+    let extracted = build_extracted(tmp_dir.path());
+
+    assert_eq!(from_tar.info.name, "release");
+    assert_eq!(from_tar.info_hash_v1(), extracted.info_hash_v1());
+    assert_eq!(from_tar.info_hash_v2(), extracted.info_hash_v2());
+}
+
+#[test]
+fn test_deflated_zip_matches_extracted_info_hash() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let zip_path = tmp_dir.path().join("release.zip");
+    write_zip(&zip_path, zip::CompressionMethod::Deflated);
+
+    let from_zip = build(&zip_path, true);
+    let extracted = build_extracted(tmp_dir.path());
+
+    assert_eq!(from_zip.info_hash_v1(), extracted.info_hash_v1());
+    assert_eq!(from_zip.info_hash_v2(), extracted.info_hash_v2());
+}
+
+#[test]
+fn test_tar_gz_matches_extracted_info_hash() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let tar_path = tmp_dir.path().join("release.tar.gz");
+
+    // Out of order, so the archive has to be reread to hash in torrent order
+    let mut entries = sample_entries();
+    entries.reverse();
+    let gz = flate2::write::GzEncoder::new(
+        File::create(&tar_path).unwrap(),
+        flate2::Compression::default(),
+    );
+    write_tar(gz, entries);
+
+    let from_tar = build(&tar_path, true);
+    let extracted = build_extracted(tmp_dir.path());
+
+    assert_eq!(from_tar.info.name, "release");
+    assert_eq!(from_tar.info_hash_v1(), extracted.info_hash_v1());
+    assert_eq!(from_tar.info_hash_v2(), extracted.info_hash_v2());
+}
+
+#[test]
+fn test_unsupported_compression_is_named() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let tar_path = tmp_dir.path().join("release.tar.xz");
+    std::fs::write(&tar_path, b"\xFD7zXZ\0 not really xz").unwrap();
+
+    let options = TorrentOptions {
+        from_archive: true,
+        ..Default::default()
+    };
+    let err = TorrentBuilder::new(tar_path, options).build().unwrap_err();
+    assert!(err.to_string().contains("xz"), "{err:#}");
+}
+
+#[test]
+fn test_archive_dotfiles_are_skipped() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let tar_path = tmp_dir.path().join("release.tar");
+    let mut entries = sample_entries();
+    entries.push((".DS_Store", b"finder".to_vec()));
+    entries.push(("sub/.git/config", b"[core]".to_vec()));
+    write_tar(File::create(&tar_path).unwrap(), entries);
+
+    let options = TorrentOptions {
+        mode: Mode::Hybrid,
+        piece_length: Some(15),
+        no_date: true,
+        from_archive: true,
+        ..Default::default()
+    };
+    let from_tar = TorrentBuilder::new(tar_path, options).build().unwrap();
+    let extracted = build_extracted(tmp_dir.path());
+
+    assert_eq!(from_tar.info_hash_v1(), extracted.info_hash_v1());
+    assert_eq!(from_tar.info_hash_v2(), extracted.info_hash_v2());
+}
+
+#[test]
+fn test_zip_with_hidden_entry_matches_extracted_info_hash() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let zip_path = tmp_dir.path().join("release.zip");
+    let mut entries = sample_entries();
+    entries.push((".hidden", b"dotfile".to_vec()));
+    write_zip_entries(&zip_path, zip::CompressionMethod::Stored, entries.clone());
+    let content_dir = extract(tmp_dir.path(), entries);
+
+    // Skipped from both by default, kept in both with include_hidden
+    for include_hidden in [false, true] {
+        let build = |source: &Path, from_archive: bool| {
+            let options = TorrentOptions {
+                mode: Mode::Hybrid,
+                piece_length: Some(15),
+                no_date: true,
+                from_archive,
+                include_hidden,
+                ..Default::default()
+            };
+            TorrentBuilder::new(source.to_path_buf(), options)
+                .build()
+                .unwrap()
+        };
+        let from_zip = build(&zip_path, true);
+        let extracted = build(&content_dir, false);
+
+        let has_hidden = from_zip
+            .info
+            .files
+            .as_ref()
+            .unwrap()
+            .iter()
+            .any(|f| f.path == [".hidden"]);
+        assert_eq!(has_hidden, include_hidden);
+        assert_eq!(from_zip.info_hash_v1(), extracted.info_hash_v1());
+        assert_eq!(from_zip.info_hash_v2(), extracted.info_hash_v2());
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::field_reassign_with_default)]
+
 use std::fs::File;
 use std::io::Write;
 use torrite::{TorrentBuilder, TorrentOptions, Mode};
@@ -32,3 +34,83 @@ fn test_generate_hybrid_single_file_torrent() {
 
     std::fs::remove_dir_all(&tmp_dir).unwrap();
 }
+
+#[test]
+fn test_custom_name_for_directory_keeps_relative_paths() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("foo");
+    std::fs::create_dir_all(content_dir.join("sub")).unwrap();
+    File::create(content_dir.join("a.txt"))
+        .unwrap()
+        .write_all(b"file a")
+        .unwrap();
+    File::create(content_dir.join("sub").join("b.txt"))
+        .unwrap()
+        .write_all(b"file b")
+        .unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::Hybrid;
+    options.piece_length = Some(15);
+    options.name = Some("bar".into());
+
+    let builder = TorrentBuilder::new(content_dir, options);
+    let torrent = builder.build().unwrap();
+
+    assert_eq!(torrent.info.name, "bar");
+
+    // V1 paths are relative to the source directory, without either name
+    let paths: Vec<Vec<String>> = torrent
+        .info
+        .files
+        .unwrap()
+        .into_iter()
+        .filter(|f| f.attr.is_none())
+        .map(|f| f.path)
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            vec!["a.txt".to_string()],
+            vec!["sub".into(), "b.txt".into()]
+        ]
+    );
+    for path in &paths {
+        assert!(!path.iter().any(|c| c == "foo" || c == "bar"));
+    }
+
+    // V2 file tree is rooted the same way
+    let tree = torrent.info.file_tree.unwrap();
+    let roots: Vec<&String> = tree.keys().collect();
+    assert_eq!(roots, vec!["a.txt", "sub"]);
+}
+
+#[test]
+fn test_no_padding_hybrid() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("content");
+    std::fs::create_dir_all(&content_dir).unwrap();
+    File::create(content_dir.join("a.txt")).unwrap().write_all(b"not a full piece").unwrap();
+    File::create(content_dir.join("b.txt")).unwrap().write_all(b"second file").unwrap();
+
+    let build = |no_padding: bool| {
+        let mut options = TorrentOptions::default();
+        options.mode = Mode::Hybrid;
+        options.piece_length = Some(15);
+        options.no_padding = no_padding;
+        TorrentBuilder::new(content_dir.clone(), options).build().unwrap()
+    };
+    let is_pad = |path: &Vec<String>| path.first().is_some_and(|p| p == ".pad");
+
+    // Padding is inserted after a.txt by default
+    let padded = build(false).info.files.unwrap();
+    assert!(padded.iter().any(|f| is_pad(&f.path)));
+
+    let torrent = build(true);
+    let files = torrent.info.files.unwrap();
+    assert!(!files.iter().any(|f| is_pad(&f.path) || f.attr.is_some()));
+    assert_eq!(files.len(), 2);
+    // Still hybrid
+    assert_eq!(torrent.info.meta_version, Some(2));
+    assert!(torrent.info.pieces.is_some());
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::field_reassign_with_default)]
+
 use std::fs::File;
 use std::io::Write;
 use torrite::{TorrentBuilder, TorrentOptions};
@@ -41,3 +43,45 @@ fn test_torrent_metadata_options() {
 
     std::fs::remove_dir_all(&tmp_dir).unwrap();
 }
+
+#[test]
+fn test_bep38_fields_round_trip() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("bep38.txt");
+    File::create(&file_path)
+        .unwrap()
+        .write_all(b"BEP 38")
+        .unwrap();
+
+    let similar = vec![vec![0xab; 20], vec![0xcd; 32]];
+    let mut options = TorrentOptions::default();
+    options.similar = similar.clone();
+    options.collections = vec!["season-1".into(), "remux".into()];
+    options.no_date = true;
+
+    let torrent = TorrentBuilder::new(file_path.clone(), options.clone())
+        .build()
+        .unwrap();
+
+    let bytes = serde_bencode::to_bytes(&torrent).unwrap();
+    let parsed: torrite::Torrent = serde_bencode::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.info_hash_v1(), torrent.info_hash_v1());
+    let parsed_similar: Vec<Vec<u8>> = parsed
+        .info
+        .similar
+        .unwrap()
+        .into_iter()
+        .map(|h| h.into_vec())
+        .collect();
+    assert_eq!(parsed_similar, similar);
+    assert_eq!(
+        parsed.info.collections,
+        Some(vec!["season-1".to_string(), "remux".to_string()])
+    );
+
+    // Both fields live in the info dict, so they change the info hash
+    options.similar.clear();
+    options.collections.clear();
+    let plain = TorrentBuilder::new(file_path, options).build().unwrap();
+    assert_ne!(plain.info_hash_v1(), torrent.info_hash_v1());
+}
@@ -1,6 +1,10 @@
+#![allow(clippy::field_reassign_with_default)]
+
 use std::fs::File;
 use std::io::Write;
-use torrite::{TorrentBuilder, TorrentOptions};
+use std::path::PathBuf;
+use torrite::models::Node;
+use torrite::{FileEntryView, Mode, TorrentBuilder, TorrentOptions};
 
 #[test]
 fn test_exclude_patterns() {
@@ -54,3 +58,438 @@ fn test_piece_length_customization() {
 
     std::fs::remove_dir_all(&tmp_dir).unwrap();
 }
+
+#[test]
+fn test_keep_empty_dirs() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("content");
+    std::fs::create_dir_all(content_dir.join("empty")).unwrap();
+    std::fs::create_dir_all(content_dir.join("full")).unwrap();
+    File::create(content_dir.join("full/a.txt"))
+        .unwrap()
+        .write_all(b"data")
+        .unwrap();
+
+    // Off by default: the empty directory disappears
+    let torrent = TorrentBuilder::new(content_dir.clone(), TorrentOptions::default())
+        .build()
+        .unwrap();
+    let files = torrent.info.files.unwrap();
+    assert_eq!(files.len(), 1);
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::Hybrid;
+    options.keep_empty_dirs = true;
+    let torrent = TorrentBuilder::new(content_dir, options).build().unwrap();
+
+    let files = torrent.info.files.unwrap();
+    let keep = files
+        .iter()
+        .find(|f| f.path == vec!["empty", ".keep"])
+        .expect("placeholder missing");
+    assert_eq!(keep.length, 0);
+
+    let tree = torrent.info.file_tree.unwrap();
+    let Some(Node::Directory(empty)) = tree.get("empty") else {
+        panic!("empty dir missing from file tree")
+    };
+    assert!(matches!(empty.get(".keep"), Some(Node::File(f)) if f.metadata.length == 0));
+}
+
+#[test]
+fn test_build_timed_reports_phases() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("data.bin");
+    File::create(&file_path).unwrap().write_all(&vec![7u8; 256 * 1024]).unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::V2;
+    let (torrent, timings) = TorrentBuilder::new(file_path, options).with_threads(2).build_timed().unwrap();
+
+    assert!(torrent.info.file_tree.is_some());
+    // V1 hashing is skipped for V2-only torrents
+    assert_eq!(timings.hash_v1, std::time::Duration::ZERO);
+    assert!(timings.hash_v2 > std::time::Duration::ZERO);
+    assert!(timings.total >= timings.scan + timings.hash_v2);
+    assert_eq!(timings.bytes_hashed, 256 * 1024);
+}
+
+#[test]
+fn test_relative_to_keeps_directory_structure() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let base = tmp_dir.path().join("show");
+    let episode = base.join("season1").join("episode.mkv");
+    std::fs::create_dir_all(episode.parent().unwrap()).unwrap();
+    File::create(&episode).unwrap().write_all(b"episode data").unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::Hybrid;
+    options.relative_to = Some(base.clone());
+    let torrent = TorrentBuilder::new(episode.clone(), options).build().unwrap();
+
+    // A single file source still gets a multi-file layout under the base's name
+    assert_eq!(torrent.info.name, "show");
+    assert!(torrent.info.length.is_none());
+    let files = torrent.info.files.unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, vec!["season1", "episode.mkv"]);
+    let tree = torrent.info.file_tree.unwrap();
+    assert!(matches!(tree.get("season1"), Some(Node::Directory(d)) if d.contains_key("episode.mkv")));
+
+    // The source has to live below the base
+    let mut options = TorrentOptions::default();
+    options.relative_to = Some(base.join("season1").join("other"));
+    std::fs::create_dir_all(base.join("season1").join("other")).unwrap();
+    assert!(TorrentBuilder::new(episode, options).build().is_err());
+}
+
+#[test]
+fn test_dry_run_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("preview");
+    std::fs::create_dir_all(source.join("sub")).unwrap();
+    std::fs::write(source.join("b.bin"), vec![0u8; 40_000]).unwrap();
+    std::fs::write(source.join("sub/a.txt"), b"hello").unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::Hybrid;
+    options.piece_length = Some(15);
+    let report = TorrentBuilder::new(source, options)
+        .dry_run_report()
+        .unwrap();
+
+    assert_eq!(
+        report.files,
+        vec![
+            FileEntryView {
+                path: PathBuf::from("b.bin"),
+                length: 40_000,
+            },
+            FileEntryView {
+                path: PathBuf::from("sub/a.txt"),
+                length: 5,
+            },
+        ]
+    );
+    assert_eq!(report.total_size, 40_005);
+    assert_eq!(report.piece_length, 1 << 15);
+    assert_eq!(report.piece_exp, 15);
+    assert_eq!(report.num_pieces, 2);
+    assert_eq!(report.mode, Mode::Hybrid);
+}
+
+#[test]
+fn test_no_recursion_takes_top_level_files_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("flat");
+    std::fs::create_dir_all(source.join("sub/deeper")).unwrap();
+    std::fs::create_dir(source.join("empty")).unwrap();
+    std::fs::write(source.join("a.txt"), b"top level a").unwrap();
+    std::fs::write(source.join("b.txt"), b"top level b").unwrap();
+    std::fs::write(source.join("sub/c.txt"), b"nested").unwrap();
+    std::fs::write(source.join("sub/deeper/d.txt"), b"more nested").unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.no_date = true;
+    options.no_recursion = true;
+    options.keep_empty_dirs = true;
+    let torrent = TorrentBuilder::new(source, options).build().unwrap();
+
+    let paths: Vec<Vec<String>> = torrent
+        .info
+        .files
+        .unwrap()
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    assert_eq!(
+        paths,
+        vec![vec!["a.txt".to_string()], vec!["b.txt".to_string()]]
+    );
+    assert_eq!(torrent.info.name, "flat");
+}
+
+#[test]
+fn test_hybrid_progress_covers_both_passes() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("progress");
+    std::fs::create_dir(&source).unwrap();
+    std::fs::write(source.join("a.bin"), vec![1u8; 40_000]).unwrap();
+    std::fs::write(source.join("b.bin"), vec![2u8; 5]).unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::Hybrid;
+    options.piece_length = Some(15);
+    let pb = indicatif::ProgressBar::hidden();
+    TorrentBuilder::new(source, options)
+        .with_progress_bar(pb.clone())
+        .build()
+        .unwrap();
+
+    // V1 reads a.bin padded to two 32 KiB pieces plus b.bin; V2 reads only the content
+    let v1_bytes = 2 * 32_768 + 5;
+    let v2_bytes = 40_005;
+    assert_eq!(pb.length(), Some(v1_bytes + v2_bytes));
+    assert_eq!(pb.position(), v1_bytes + v2_bytes);
+    assert!(pb.is_finished());
+}
+
+#[test]
+fn test_invalid_torrent_names_are_rejected() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("data.bin");
+    File::create(&file_path).unwrap().write_all(b"some data").unwrap();
+
+    for name in ["", "   ", "a/b", "a\\b", ".."] {
+        let mut options = TorrentOptions::default();
+        options.name = Some(name.into());
+        assert!(TorrentBuilder::new(file_path.clone(), options).build().is_err(), "accepted {:?}", name);
+    }
+
+    // Surrounding whitespace is trimmed rather than rejected
+    let mut options = TorrentOptions::default();
+    options.name = Some(" Album ".into());
+    let torrent = TorrentBuilder::new(file_path, options).build().unwrap();
+    assert_eq!(torrent.info.name, "Album");
+}
+
+#[test]
+fn test_flatten_single_file_directory() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let dir = tmp_dir.path().join("Release");
+    std::fs::create_dir(&dir).unwrap();
+    let file_path = dir.join("movie.mkv");
+    File::create(&file_path).unwrap().write_all(&vec![9u8; 100_000]).unwrap();
+
+    // Without the flag the directory is kept
+    let torrent = TorrentBuilder::new(dir.clone(), TorrentOptions::default()).build().unwrap();
+    assert_eq!(torrent.info.name, "Release");
+    assert_eq!(torrent.info.files.as_ref().unwrap()[0].path, vec!["movie.mkv"]);
+
+    // With it the torrent matches one made from the file itself
+    for mode in [Mode::V1, Mode::V2, Mode::Hybrid] {
+        let mut options = TorrentOptions::default();
+        options.mode = mode;
+        options.flatten_single = true;
+        let flattened = TorrentBuilder::new(dir.clone(), options.clone()).build().unwrap();
+        assert_eq!(flattened.info.name, "movie.mkv");
+        assert!(flattened.info.files.is_none());
+
+        options.flatten_single = false;
+        let direct = TorrentBuilder::new(file_path.clone(), options).build().unwrap();
+        assert_eq!(flattened.info_hash_v1(), direct.info_hash_v1());
+        assert_eq!(flattened.info_hash_v2(), direct.info_hash_v2());
+    }
+
+    // A second file keeps the multi-file layout
+    File::create(dir.join("extra.nfo")).unwrap().write_all(b"nfo").unwrap();
+    let mut options = TorrentOptions::default();
+    options.flatten_single = true;
+    let torrent = TorrentBuilder::new(dir.clone(), options).build().unwrap();
+    assert_eq!(torrent.info.name, "Release");
+    assert_eq!(torrent.info.files.unwrap().len(), 2);
+
+    // Unless it's excluded, and then the name used for the output file
+    // follows the filtered list too
+    let mut options = TorrentOptions::default();
+    options.flatten_single = true;
+    options.exclude = vec!["*.nfo".into()];
+    let builder = TorrentBuilder::new(dir, options);
+    assert_eq!(builder.torrent_name().unwrap(), "movie.mkv");
+    let torrent = builder.build().unwrap();
+    assert_eq!(torrent.info.name, "movie.mkv");
+}
+
+#[test]
+fn test_strip_root() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let download = tmp_dir.path().join("download");
+    let release = download.join("Release.Name");
+    std::fs::create_dir_all(release.join("Subs")).unwrap();
+    File::create(release.join("movie.mkv")).unwrap().write_all(&vec![1u8; 50_000]).unwrap();
+    File::create(release.join("Subs").join("en.srt")).unwrap().write_all(b"subs").unwrap();
+
+    let paths = |torrent: &torrite::Torrent| -> Vec<String> {
+        torrent.info.files.as_ref().unwrap().iter().map(|f| f.path.join("/")).collect()
+    };
+
+    let plain = TorrentBuilder::new(download.clone(), TorrentOptions::default()).build().unwrap();
+    assert_eq!(plain.info.name, "download");
+    assert_eq!(paths(&plain), vec!["Release.Name/Subs/en.srt", "Release.Name/movie.mkv"]);
+
+    let mut options = TorrentOptions::default();
+    options.strip_root = true;
+    let stripped = TorrentBuilder::new(download.clone(), options.clone()).build().unwrap();
+    assert_eq!(stripped.info.name, "Release.Name");
+    assert_eq!(paths(&stripped), vec!["Subs/en.srt", "movie.mkv"]);
+    assert!(paths(&stripped).iter().all(|p| !p.contains("Release.Name")));
+
+    // A file beside the top-level directory leaves nothing to strip
+    File::create(download.join("stray.txt")).unwrap().write_all(b"stray").unwrap();
+    let err = TorrentBuilder::new(download.clone(), options).build().unwrap_err();
+    assert!(err.to_string().contains("single top-level directory"));
+
+    // Unless it's excluded: the name comes from the filtered file list
+    let mut options = TorrentOptions::default();
+    options.strip_root = true;
+    options.exclude = vec!["stray.txt".into()];
+    let builder = TorrentBuilder::new(download, options);
+    assert_eq!(builder.torrent_name().unwrap(), "Release.Name");
+    let stripped = builder.build().unwrap();
+    assert_eq!(stripped.info.name, "Release.Name");
+}
+
+#[test]
+fn test_absurd_piece_length_is_an_error() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("data.bin");
+    File::create(&file_path).unwrap().write_all(b"some data").unwrap();
+
+    for exp in [63, 64, 200, u32::MAX] {
+        let mut options = TorrentOptions::default();
+        options.piece_length = Some(exp);
+        let err = TorrentBuilder::new(file_path.clone(), options.clone()).build().unwrap_err();
+        assert!(err.to_string().contains("too large"), "{}", err);
+
+        options.piece_length = None;
+        options.max_piece_exp = Some(exp);
+        assert!(TorrentBuilder::new(file_path.clone(), options).dry_run_report().is_err());
+    }
+}
+
+#[test]
+fn test_exclude_hidden() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("project");
+    std::fs::create_dir_all(content_dir.join(".git").join("objects")).unwrap();
+    std::fs::create_dir_all(content_dir.join("docs")).unwrap();
+    File::create(content_dir.join(".git").join("objects").join("ab")).unwrap().write_all(b"object").unwrap();
+    File::create(content_dir.join(".DS_Store")).unwrap().write_all(b"finder").unwrap();
+    File::create(content_dir.join(".env")).unwrap().write_all(b"KEY=1").unwrap();
+    File::create(content_dir.join("docs").join("readme.txt")).unwrap().write_all(b"readme").unwrap();
+    File::create(content_dir.join("main.rs")).unwrap().write_all(b"fn main() {}").unwrap();
+
+    let paths = |torrent: torrite::Torrent| -> Vec<String> {
+        torrent.info.files.unwrap().into_iter().map(|f| f.path.join("/")).collect()
+    };
+
+    // Hidden files are found by the walk and only the option decides whether they stay
+    let mut options = TorrentOptions::default();
+    options.include_hidden = true;
+    let torrent = TorrentBuilder::new(content_dir.clone(), options).build().unwrap();
+    assert_eq!(
+        paths(torrent),
+        vec![".DS_Store", ".env", ".git/objects/ab", "docs/readme.txt", "main.rs"]
+    );
+
+    let torrent = TorrentBuilder::new(content_dir.clone(), TorrentOptions::default()).build().unwrap();
+    assert_eq!(paths(torrent), vec!["docs/readme.txt", "main.rs"]);
+
+    // An include that spells out the dot picks a hidden file; a wildcard doesn't
+    let mut options = TorrentOptions::default();
+    options.include_only = vec![".env".to_string(), "*.rs".to_string(), "ab".to_string()];
+    let torrent = TorrentBuilder::new(content_dir, options).build().unwrap();
+    assert_eq!(paths(torrent), vec![".env", "main.rs"]);
+}
+
+#[test]
+fn test_include_only() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("season");
+    std::fs::create_dir_all(content_dir.join("extras")).unwrap();
+    File::create(content_dir.join("e01.mkv")).unwrap().write_all(b"episode one").unwrap();
+    File::create(content_dir.join("e02.mkv")).unwrap().write_all(b"episode two").unwrap();
+    File::create(content_dir.join("e01.srt")).unwrap().write_all(b"subtitles").unwrap();
+    File::create(content_dir.join("info.nfo")).unwrap().write_all(b"nfo").unwrap();
+    File::create(content_dir.join("extras").join("sample.mkv")).unwrap().write_all(b"sample").unwrap();
+
+    let paths = |torrent: torrite::Torrent| -> Vec<String> {
+        torrent.info.files.unwrap().into_iter().map(|f| f.path.join("/")).collect()
+    };
+
+    let mut options = TorrentOptions::default();
+    options.include_only = vec!["*.mkv".to_string()];
+    let torrent = TorrentBuilder::new(content_dir.clone(), options.clone()).build().unwrap();
+    assert_eq!(paths(torrent), vec!["e01.mkv", "e02.mkv", "extras/sample.mkv"]);
+
+    // Excludes still win over includes
+    options.exclude = vec!["extras/*".to_string()];
+    let torrent = TorrentBuilder::new(content_dir, options).build().unwrap();
+    assert_eq!(paths(torrent), vec!["e01.mkv", "e02.mkv"]);
+}
+
+#[test]
+fn test_skip_empty_files() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("release");
+    std::fs::create_dir_all(content_dir.join("sub")).unwrap();
+    File::create(content_dir.join("data.bin")).unwrap().write_all(b"payload").unwrap();
+    for name in ["a.empty", "b.empty", "sub/c.empty"] {
+        File::create(content_dir.join(name)).unwrap();
+    }
+
+    let mut options = TorrentOptions::default();
+    let torrent = TorrentBuilder::new(content_dir.clone(), options.clone()).build().unwrap();
+    assert_eq!(torrent.info.files.unwrap().len(), 4);
+
+    options.skip_empty_files = true;
+    let torrent = TorrentBuilder::new(content_dir.clone(), options.clone()).build().unwrap();
+    let files = torrent.info.files.unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, vec!["data.bin"]);
+
+    // Nothing left to hash is an error, not an empty torrent
+    std::fs::remove_file(content_dir.join("data.bin")).unwrap();
+    assert!(TorrentBuilder::new(content_dir, options).build().is_err());
+}
+
+#[test]
+fn test_max_memory_cap_still_hashes_large_pieces() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("large.bin");
+    let data: Vec<u8> = (0..10_000_000u32).map(|i| (i % 251) as u8).collect();
+    File::create(&file_path).unwrap().write_all(&data).unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::Hybrid;
+    options.piece_length = Some(22);
+
+    let unbounded = TorrentBuilder::new(file_path.clone(), options.clone())
+        .with_threads(4)
+        .build()
+        .unwrap();
+    // 1 KiB is smaller than a single 4 MiB piece, so hashing falls back to one thread
+    let capped = TorrentBuilder::new(file_path, options)
+        .with_threads(4)
+        .with_max_memory(1024)
+        .build()
+        .unwrap();
+
+    assert_eq!(capped.info.pieces, unbounded.info.pieces);
+    assert_eq!(capped.info_hash_v1(), unbounded.info_hash_v1());
+    assert_eq!(capped.info_hash_v2(), unbounded.info_hash_v2());
+}
+
+#[test]
+fn test_min_pieces_lowers_piece_length() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("small.bin");
+    File::create(&file_path).unwrap().write_all(&vec![5u8; 150_000]).unwrap();
+    let piece_count = |torrent: &torrite::Torrent| torrent.info.pieces.as_ref().unwrap().len() / 20;
+
+    // The default 32 KiB pieces give only 5
+    let torrent = TorrentBuilder::new(file_path.clone(), TorrentOptions::default()).build().unwrap();
+    assert_eq!(torrent.info.piece_length, 1 << 15);
+    assert_eq!(piece_count(&torrent), 5);
+
+    let mut options = TorrentOptions::default();
+    options.min_pieces = Some(8);
+    let torrent = TorrentBuilder::new(file_path.clone(), options.clone()).build().unwrap();
+    assert_eq!(torrent.info.piece_length, 1 << 14);
+    assert!(piece_count(&torrent) >= 8);
+
+    // An explicit piece length wins
+    options.piece_length = Some(16);
+    let torrent = TorrentBuilder::new(file_path, options).build().unwrap();
+    assert_eq!(torrent.info.piece_length, 1 << 16);
+}
@@ -0,0 +1,70 @@
+//! A library build without progress or verbose output must not write to the console
+//!
+//! Output written straight to the process's stdout and stderr can't be captured from
+//! inside a test, so the test re-runs its own binary with `--nocapture` and checks
+//! what the child printed between two markers.
+
+use std::io::Write;
+use std::process::Command;
+use torrite::{Mode, TorrentBuilder, TorrentOptions};
+
+const CHILD_ENV: &str = "TORRITE_QUIET_TEST_DIR";
+const START: &str = "<<build start>>";
+const END: &str = "<<build end>>";
+
+/// The builds run in the child: every mode, plus warnings that must only reach `log`
+fn run_builds(dir: &std::path::Path) {
+    let content = dir.join("content");
+    std::fs::create_dir_all(content.join("nested")).unwrap();
+    std::fs::write(content.join("a.bin"), vec![1u8; 70_000]).unwrap();
+    std::fs::write(content.join("nested/b.bin"), vec![2u8; 5_000]).unwrap();
+    // Warned about as a likely mistake
+    std::fs::write(content.join("stray.torrent"), b"d4:infodee").unwrap();
+
+    for mode in [Mode::V1, Mode::V2, Mode::Hybrid] {
+        let options = TorrentOptions {
+            mode,
+            announce: vec!["udp://tracker.opentrackr.org:1337/announce".to_string()],
+            ..Default::default()
+        };
+        let builder = TorrentBuilder::new(content.clone(), options);
+        builder.dry_run_report().unwrap();
+        builder.build().unwrap();
+    }
+}
+
+#[test]
+fn test_library_build_is_silent() {
+    if let Ok(dir) = std::env::var(CHILD_ENV) {
+        let mut stdout = std::io::stdout();
+        writeln!(stdout, "{}", START).unwrap();
+        stdout.flush().unwrap();
+        run_builds(std::path::Path::new(&dir));
+        std::io::stderr().flush().unwrap();
+        writeln!(stdout, "{}", END).unwrap();
+        stdout.flush().unwrap();
+        // Skip the harness's summary so only the builds' output is between the markers
+        std::process::exit(0);
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "test_library_build_is_silent", "--nocapture"])
+        .env(CHILD_ENV, dir.path())
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        output.status.success(),
+        "child failed: {}{}",
+        stdout,
+        stderr
+    );
+
+    let start = stdout.find(START).expect("start marker") + START.len() + 1;
+    let end = stdout.find(END).expect("end marker");
+    assert_eq!(&stdout[start..end], "", "builds wrote to stdout");
+    assert_eq!(stderr, "", "builds wrote to stderr");
+}
@@ -0,0 +1,92 @@
+//! V1 info hashes checked against reference values computed outside torrite
+//!
+//! Each fixture is generated from a fixed byte pattern, so nothing binary lives in
+//! the repository. The expected hashes come from `tests/reference/v1_reference.py`,
+//! a standalone bencode + SHA-1 script that builds the same info dictionaries
+//! (sorted keys, no extra fields); `python3 tests/reference/v1_reference.py` (3.11)
+//! prints them. A mismatch here means piece hashing, file ordering or bencode key
+//! ordering has drifted from that script. They haven't been checked against
+//! mktorrent or imdl.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use torrite::{Mode, TorrentBuilder, TorrentOptions};
+
+/// `interop.bin`, 100 000 bytes of `pattern(_, 1)`, 32 KiB pieces
+const SINGLE_FILE_V1: &str = "3916907cf5d9400d908c318918ab190c889861fa";
+/// The same file with 64 KiB pieces, `private` set and source `INTEROP`
+const PRIVATE_SOURCE_V1: &str = "de58495c050dd391f4c8fecfb77bce648fc75109";
+/// `interop_set/` from [`MULTI_FILE_LAYOUT`], 32 KiB pieces
+const MULTI_FILE_V1: &str = "064a80e47f39637c2fcdf5a2629985659c3e4c32";
+
+/// Relative path, length and pattern seed of each file in the multi-file fixture.
+/// Pieces span file boundaries and an empty file sits in its own directory.
+const MULTI_FILE_LAYOUT: &[(&str, usize, usize)] = &[
+    ("cover.jpg", 5_000, 5),
+    ("disc1/track01.flac", 70_000, 3),
+    ("disc1/track02.flac", 40_000, 4),
+    ("disc2/notes.txt", 0, 0),
+    ("readme.txt", 1_000, 2),
+];
+
+/// Deterministic fixture content: byte `i` is `(i * 7 + seed) % 251`
+fn pattern(len: usize, seed: usize) -> Vec<u8> {
+    (0..len).map(|i| ((i * 7 + seed) % 251) as u8).collect()
+}
+
+fn write_fixture(path: &Path, len: usize, seed: usize) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    File::create(path)
+        .unwrap()
+        .write_all(&pattern(len, seed))
+        .unwrap();
+}
+
+fn v1_options(piece_exp: u32) -> TorrentOptions {
+    TorrentOptions {
+        mode: Mode::V1,
+        piece_length: Some(piece_exp),
+        ..Default::default()
+    }
+}
+
+fn v1_hash(source: &Path, options: TorrentOptions) -> String {
+    let torrent = TorrentBuilder::new(source.to_path_buf(), options)
+        .build()
+        .unwrap();
+    hex::encode(torrent.info_hash_v1().unwrap())
+}
+
+#[test]
+fn test_single_file_matches_reference() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("interop.bin");
+    write_fixture(&file_path, 100_000, 1);
+
+    assert_eq!(v1_hash(&file_path, v1_options(15)), SINGLE_FILE_V1);
+}
+
+#[test]
+fn test_private_with_source_matches_reference() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("interop.bin");
+    write_fixture(&file_path, 100_000, 1);
+
+    let mut options = v1_options(16);
+    options.private = true;
+    options.source_string = Some("INTEROP".to_string());
+    assert_eq!(v1_hash(&file_path, options), PRIVATE_SOURCE_V1);
+}
+
+#[test]
+fn test_multi_file_matches_reference() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("interop_set");
+    // Written out of order so the result can't depend on creation order
+    for (path, len, seed) in MULTI_FILE_LAYOUT.iter().rev() {
+        write_fixture(&content_dir.join(path), *len, *seed);
+    }
+
+    assert_eq!(v1_hash(&content_dir, v1_options(15)), MULTI_FILE_V1);
+}
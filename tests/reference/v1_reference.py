@@ -0,0 +1,33 @@
+"""Print the reference V1 info hashes used by tests/reference.rs.
+
+Standalone on purpose: plain bencode and hashlib, nothing from torrite. Each
+constant is named as in the test file. Run with `python3 tests/reference/v1_reference.py`.
+"""
+
+import hashlib
+
+def bencode(v):
+    if isinstance(v, int): return b"i%de" % v
+    if isinstance(v, str): v = v.encode()
+    if isinstance(v, bytes): return b"%d:%s" % (len(v), v)
+    if isinstance(v, list): return b"l" + b"".join(map(bencode, v)) + b"e"
+    return b"d" + b"".join(bencode(k) + bencode(v[k]) for k in sorted(v)) + b"e"
+
+def pattern(n, seed): return bytes((i * 7 + seed) % 251 for i in range(n))
+
+def pieces(data, plen):
+    return b"".join(hashlib.sha1(data[i:i + plen]).digest() for i in range(0, len(data), plen))
+
+def info_hash(info): return hashlib.sha1(bencode(info)).hexdigest()
+
+single = pattern(100_000, 1)
+print("SINGLE_FILE_V1", info_hash({"name": "interop.bin", "length": len(single),
+      "piece length": 1 << 15, "pieces": pieces(single, 1 << 15)}))
+print("PRIVATE_SOURCE_V1", info_hash({"name": "interop.bin", "length": len(single),
+      "piece length": 1 << 16, "pieces": pieces(single, 1 << 16), "private": 1, "source": "INTEROP"}))
+layout = [("cover.jpg", 5_000, 5), ("disc1/track01.flac", 70_000, 3),
+          ("disc1/track02.flac", 40_000, 4), ("disc2/notes.txt", 0, 0), ("readme.txt", 1_000, 2)]
+data = b"".join(pattern(n, s) for _, n, s in layout)
+print("MULTI_FILE_V1", info_hash({"name": "interop_set", "piece length": 1 << 15,
+      "pieces": pieces(data, 1 << 15),
+      "files": [{"length": n, "path": p.split("/")} for p, n, _ in layout]}))
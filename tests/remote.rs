@@ -0,0 +1,79 @@
+#![cfg(feature = "http")]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use torrite::{Mode, TorrentBuilder, TorrentOptions};
+
+fn sample_data() -> Vec<u8> {
+    (0..100_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+/// Serve `data` over plain HTTP for `requests` connections, returning the file's URL
+fn serve(data: Vec<u8>, requests: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "http://{}/files/Sample%20Data.bin",
+        listener.local_addr().unwrap()
+    );
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                data.len()
+            )
+            .unwrap();
+            stream.write_all(&data).unwrap();
+        }
+    });
+    url
+}
+
+fn options(mode: Mode) -> TorrentOptions {
+    TorrentOptions {
+        mode,
+        piece_length: Some(15),
+        no_date: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_from_url_matches_local_file() {
+    let data = sample_data();
+    let url = serve(data.clone(), 3);
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let local = tmp_dir.path().join("Sample Data.bin");
+    std::fs::write(&local, &data).unwrap();
+
+    for mode in [Mode::V1, Mode::V2, Mode::Hybrid] {
+        let mut remote_options = options(mode);
+        remote_options.from_url = true;
+        let remote = TorrentBuilder::new(PathBuf::from(&url), remote_options)
+            .build()
+            .unwrap();
+
+        let mut local_options = options(mode);
+        local_options.web_seed = vec![url.clone()];
+        let local = TorrentBuilder::new(local.clone(), local_options)
+            .build()
+            .unwrap();
+
+        assert_eq!(remote.info.name, "Sample Data.bin");
+        assert_eq!(remote.url_list, Some(vec![url.clone()]));
+        assert_eq!(
+            serde_bencode::to_bytes(&remote).unwrap(),
+            serde_bencode::to_bytes(&local).unwrap(),
+            "{:?}",
+            mode
+        );
+    }
+}
@@ -84,6 +84,28 @@ fn test_output_json() {
         .stdout(predicate::str::contains("\"info_hash_v1\":"));
 }
 
+#[test]
+fn test_output_json_v2_short_hash() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("json_v2.txt");
+    fs::write(&source_file, "json v2 data").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg(&source_file)
+        .arg("--v2")
+        .arg("--json")
+        .arg("-o")
+        .arg(temp_dir.path().join("out.torrent"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(summary.get("info_hash_v1").is_none());
+    let full = summary["info_hash_v2"].as_str().unwrap();
+    assert_eq!(summary["info_hash_v2_short"].as_str().unwrap(), &full[..40]);
+}
+
 #[test]
 fn test_verify() {
     let mut cmd_create = Command::new(env!("CARGO_BIN_EXE_torrite"));
@@ -113,6 +135,164 @@ fn test_verify() {
         .stdout(predicate::str::contains("Verification Successful!"));
 }
 
+#[test]
+fn test_verify_json_report() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("set");
+    fs::create_dir(&source_dir).unwrap();
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    fs::write(source_dir.join("a.bin"), &data).unwrap();
+    fs::write(source_dir.join("b.bin"), b"second file").unwrap();
+    let torrent_file = temp_dir.path().join("set.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .args(["-l", "15", "-o"])
+        .arg(&torrent_file)
+        .assert()
+        .success();
+
+    let report = |expect_ok: bool| -> serde_json::Value {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("verify")
+            .arg(&torrent_file)
+            .arg("--path")
+            .arg(&source_dir)
+            .arg("--json");
+        let assert = if expect_ok {
+            cmd.assert().success()
+        } else {
+            cmd.assert().failure()
+        };
+        serde_json::from_slice(&assert.get_output().stdout).unwrap()
+    };
+
+    let good = report(true);
+    assert_eq!(good["ok"], true);
+    assert_eq!(good["files_checked"], 2);
+    assert_eq!(good["pieces_checked"], 4);
+    assert_eq!(good["bad_pieces"], serde_json::json!([]));
+    assert_eq!(good["missing"], serde_json::json!([]));
+    assert_eq!(good["v1_ok"], true);
+    assert_eq!(good["v2_ok"], true);
+
+    // Corrupt the second 32 KiB piece
+    let mut corrupted = data.clone();
+    corrupted[40_000] ^= 0xFF;
+    fs::write(source_dir.join("a.bin"), &corrupted).unwrap();
+    let bad = report(false);
+    assert_eq!(bad["ok"], false);
+    assert_eq!(bad["bad_pieces"], serde_json::json!([1]));
+    assert_eq!(bad["v1_ok"], false);
+
+    fs::remove_file(source_dir.join("b.bin")).unwrap();
+    let missing = report(false);
+    assert_eq!(missing["ok"], false);
+    assert_eq!(missing["missing"].as_array().unwrap().len(), 1);
+    assert!(missing["missing"][0].as_str().unwrap().contains("b.bin"));
+}
+
+#[test]
+fn test_verify_raw_image() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("disc");
+    fs::create_dir(&source_dir).unwrap();
+    let first: Vec<u8> = (0..50_000u32).map(|i| (i % 251) as u8).collect();
+    let second: Vec<u8> = (0..30_000u32).map(|i| (i % 13) as u8).collect();
+    fs::write(source_dir.join("a.bin"), &first).unwrap();
+    fs::write(source_dir.join("b.bin"), &second).unwrap();
+    let torrent_file = temp_dir.path().join("disc.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .args(["-l", "15", "-o"])
+        .arg(&torrent_file)
+        .assert()
+        .success();
+
+    // The two files back to back, in torrent order
+    let image = temp_dir.path().join("disc.img");
+    fs::write(&image, [first.as_slice(), second.as_slice()].concat()).unwrap();
+    let verify_raw = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("verify")
+            .arg(&torrent_file)
+            .arg("--raw")
+            .arg(&image);
+        cmd
+    };
+    verify_raw()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Verification Successful!"));
+
+    // Corruption in the second file's span is caught through the image
+    let mut corrupted = [first.as_slice(), second.as_slice()].concat();
+    corrupted[60_000] ^= 0xFF;
+    fs::write(&image, &corrupted).unwrap();
+    verify_raw()
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 pieces corrupt"));
+
+    // An image of the wrong size is rejected before hashing
+    fs::write(&image, &first).unwrap();
+    verify_raw()
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Size mismatch"));
+}
+
+#[test]
+fn test_verify_continue_on_error() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("download");
+    fs::create_dir(&source_dir).unwrap();
+    for name in ["a.bin", "b.bin", "c.bin"] {
+        fs::write(source_dir.join(name), name.repeat(100)).unwrap();
+    }
+    let torrent_file = temp_dir.path().join("download.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .arg("-o")
+        .arg(&torrent_file)
+        .assert()
+        .success();
+
+    fs::remove_file(source_dir.join("a.bin")).unwrap();
+    fs::remove_file(source_dir.join("c.bin")).unwrap();
+
+    let verify = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("verify")
+            .arg(&torrent_file)
+            .arg("--path")
+            .arg(&source_dir);
+        cmd
+    };
+
+    // By default only the first problem is reported
+    verify()
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("a.bin"))
+        .stderr(predicate::str::contains("c.bin").not());
+
+    verify()
+        .arg("--continue-on-error")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Missing file").and(predicate::str::contains("a.bin")))
+        .stdout(predicate::str::contains("c.bin"))
+        .stdout(predicate::str::contains(
+            "2 of 3 files are missing or have the wrong size",
+        ));
+}
+
 #[test]
 fn test_edit() {
     let mut cmd_create = Command::new(env!("CARGO_BIN_EXE_torrite"));
@@ -156,6 +336,286 @@ fn test_edit() {
         .success();
 }
 
+#[test]
+fn test_edit_batch() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    for name in ["one", "two"] {
+        let source_file = temp_dir.path().join(format!("{}.txt", name));
+        fs::write(&source_file, format!("{} data", name)).unwrap();
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .arg("create")
+            .arg(&source_file)
+            .arg("-o")
+            .arg(temp_dir.path().join(format!("{}.torrent", name)))
+            .assert()
+            .success();
+    }
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("edit")
+        .arg("--batch")
+        .arg(temp_dir.path().join("*.torrent"))
+        .arg("--comment")
+        .arg("Batch Comment")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("one.torrent"))
+        .stdout(predicate::str::contains("two.torrent"))
+        .stdout(predicate::str::contains("Edited 2 of 2 torrent(s)"));
+
+    for name in ["one", "two"] {
+        let torrent: torrite::Torrent = serde_bencode::from_bytes(
+            &fs::read(temp_dir.path().join(format!("{}.torrent", name))).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(torrent.comment.as_deref(), Some("Batch Comment"));
+    }
+
+    // Nothing to apply is an error rather than a silent no-op
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("edit")
+        .arg("--batch")
+        .arg(temp_dir.path().join("*.torrent"))
+        .assert()
+        .failure();
+
+    // A file that can't be edited is reported on stderr and fails the run, but
+    // doesn't stop the others from being edited
+    fs::write(temp_dir.path().join("bad.torrent"), "not bencode").unwrap();
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("edit")
+        .arg("--batch")
+        .arg(temp_dir.path().join("*.torrent"))
+        .arg("--comment")
+        .arg("Second Comment")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("bad.torrent"))
+        .stdout(predicate::str::contains("Error:").not())
+        .stdout(predicate::str::contains("Edited 2 of 3 torrent(s)"));
+}
+
+#[test]
+fn test_announce_list_flatten() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("flatten.txt");
+    fs::write(&source_file, "flatten test data").unwrap();
+    let tiers = |path: &std::path::Path| {
+        let torrent: torrite::Torrent =
+            serde_bencode::from_bytes(&fs::read(path).unwrap()).unwrap();
+        torrent.trackers()
+    };
+
+    let flat = temp_dir.path().join("flat.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args([
+            "-a",
+            "http://a/announce,http://b/announce",
+            "-a",
+            "http://c/announce",
+        ])
+        .arg("--announce-list-flatten")
+        .arg("-o")
+        .arg(&flat)
+        .assert()
+        .success();
+    assert_eq!(
+        tiers(&flat),
+        vec![vec![
+            "http://a/announce",
+            "http://b/announce",
+            "http://c/announce"
+        ]]
+    );
+
+    let tiered = temp_dir.path().join("tiered.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["-a", "http://a/announce", "-a", "http://b/announce"])
+        .arg("-o")
+        .arg(&tiered)
+        .assert()
+        .success();
+    assert_eq!(tiers(&tiered).len(), 2);
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("edit")
+        .arg(&tiered)
+        .arg("--announce-list-flatten")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Flattened trackers into a single tier.",
+        ));
+    assert_eq!(
+        tiers(&tiered),
+        vec![vec!["http://a/announce", "http://b/announce"]]
+    );
+}
+
+#[test]
+fn test_completions_bash() {
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let script = String::from_utf8(output).unwrap();
+    assert!(!script.is_empty());
+    assert!(script.contains("torrite"));
+    assert!(script.contains("--piece-length"));
+
+    for shell in ["zsh", "fish", "powershell"] {
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .args(["completions", shell])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("torrite"));
+    }
+}
+
+#[test]
+fn test_piece_layers_external_round_trip() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let content = temp_dir.path().join("release");
+    fs::create_dir(&content).unwrap();
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    fs::write(content.join("big.bin"), &data).unwrap();
+    fs::write(content.join("small.txt"), "small").unwrap();
+
+    let create = |output: &std::path::Path, external: bool| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("create")
+            .arg(&content)
+            .args(["--hybrid", "-l", "15", "-d", "-o"])
+            .arg(output);
+        if external {
+            cmd.arg("--piece-layers-external");
+        }
+        cmd.assert().success();
+        serde_bencode::from_bytes::<torrite::Torrent>(&fs::read(output).unwrap()).unwrap()
+    };
+    let inline = create(&temp_dir.path().join("inline.torrent"), false);
+    let split_file = temp_dir.path().join("split.torrent");
+    let split = create(&split_file, true);
+    let layers_file = temp_dir.path().join("split.layers");
+
+    assert!(split.piece_layers.is_none());
+    assert!(layers_file.exists());
+
+    // An existing layers file stops the run before the torrent is written
+    fs::remove_file(&split_file).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&content)
+        .args(["--hybrid", "-l", "15", "-d", "--piece-layers-external", "-o"])
+        .arg(&split_file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("split.layers"));
+    assert!(!split_file.exists());
+    fs::remove_file(&layers_file).unwrap();
+    create(&split_file, true);
+    assert_eq!(split.info_hash_v1(), inline.info_hash_v1());
+    assert_eq!(split.info_hash_v2(), inline.info_hash_v2());
+
+    let merged_file = temp_dir.path().join("merged.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("recalc")
+        .arg(&split_file)
+        .arg("--layers")
+        .arg(&layers_file)
+        .arg("-o")
+        .arg(&merged_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Merged piece layers for 1 file(s)"));
+    let merged: torrite::Torrent =
+        serde_bencode::from_bytes(&fs::read(&merged_file).unwrap()).unwrap();
+    assert_eq!(merged.piece_layers, inline.piece_layers);
+    assert_eq!(merged.info_hash_v1(), inline.info_hash_v1());
+    assert_eq!(merged.info_hash_v2(), inline.info_hash_v2());
+    assert_eq!(
+        fs::read(&merged_file).unwrap(),
+        fs::read(temp_dir.path().join("inline.torrent")).unwrap()
+    );
+
+    // Layers from another torrent are refused
+    let other = temp_dir.path().join("other.torrent");
+    fs::write(content.join("small.txt"), "changed").unwrap();
+    create(&other, false);
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("recalc")
+        .arg(&other)
+        .arg("--layers")
+        .arg(&layers_file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Piece layers belong to info hash"));
+}
+
+#[test]
+fn test_recalc_restores_piece_layers() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let content = temp_dir.path().join("release");
+    fs::create_dir(&content).unwrap();
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    fs::write(content.join("big.bin"), &data).unwrap();
+    fs::write(content.join("small.txt"), "small").unwrap();
+    let torrent_file = temp_dir.path().join("release.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&content)
+        .args(["--hybrid", "-l", "15", "-o"])
+        .arg(&torrent_file)
+        .assert()
+        .success();
+    let original: torrite::Torrent =
+        serde_bencode::from_bytes(&fs::read(&torrent_file).unwrap()).unwrap();
+    assert!(!original.piece_layers.as_ref().unwrap().is_empty());
+
+    let mut stripped = original.clone();
+    stripped.piece_layers = None;
+    let stripped_file = temp_dir.path().join("stripped.torrent");
+    fs::write(&stripped_file, serde_bencode::to_bytes(&stripped).unwrap()).unwrap();
+
+    let recalc = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("recalc")
+            .arg(&stripped_file)
+            .arg("--path")
+            .arg(&content);
+        cmd
+    };
+    recalc().assert().success().stdout(predicate::str::contains(
+        "Restored piece layers for 1 file(s)",
+    ));
+
+    let restored: torrite::Torrent =
+        serde_bencode::from_bytes(&fs::read(&stripped_file).unwrap()).unwrap();
+    assert_eq!(restored.piece_layers, original.piece_layers);
+    assert_eq!(restored.info_hash_v1(), original.info_hash_v1());
+    assert_eq!(restored.info_hash_v2(), original.info_hash_v2());
+
+    // Data that doesn't reproduce the file tree leaves the torrent alone
+    let mut corrupt = data;
+    corrupt[50_000] ^= 0xFF;
+    fs::write(content.join("big.bin"), corrupt).unwrap();
+    fs::write(&stripped_file, serde_bencode::to_bytes(&stripped).unwrap()).unwrap();
+    recalc().assert().failure().stderr(predicate::str::contains(
+        "Data doesn't match the torrent's file tree, not writing piece layers: big.bin",
+    ));
+    let untouched: torrite::Torrent =
+        serde_bencode::from_bytes(&fs::read(&stripped_file).unwrap()).unwrap();
+    assert!(untouched.piece_layers.is_none());
+}
+
 #[test]
 fn test_dry_run() {
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
@@ -213,6 +673,78 @@ fn test_inspect() {
         .stdout(predicate::str::contains("Name:"));
 }
 
+#[test]
+fn test_stats_reports_bytes_hashed() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("stats");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("a.bin"), vec![1u8; 40_000]).unwrap();
+    fs::write(source_dir.join("b.bin"), vec![2u8; 25_000]).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .arg("--stats")
+        .arg("-o")
+        .arg(temp_dir.path().join("stats.torrent"))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("(65000 bytes)"))
+        .stderr(predicate::str::contains("V1 hashing"));
+
+    // A hybrid build hashes the data once per pass
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .args(["--hybrid", "--no-padding", "--stats", "-o"])
+        .arg(temp_dir.path().join("stats_hybrid.torrent"))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("(130000 bytes)"));
+}
+
+#[test]
+fn test_inspect_piece_count_mismatch() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("pieces.bin");
+    fs::write(&source_file, vec![5u8; 100_000]).unwrap();
+    let torrent_file = temp_dir.path().join("pieces.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["-l", "15", "-o"])
+        .arg(&torrent_file)
+        .assert()
+        .success();
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("inspect")
+        .arg(&torrent_file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Piece Count:")
+                .and(predicate::str::contains("piece hashes stored").not()),
+        );
+
+    // Drop the last piece hash, as a truncated or tampered torrent would
+    let mut torrent: torrite::Torrent =
+        serde_bencode::from_bytes(&fs::read(&torrent_file).unwrap()).unwrap();
+    let pieces = torrent.info.pieces.as_mut().unwrap();
+    let truncated = pieces.len() - 20;
+    pieces.truncate(truncated);
+    fs::write(&torrent_file, serde_bencode::to_bytes(&torrent).unwrap()).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("inspect")
+        .arg(&torrent_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "3 piece hashes stored, but 97.66 KiB at this piece size would need 4",
+        ));
+}
+
 #[test]
 fn test_config_file_and_profile() {
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
@@ -252,6 +784,216 @@ fn test_config_file_and_profile() {
         .stdout(predicate::str::contains("\"comment\": \"Profile Comment\""));
 }
 
+#[test]
+fn test_config_output_directory() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("release.txt");
+    fs::write(&source_file, "output directory test").unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+            [output]
+            directory = "{}"
+
+            [profiles.ptp]
+            output_directory = "{}"
+        "#,
+            temp_dir.path().join("torrents").display(),
+            temp_dir.path().join("ptp").display()
+        ),
+    )
+    .unwrap();
+
+    let create = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .current_dir(temp_dir.path())
+            .arg("--config")
+            .arg(&config_path)
+            .arg("create")
+            .arg(&source_file)
+            .args(args)
+            .assert()
+            .success();
+    };
+
+    create(&[]);
+    assert!(
+        temp_dir
+            .path()
+            .join("torrents/release.txt.torrent")
+            .exists()
+    );
+
+    // A profile's directory wins, and -o still overrides both
+    create(&["-P", "ptp"]);
+    assert!(temp_dir.path().join("ptp/release.txt.torrent").exists());
+
+    create(&["-o", "here.torrent"]);
+    assert!(temp_dir.path().join("here.torrent").exists());
+}
+
+#[test]
+fn test_default_profile_is_always_applied() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("defaults.txt");
+    fs::write(&source_file, "default profile test").unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        r#"
+        [profiles.default]
+        private = true
+        comment = "Org default"
+
+        [profiles.public]
+        private = false
+    "#,
+    )
+    .unwrap();
+
+    let create = |out: &str, args: &[&str]| -> (torrite::Torrent, String) {
+        let output = temp_dir.path().join(out);
+        let assert = Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .arg("--config")
+            .arg(&config_path)
+            .arg("create")
+            .arg(&source_file)
+            .arg("-o")
+            .arg(&output)
+            .args(args)
+            .assert()
+            .success();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).into_owned();
+        (serde_bencode::from_bytes(&fs::read(output).unwrap()).unwrap(), stderr)
+    };
+
+    // Picked up without -P, and without announcing it
+    let (torrent, stderr) = create("plain.torrent", &[]);
+    assert_eq!(torrent.info.private, Some(1));
+    assert_eq!(torrent.comment.as_deref(), Some("Org default"));
+    assert!(!stderr.contains("Using profile"), "{stderr}");
+
+    // A selected profile and explicit flags both win over it
+    let (torrent, stderr) = create("profile.torrent", &["-P", "public"]);
+    assert!(stderr.contains("Using profile: public"), "{stderr}");
+    assert_eq!(torrent.info.private, None);
+    assert_eq!(torrent.comment.as_deref(), Some("Org default"));
+    let (torrent, _) = create("flags.torrent", &["-c", "Mine"]);
+    assert_eq!(torrent.comment.as_deref(), Some("Mine"));
+}
+
+#[test]
+fn test_config_default_excludes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("release");
+    fs::create_dir(&source_dir).unwrap();
+    for name in ["movie.mkv", "movie.nfo", "Thumbs.db"] {
+        fs::write(source_dir.join(name), name).unwrap();
+    }
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        r#"
+        [defaults]
+        exclude = ["Thumbs.db"]
+
+        [profiles.scene]
+        exclude = ["*.nfo"]
+    "#,
+    )
+    .unwrap();
+
+    let names = |out: &str, args: &[&str]| -> Vec<String> {
+        let output = temp_dir.path().join(out);
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .arg("--config")
+            .arg(&config_path)
+            .arg("create")
+            .arg(&source_dir)
+            .arg("-o")
+            .arg(&output)
+            .args(args)
+            .assert()
+            .success();
+        let torrent: torrite::Torrent =
+            serde_bencode::from_bytes(&fs::read(output).unwrap()).unwrap();
+        torrent
+            .info
+            .files
+            .unwrap()
+            .into_iter()
+            .map(|f| f.path.join("/"))
+            .collect()
+    };
+
+    // Applied without -e, and merged with -e or a profile's excludes
+    assert_eq!(names("plain.torrent", &[]), ["movie.mkv", "movie.nfo"]);
+    assert_eq!(names("flag.torrent", &["-e", "*.nfo"]), ["movie.mkv"]);
+    assert_eq!(names("profile.torrent", &["-P", "scene"]), ["movie.mkv"]);
+    assert_eq!(
+        names("all.torrent", &["--no-default-excludes"]),
+        ["Thumbs.db", "movie.mkv", "movie.nfo"]
+    );
+}
+
+#[test]
+fn test_merge_trackers_with_profile() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("merge.txt");
+    fs::write(&source_file, "merge trackers test").unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        r#"
+        [profiles.site]
+        announce = ["https://site.example/announce"]
+    "#,
+    )
+    .unwrap();
+
+    let create = |out: &str, args: &[&str]| -> torrite::Torrent {
+        let output = temp_dir.path().join(out);
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .arg("--config")
+            .arg(&config_path)
+            .arg("create")
+            .arg(&source_file)
+            .args(["-P", "site", "-o"])
+            .arg(&output)
+            .args(args)
+            .assert()
+            .success();
+        serde_bencode::from_bytes(&fs::read(output).unwrap()).unwrap()
+    };
+
+    // By default -a replaces the profile's trackers
+    let torrent = create("override.torrent", &["-a", "https://mine.example/announce"]);
+    assert_eq!(
+        torrent.trackers(),
+        vec![vec!["https://mine.example/announce"]]
+    );
+
+    let torrent = create(
+        "merged.torrent",
+        &[
+            "--merge-trackers",
+            "-a",
+            "https://mine.example/announce",
+            "-a",
+            "https://site.example/announce",
+        ],
+    );
+    assert_eq!(
+        torrent.trackers(),
+        vec![
+            vec!["https://site.example/announce"],
+            vec!["https://mine.example/announce"]
+        ]
+    );
+}
+
 #[test]
 fn test_tracker_defaults_ptp() {
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
@@ -293,3 +1035,1282 @@ fn test_tracker_defaults_override() {
         .success()
         .stdout(predicate::str::contains("\"source\": \"CUSTOM_SOURCE\""));
 }
+
+#[test]
+fn test_output_pattern() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("pattern.txt");
+    fs::write(&source_file, "output pattern data").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .current_dir(temp_dir.path())
+        .arg("create")
+        .arg(&source_file)
+        .arg("-a")
+        .arg("https://tracker.example.org:8080/announce")
+        .arg("--output-pattern")
+        .arg("{tracker}/{name}-{infohash8}.torrent")
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let summary: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let info_hash = summary["info_hash_v1"].as_str().unwrap();
+    let expected = format!(
+        "tracker.example.org/pattern.txt-{}.torrent",
+        &info_hash[..8]
+    );
+
+    assert_eq!(summary["file_path"].as_str().unwrap(), expected);
+    assert!(temp_dir.path().join(&expected).is_file());
+}
+
+#[test]
+fn test_scan() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path().join("tree");
+    fs::create_dir_all(root.join("sub")).unwrap();
+    fs::write(root.join("a.txt"), "aaaa").unwrap();
+    fs::write(root.join("sub/b.txt"), "bbbbbb").unwrap();
+    fs::write(root.join("skip.nfo"), "skipped").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("scan")
+        .arg(&root)
+        .arg("-e")
+        .arg("*.nfo")
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["file_count"], 2);
+    assert_eq!(report["total_size"], 10);
+    assert_eq!(report["piece_exp"], 15);
+    assert_eq!(report["files"][0]["path"], "a.txt");
+    assert_eq!(report["files"][1]["path"], "sub/b.txt");
+    assert_eq!(report["files"][1]["length"], 6);
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("scan")
+        .arg(&root)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("skip.nfo"))
+        .stdout(predicate::str::contains("File Count:"));
+
+    // The walk options match create's
+    fs::create_dir(root.join("empty")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("scan")
+        .arg(&root)
+        .args(["--no-recursion", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["file_count"], 2);
+    assert_eq!(report["files"][1]["path"], "skip.nfo");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("scan")
+        .arg(&root)
+        .args(["--keep-empty-dirs", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["files"][1]["path"], "empty/.keep");
+}
+
+#[test]
+fn test_verify_find() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let content = temp_dir.path().join("downloads/2024/release");
+    fs::create_dir_all(content.join("sub")).unwrap();
+    fs::write(content.join("a.txt"), "find me").unwrap();
+    fs::write(content.join("sub/b.txt"), "and me too").unwrap();
+    let torrent_file = temp_dir.path().join("release.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&content)
+        .arg("-o")
+        .arg(&torrent_file)
+        .assert()
+        .success();
+
+    // A shallower directory with the same name but different contents is skipped
+    let decoy = temp_dir.path().join("release");
+    fs::create_dir_all(decoy.join("sub")).unwrap();
+    fs::write(decoy.join("a.txt"), "wrong size").unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("verify")
+        .arg(&torrent_file)
+        .arg("--find")
+        .arg(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Found content at: {}",
+            content.display()
+        )))
+        .stdout(predicate::str::contains("Verification Successful!"));
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("verify")
+        .arg(&torrent_file)
+        .arg("--find")
+        .arg(decoy.join("sub"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Could not find 'release'"));
+}
+
+#[test]
+fn test_private_tracker_without_private_flag() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("private_test.txt");
+    fs::write(&source_file, "private tracker data").unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        r#"
+        [profiles.ptp]
+        announce = ["https://passthepopcorn.me/announce"]
+
+        [profiles.ptp_public]
+        announce = ["https://passthepopcorn.me/announce"]
+        private = false
+    "#,
+    )
+    .unwrap();
+
+    let create = |args: &[&str], out: &str| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("--config")
+            .arg(&config_path)
+            .arg("create")
+            .arg(&source_file)
+            .args(args)
+            .arg("-o")
+            .arg(temp_dir.path().join(out));
+        cmd
+    };
+    let is_private = |out: &str| {
+        let torrent: torrite::Torrent =
+            serde_bencode::from_bytes(&fs::read(temp_dir.path().join(out)).unwrap()).unwrap();
+        torrent.info.private == Some(1)
+    };
+
+    // A profile that doesn't mention private still gets the tracker's requirement
+    create(&["-P", "ptp"], "auto.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: passthepopcorn.me requires the private flag; setting it",
+        ));
+    assert!(is_private("auto.torrent"));
+
+    // Setting the flag silences the notice
+    create(
+        &["-a", "https://passthepopcorn.me/announce", "-p"],
+        "private.torrent",
+    )
+    .assert()
+    .success()
+    .stderr(predicate::str::contains("requires the private flag").not());
+    assert!(is_private("private.torrent"));
+
+    // An explicit opt-out (profile or --public) wins, with a warning
+    create(&["-P", "ptp_public"], "profile_public.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("but the torrent is public"));
+    assert!(!is_private("profile_public.torrent"));
+    create(&["-P", "ptp", "--public"], "public.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("but the torrent is public"));
+    assert!(!is_private("public.torrent"));
+
+    // --strict turns it into an error
+    create(&["-P", "ptp", "--public", "--strict"], "strict.torrent")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("requires the private flag"));
+    assert!(!temp_dir.path().join("strict.torrent").exists());
+}
+
+#[test]
+fn test_private_with_public_tracker_warns() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("data.txt");
+    fs::write(&source_file, "some data").unwrap();
+
+    let create = |args: &[&str], out: &str| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("create")
+            .arg(&source_file)
+            .args(args)
+            .arg("-o")
+            .arg(temp_dir.path().join(out));
+        cmd
+    };
+
+    create(
+        &[
+            "-p",
+            "-a",
+            "https://private.example/announce,udp://tracker.opentrackr.org:1337/announce",
+        ],
+        "private.torrent",
+    )
+    .assert()
+    .success()
+    .stderr(predicate::str::contains(
+        "Warning: The torrent is private but announces to public tracker(s): \
+         udp://tracker.opentrackr.org:1337/announce",
+    ));
+
+    // A tracker that's neither known public nor known private gets a milder warning
+    create(
+        &["-p", "-a", "https://private.example/announce"],
+        "unknown.torrent",
+    )
+    .assert()
+    .success()
+    .stderr(predicate::str::contains(
+        "none of its trackers is a known private tracker",
+    ));
+
+    // Known private trackers don't warn
+    create(
+        &["-p", "-a", "https://passthepopcorn.me/announce"],
+        "known.torrent",
+    )
+    .assert()
+    .success()
+    .stderr(predicate::str::contains("Warning: The torrent is private").not());
+
+    // Public torrents may use public trackers freely
+    create(
+        &["-a", "udp://tracker.opentrackr.org:1337/announce"],
+        "public.torrent",
+    )
+    .assert()
+    .success()
+    .stderr(predicate::str::contains("public tracker").not());
+}
+
+#[test]
+fn test_non_ascii_source_string() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("source_test.txt");
+    fs::write(&source_file, "source tag data").unwrap();
+
+    let create = |source: &str, out: &str| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("create")
+            .arg(&source_file)
+            .args(["-s", source])
+            .arg("-o")
+            .arg(temp_dir.path().join(out));
+        cmd
+    };
+
+    create("TRÄCKER", "accent.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: Source string \"TRÄCKER\" contains non-ASCII or whitespace characters",
+        ));
+    create("MY TAG", "space.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("contains non-ASCII or whitespace"));
+    create("TRACKER", "plain.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Source string").not());
+
+    create("TRÄCKER", "strict.torrent")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("contains non-ASCII"));
+    assert!(!temp_dir.path().join("strict.torrent").exists());
+}
+
+// Needs a case-sensitive filesystem to create both names
+#[cfg(target_os = "linux")]
+#[test]
+fn test_case_insensitive_collisions() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("mixed_case");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("README"), "upper").unwrap();
+    fs::write(source_dir.join("readme"), "lower").unwrap();
+    fs::write(source_dir.join("other.txt"), "other").unwrap();
+
+    let create = |out: &str| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("create")
+            .arg(&source_dir)
+            .arg("-o")
+            .arg(temp_dir.path().join(out));
+        cmd
+    };
+
+    create("warn.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: Paths that differ only in case will collide on case-insensitive filesystems: README, readme",
+        ));
+
+    create("strict.torrent")
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("differ only in case"));
+    assert!(!temp_dir.path().join("strict.torrent").exists());
+}
+
+#[test]
+fn test_comment_template() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("release");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("a.txt"), "first").unwrap();
+    fs::write(source_dir.join("b.txt"), "second").unwrap();
+    let output_file = temp_dir.path().join("release.torrent");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .arg("--hybrid")
+        .arg("--comment-template")
+        .arg("{name}: {files} files, {size} [{infohash}]")
+        .arg("-o")
+        .arg(&output_file)
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let summary: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let expected = format!(
+        "release: 2 files, 11 B [{}]",
+        summary["info_hash_v1"].as_str().unwrap()
+    );
+
+    let torrent: torrite::Torrent =
+        serde_bencode::from_bytes(&fs::read(&output_file).unwrap()).unwrap();
+    assert_eq!(torrent.comment.as_deref(), Some(expected.as_str()));
+}
+
+#[test]
+fn test_default_mode_env() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("mode.txt");
+    fs::write(&source_file, "default mode data").unwrap();
+
+    let create = |extra: &[&str], out: &str| {
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .env("TORRITE_DEFAULT_MODE", "hybrid")
+            .arg("create")
+            .arg(&source_file)
+            .args(extra)
+            .arg("-o")
+            .arg(temp_dir.path().join(out))
+            .arg("--json")
+            .assert()
+    };
+    let mode = |assert: assert_cmd::assert::Assert| {
+        let summary: serde_json::Value =
+            serde_json::from_slice(&assert.success().get_output().stdout).unwrap();
+        summary["mode"].as_str().unwrap().to_string()
+    };
+
+    // The env var replaces the built-in V1 default...
+    assert_eq!(mode(create(&[], "env.torrent")), "hybrid");
+    // ...but an explicit flag still wins, including a plain V1
+    assert_eq!(mode(create(&["--v2"], "flag.torrent")), "v2");
+    assert_eq!(mode(create(&["--v1"], "v1.torrent")), "v1");
+
+    // So does --v1 over a default profile's mode
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(&config_path, "[profiles.default]\nv2 = true\n").unwrap();
+    let with_config = |extra: &[&str], out: &str| {
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .env_remove("TORRITE_DEFAULT_MODE")
+            .arg("--config")
+            .arg(&config_path)
+            .arg("create")
+            .arg(&source_file)
+            .args(extra)
+            .arg("-o")
+            .arg(temp_dir.path().join(out))
+            .arg("--json")
+            .assert()
+    };
+    assert_eq!(mode(with_config(&[], "profile.torrent")), "v2");
+    assert_eq!(mode(with_config(&["--v1"], "profile-v1.torrent")), "v1");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .env("TORRITE_DEFAULT_MODE", "v3")
+        .arg("create")
+        .arg(&source_file)
+        .arg("--dry-run")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("TORRITE_DEFAULT_MODE"));
+}
+
+#[test]
+fn test_rehash_verify() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("data.bin");
+    fs::write(&source_file, vec![3u8; 100_000]).unwrap();
+    let output_file = temp_dir.path().join("data.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["--hybrid", "--rehash-verify", "-v", "-o"])
+        .arg(&output_file)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Self-check passed"));
+    assert!(output_file.exists());
+}
+
+#[test]
+fn test_write_magnet() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("magnet.txt");
+    fs::write(&source_file, "magnet data").unwrap();
+    let output_file = temp_dir.path().join("out").join("magnet.torrent");
+    fs::create_dir(output_file.parent().unwrap()).unwrap();
+
+    let create = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("create")
+            .arg(&source_file)
+            .arg("--write-magnet")
+            .arg("-o")
+            .arg(&output_file)
+            .arg("--json");
+        cmd
+    };
+
+    let output = create().assert().success().get_output().stdout.clone();
+    let summary: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let info_hash = summary["info_hash_v1"].as_str().unwrap();
+
+    let magnet = fs::read_to_string(temp_dir.path().join("out").join("magnet.magnet")).unwrap();
+    assert!(magnet.starts_with("magnet:?"));
+    assert!(magnet.contains(&format!("urn:btih:{}", info_hash)));
+
+    // Existing files are only replaced with --force, and the magnet file is checked
+    // before the torrent is written
+    fs::remove_file(&output_file).unwrap();
+    create().assert().failure();
+    assert!(!output_file.exists());
+    create().arg("-f").assert().success();
+}
+
+#[test]
+fn test_write_summary() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("summary.txt");
+    fs::write(&source_file, "summary data").unwrap();
+    let output_file = temp_dir.path().join("summary.torrent");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args([
+            "--hybrid",
+            "--write-summary",
+            "-a",
+            "http://tracker/announce",
+            "-o",
+        ])
+        .arg(&output_file)
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let created: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    let summary = fs::read_to_string(temp_dir.path().join("summary.torrent.txt")).unwrap();
+    assert!(summary.contains("Name:"));
+    assert!(summary.contains("summary.txt"));
+    assert!(summary.contains(created["info_hash_v1"].as_str().unwrap()));
+    assert!(summary.contains(created["info_hash_v2"].as_str().unwrap()));
+    assert!(summary.contains("http://tracker/announce"));
+    assert!(summary.contains("File Count:"));
+    assert!(!summary.contains('\x1b'));
+}
+
+#[test]
+fn test_cross_seed_from_inherit_metadata() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("episode.mkv");
+    fs::write(&source_file, "cross-seeded content").unwrap();
+    let original = temp_dir.path().join("original.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["-c", "Original release notes", "-s", "ORIG"])
+        .arg("-o")
+        .arg(&original)
+        .assert()
+        .success();
+
+    let cross_seed = |extra: &[&str], out: &str| {
+        let output_file = temp_dir.path().join(out);
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .arg("create")
+            .arg(&source_file)
+            .arg("--cross-seed-from")
+            .arg(&original)
+            .args(extra)
+            .arg("-o")
+            .arg(&output_file)
+            .assert()
+            .success();
+        let torrent: torrite::Torrent =
+            serde_bencode::from_bytes(&fs::read(&output_file).unwrap()).unwrap();
+        torrent
+    };
+
+    let inherited = cross_seed(&["--inherit-metadata"], "inherited.torrent");
+    assert_eq!(inherited.comment.as_deref(), Some("Original release notes"));
+    assert_eq!(inherited.info.source.as_deref(), Some("ORIG"));
+    assert!(inherited.info.x_cross_seed.is_some());
+
+    let plain = cross_seed(&[], "plain.torrent");
+    assert_eq!(plain.comment, None);
+    assert_eq!(plain.info.source, None);
+    assert!(plain.info.x_cross_seed.is_some());
+
+    let overridden = cross_seed(&["--inherit-metadata", "-s", "NEW"], "overridden.torrent");
+    assert_eq!(overridden.info.source.as_deref(), Some("NEW"));
+    assert_eq!(
+        overridden.comment.as_deref(),
+        Some("Original release notes")
+    );
+}
+
+#[test]
+fn test_created_by_override_and_omission() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("parity.bin");
+    fs::write(&source_file, "byte parity").unwrap();
+    let top_level_keys = |path: &std::path::Path| -> Vec<String> {
+        let value: serde_bencode::value::Value =
+            serde_bencode::from_bytes(&fs::read(path).unwrap()).unwrap();
+        let serde_bencode::value::Value::Dict(root) = value else {
+            panic!("torrent is not a dictionary");
+        };
+        root.keys()
+            .map(|k| String::from_utf8_lossy(k).into_owned())
+            .collect()
+    };
+
+    let bare = temp_dir.path().join("bare.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["--created-by", "", "-d", "-o"])
+        .arg(&bare)
+        .assert()
+        .success();
+    assert_eq!(top_level_keys(&bare), vec!["info"]);
+    let torrent: torrite::Torrent = serde_bencode::from_bytes(&fs::read(&bare).unwrap()).unwrap();
+    assert_eq!(torrent.created_by, "");
+    assert_eq!(torrent.creation_date, None);
+    assert_eq!(torrent.comment, None);
+
+    let custom = temp_dir.path().join("custom.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["--created-by", "mktorrent 1.1", "-d", "-o"])
+        .arg(&custom)
+        .assert()
+        .success();
+    let torrent: torrite::Torrent = serde_bencode::from_bytes(&fs::read(&custom).unwrap()).unwrap();
+    assert_eq!(torrent.created_by, "mktorrent 1.1");
+
+    // Only non-info fields changed, so the info hash is shared
+    let info_hash = |path: &std::path::Path| {
+        let torrent: torrite::Torrent =
+            serde_bencode::from_bytes(&fs::read(path).unwrap()).unwrap();
+        torrent.info_hash_v1()
+    };
+    assert_eq!(info_hash(&bare), info_hash(&custom));
+}
+
+#[test]
+fn test_experimental_meta_version() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("meta.bin");
+    fs::write(&source_file, vec![4u8; 20_000]).unwrap();
+    let output = temp_dir.path().join("meta.torrent");
+
+    // Refused unless explicitly opted in
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["--hybrid", "--meta-version", "3", "-o"])
+        .arg(&output)
+        .assert()
+        .failure();
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["--hybrid", "--experimental", "--meta-version", "3", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("nonstandard meta version 3"));
+
+    let value: serde_bencode::value::Value =
+        serde_bencode::from_bytes(&fs::read(&output).unwrap()).unwrap();
+    let serde_bencode::value::Value::Dict(root) = value else {
+        panic!("torrent is not a dictionary");
+    };
+    let Some(serde_bencode::value::Value::Dict(info)) = root.get(b"info".as_slice()) else {
+        panic!("torrent has no info dictionary");
+    };
+    assert_eq!(
+        info.get(b"meta version".as_slice()),
+        Some(&serde_bencode::value::Value::Int(3))
+    );
+}
+
+#[test]
+fn test_stray_torrent_file_warns() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("album");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("track.flac"), "audio").unwrap();
+    fs::write(source_dir.join("old.torrent"), "d4:infodee").unwrap();
+
+    let create = |extra: &[&str], out: &str| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_torrite"));
+        cmd.arg("create")
+            .arg(&source_dir)
+            .args(extra)
+            .arg("-o")
+            .arg(temp_dir.path().join(out));
+        cmd
+    };
+
+    create(&[], "warned.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "The content includes .torrent file(s): old.torrent",
+        ))
+        .stderr(predicate::str::contains("-e '*.torrent'"));
+    create(&["--allow-torrent-files"], "allowed.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(".torrent file(s)").not());
+    create(&["-e", "*.torrent"], "excluded.torrent")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(".torrent file(s)").not());
+}
+
+#[test]
+fn test_piece_length_from() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let reference_file = temp_dir.path().join("reference.bin");
+    fs::write(&reference_file, vec![1u8; 10_000]).unwrap();
+    let reference = temp_dir.path().join("reference.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&reference_file)
+        .args(["-l", "17", "-s", "REF", "-o"])
+        .arg(&reference)
+        .assert()
+        .success();
+
+    // Different content and layout; only the piece length carries over
+    let source_dir = temp_dir.path().join("other");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("a.bin"), vec![2u8; 300_000]).unwrap();
+    fs::write(source_dir.join("b.bin"), vec![3u8; 5_000]).unwrap();
+    let output = temp_dir.path().join("other.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .arg("--piece-length-from")
+        .arg(&reference)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success();
+
+    let torrent: torrite::Torrent = serde_bencode::from_bytes(&fs::read(&output).unwrap()).unwrap();
+    assert_eq!(torrent.info.piece_length, 1 << 17);
+    assert_eq!(torrent.info.name, "other");
+    assert_eq!(torrent.info.source, None);
+    assert_eq!(torrent.info.files.as_ref().map(Vec::len), Some(2));
+}
+
+#[test]
+fn test_like_inherits_settings() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("companion.bin");
+    fs::write(&source_file, vec![3u8; 50_000]).unwrap();
+    let template = temp_dir.path().join("template.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["-l", "16", "-p", "-s", "SRC"])
+        .args(["-a", "http://a.example/announce,http://b.example/announce"])
+        .args(["-a", "http://c.example/announce"])
+        .arg("-o")
+        .arg(&template)
+        .assert()
+        .success();
+
+    let create_like = |extra: &[&str], out: &str| {
+        let output_file = temp_dir.path().join(out);
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .arg("create")
+            .arg(&source_file)
+            .arg("--like")
+            .arg(&template)
+            .args(extra)
+            .arg("-o")
+            .arg(&output_file)
+            .assert()
+            .success();
+        let torrent: torrite::Torrent =
+            serde_bencode::from_bytes(&fs::read(&output_file).unwrap()).unwrap();
+        torrent
+    };
+
+    let like = create_like(&[], "like.torrent");
+    assert_eq!(like.info.piece_length, 1 << 16);
+    assert_eq!(like.info.private, Some(1));
+    assert_eq!(like.info.source.as_deref(), Some("SRC"));
+    assert_eq!(
+        like.trackers(),
+        vec![
+            vec![
+                "http://a.example/announce".to_string(),
+                "http://b.example/announce".to_string()
+            ],
+            vec!["http://c.example/announce".to_string()],
+        ]
+    );
+
+    // Explicit flags still win
+    let overridden = create_like(&["-l", "15"], "overridden.torrent");
+    assert_eq!(overridden.info.piece_length, 1 << 15);
+}
+
+#[test]
+fn test_emit_json() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("release");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("big.bin"), vec![7u8; 100_000]).unwrap();
+    fs::write(source_dir.join("small.txt"), "small").unwrap();
+    let output_file = temp_dir.path().join("release.json");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .args(["--hybrid", "-l", "15", "--emit", "json", "-c", "debug dump"])
+        .arg("-o")
+        .arg(&output_file)
+        .assert()
+        .success();
+
+    let json: serde_json::Value = serde_json::from_slice(&fs::read(&output_file).unwrap()).unwrap();
+    assert_eq!(json["comment"], "debug dump");
+    let info = &json["info"];
+    assert_eq!(info["name"], "release");
+    assert_eq!(info["piece length"], 1 << 15);
+    assert_eq!(info["meta version"], 2);
+
+    // Binary fields come out as hex strings
+    let pieces = info["pieces"].as_str().unwrap();
+    assert_eq!(pieces.len() % 40, 0);
+    assert!(pieces.chars().all(|c| c.is_ascii_hexdigit()));
+    let root = info["file tree"]["big.bin"][""]["pieces root"]
+        .as_str()
+        .unwrap();
+    assert_eq!(root.len(), 64);
+    assert!(json["piece layers"].get(root).unwrap().is_string());
+}
+
+#[test]
+fn test_date_comment() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("dated.txt");
+    fs::write(&source_file, "dated content").unwrap();
+
+    let comment_with = |args: &[&str], out: &str| {
+        let output_file = temp_dir.path().join(out);
+        Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .arg("create")
+            .arg(&source_file)
+            .arg("--date-comment")
+            .args(args)
+            .arg("-o")
+            .arg(&output_file)
+            .assert()
+            .success();
+        let torrent: torrite::Torrent =
+            serde_bencode::from_bytes(&fs::read(&output_file).unwrap()).unwrap();
+        torrent.comment
+    };
+
+    assert_eq!(
+        comment_with(&["--date", "1700000000", "-c", "Notes"], "fixed.torrent").as_deref(),
+        Some("Notes\nCreated 2023-11-14T22:13:20Z (UTC)")
+    );
+
+    let now = comment_with(&[], "now.torrent").unwrap();
+    let stamp = now
+        .strip_prefix("Created ")
+        .and_then(|s| s.strip_suffix(" (UTC)"))
+        .unwrap();
+    assert!(chrono::DateTime::parse_from_rfc3339(stamp).is_ok());
+
+    assert_eq!(
+        comment_with(&["--no-date", "-c", "Notes"], "undated.torrent").as_deref(),
+        Some("Notes")
+    );
+}
+
+#[test]
+fn test_threads_zero_means_auto() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("threads.txt");
+    fs::write(&source_file, "thread count data").unwrap();
+
+    let hash_with = |threads: &str, out: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+            .arg("create")
+            .arg(&source_file)
+            .arg("--threads")
+            .arg(threads)
+            .arg("-o")
+            .arg(temp_dir.path().join(out))
+            .arg("--json")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let summary: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        summary["info_hash_v1"].as_str().unwrap().to_string()
+    };
+
+    assert_eq!(
+        hash_with("0", "auto.torrent"),
+        hash_with("2", "two.torrent")
+    );
+}
+
+#[test]
+fn test_inspect_dump_bencode() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("dump.bin");
+    fs::write(&source_file, vec![3u8; 40_000]).unwrap();
+    let torrent_file = temp_dir.path().join("dump.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .arg("--hybrid")
+        .arg("-l")
+        .arg("14")
+        .arg("-a")
+        .arg("http://tracker.example/announce")
+        .arg("-o")
+        .arg(&torrent_file)
+        .assert()
+        .success();
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("inspect")
+        .arg(&torrent_file)
+        .arg("--dump-bencode")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("dict["))
+        .stdout(predicate::str::contains(
+            "  \"announce\": str[31] \"http://tracker.example/announce\"",
+        ))
+        .stdout(predicate::str::contains("  \"info\": dict["))
+        .stdout(predicate::str::contains("  \"piece layers\": dict[1]"))
+        // 3 pieces of 20 bytes, shown as truncated hex
+        .stdout(predicate::str::contains("\"pieces\": bytes[60] "))
+        .stdout(predicate::str::contains("Torrent Metadata:").not());
+}
+
+#[test]
+fn test_inspect_piece_layers() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("layers.bin");
+    // Four 16 KiB pieces
+    fs::write(&source_file, vec![5u8; 60_000]).unwrap();
+    let torrent_file = temp_dir.path().join("layers.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .arg("--v2")
+        .arg("-l")
+        .arg("14")
+        .arg("-o")
+        .arg(&torrent_file)
+        .assert()
+        .success();
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("inspect")
+        .arg(&torrent_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Piece Layers:"))
+        .stdout(predicate::str::contains("Entries:      1"))
+        .stdout(predicate::str::contains("Layer Bytes:  128 B"))
+        .stdout(predicate::str::contains("4 pieces"));
+}
+
+#[test]
+fn test_glob_source() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let videos = temp_dir.path().join("videos");
+    fs::create_dir(&videos).unwrap();
+    fs::write(videos.join("a.mkv"), vec![1u8; 20_000]).unwrap();
+    fs::write(videos.join("b.mkv"), vec![2u8; 30_000]).unwrap();
+    fs::write(videos.join("notes.txt"), b"not a video").unwrap();
+    let pattern = videos.join("*.mkv");
+
+    let output = temp_dir.path().join("glob.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&pattern)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success();
+    let torrent: torrite::Torrent = serde_bencode::from_bytes(&fs::read(&output).unwrap()).unwrap();
+    assert_eq!(torrent.info.name, "videos");
+    let paths: Vec<_> = torrent
+        .info
+        .files
+        .unwrap()
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    assert_eq!(paths, [vec!["a.mkv"], vec!["b.mkv"]]);
+
+    // --base keeps the directory the glob was rooted in
+    let based = temp_dir.path().join("based.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&pattern)
+        .arg("--base")
+        .arg(temp_dir.path())
+        .arg("-o")
+        .arg(&based)
+        .assert()
+        .success();
+    let torrent: torrite::Torrent = serde_bencode::from_bytes(&fs::read(&based).unwrap()).unwrap();
+    let paths: Vec<_> = torrent
+        .info
+        .files
+        .unwrap()
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    assert_eq!(paths, [vec!["videos", "a.mkv"], vec!["videos", "b.mkv"]]);
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(videos.join("*.iso"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No files match"));
+}
+
+#[test]
+fn test_edit_validate() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("valid.bin");
+    fs::write(&source_file, vec![6u8; 100_000]).unwrap();
+    let torrent_file = temp_dir.path().join("valid.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["-l", "15", "-o"])
+        .arg(&torrent_file)
+        .assert()
+        .success();
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args(["edit", "--validate"])
+        .arg(&torrent_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("is valid."));
+
+    // A missing piece hash and a path-traversing name
+    let mut torrent: torrite::Torrent =
+        serde_bencode::from_bytes(&fs::read(&torrent_file).unwrap()).unwrap();
+    let pieces = torrent.info.pieces.as_mut().unwrap();
+    let truncated = pieces.len() - 20;
+    pieces.truncate(truncated);
+    torrent.info.name = "..".to_string();
+    let bytes = serde_bencode::to_bytes(&torrent).unwrap();
+    fs::write(&torrent_file, &bytes).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args(["edit", "--validate"])
+        .arg(&torrent_file)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("3 piece hashes stored"))
+        .stdout(predicate::str::contains("'..' is not a valid file name"))
+        .stderr(predicate::str::contains("has 2 problem(s)"));
+    // Nothing was rewritten
+    assert_eq!(fs::read(&torrent_file).unwrap(), bytes);
+}
+
+#[test]
+fn test_publisher_round_trip() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_file = temp_dir.path().join("release.bin");
+    fs::write(&source_file, vec![7u8; 40_000]).unwrap();
+    let plain = temp_dir.path().join("plain.torrent");
+    let published = temp_dir.path().join("published.torrent");
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .arg("-o")
+        .arg(&plain)
+        .assert()
+        .success();
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_file)
+        .args(["--publisher", "Example Group"])
+        .args(["--publisher-url", "https://example.org/"])
+        .arg("-o")
+        .arg(&published)
+        .assert()
+        .success();
+
+    let read = |path: &std::path::Path| -> torrite::Torrent {
+        serde_bencode::from_bytes(&fs::read(path).unwrap()).unwrap()
+    };
+    let torrent = read(&published);
+    assert_eq!(torrent.publisher.as_deref(), Some("Example Group"));
+    assert_eq!(
+        torrent.publisher_url.as_deref(),
+        Some("https://example.org/")
+    );
+    assert_eq!(torrent.info_hash_v1(), read(&plain).info_hash_v1());
+
+    // A headless edit keeps both fields
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args(["edit", "--comment", "edited"])
+        .arg(&published)
+        .assert()
+        .success();
+    let edited = read(&published);
+    assert_eq!(edited.comment.as_deref(), Some("edited"));
+    assert_eq!(edited.publisher, torrent.publisher);
+    assert_eq!(edited.publisher_url, torrent.publisher_url);
+
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("inspect")
+        .arg(&published)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Example Group (https://example.org/)",
+        ));
+}
+
+#[test]
+fn test_store_mtime() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("dated");
+    fs::create_dir(&source_dir).unwrap();
+    let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+    for name in ["a.bin", "b.bin"] {
+        let path = source_dir.join(name);
+        fs::write(&path, vec![4u8; 10_000]).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    let read = |path: &std::path::Path| -> torrite::Torrent {
+        serde_bencode::from_bytes(&fs::read(path).unwrap()).unwrap()
+    };
+    let plain = temp_dir.path().join("plain.torrent");
+    let dated = temp_dir.path().join("dated.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(&source_dir)
+        .arg("-o")
+        .arg(&plain)
+        .assert()
+        .success();
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args(["create", "--store-mtime"])
+        .arg(&source_dir)
+        .arg("-o")
+        .arg(&dated)
+        .assert()
+        .success();
+
+    let plain = read(&plain);
+    assert!(
+        plain
+            .info
+            .files
+            .as_ref()
+            .unwrap()
+            .iter()
+            .all(|f| f.mtime.is_none())
+    );
+    let dated = read(&dated);
+    for file in dated.info.files.as_ref().unwrap() {
+        assert_eq!(file.mtime, Some(1_600_000_000));
+    }
+    // The key lives in the info dict
+    assert_ne!(dated.info_hash_v1(), plain.info_hash_v1());
+}
+
+#[test]
+fn test_length_hashes_a_prefix() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let source = temp_dir.path().join("disk.img");
+    fs::write(&source, &data).unwrap();
+    let truncated_dir = temp_dir.path().join("truncated");
+    fs::create_dir(&truncated_dir).unwrap();
+    fs::write(truncated_dir.join("disk.img"), &data[..40_000]).unwrap();
+
+    let read = |path: &std::path::Path| -> torrite::Torrent {
+        serde_bencode::from_bytes(&fs::read(path).unwrap()).unwrap()
+    };
+    let forced = temp_dir.path().join("forced.torrent");
+    let expected = temp_dir.path().join("expected.torrent");
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args(["create", "--length", "40000"])
+        .arg(&source)
+        .arg("-o")
+        .arg(&forced)
+        .assert()
+        .success();
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .arg("create")
+        .arg(truncated_dir.join("disk.img"))
+        .arg("-o")
+        .arg(&expected)
+        .assert()
+        .success();
+
+    let forced = read(&forced);
+    assert_eq!(forced.info.length, Some(40_000));
+    assert_eq!(forced.info.name, "disk.img");
+    assert_eq!(forced.info_hash_v1(), read(&expected).info_hash_v1());
+
+    // More bytes than the file holds
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args(["create", "--length", "100001"])
+        .arg(&source)
+        .arg("-o")
+        .arg(temp_dir.path().join("long.torrent"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("less than --length 100001"));
+}
+
+#[test]
+fn test_piece_length_report_json() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = temp_dir.path().join("padded");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("a.bin"), vec![1u8; 20_000]).unwrap();
+    fs::write(source_dir.join("b.bin"), vec![2u8; 100]).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args([
+            "create",
+            "--hybrid",
+            "-l",
+            "14",
+            "--piece-length-report",
+            "--json",
+        ])
+        .arg(&source_dir)
+        .arg("-o")
+        .arg(temp_dir.path().join("padded.torrent"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let padding = &summary["padding"];
+    assert_eq!(padding["padding_size"], 12_768);
+    assert_eq!(padding["content_size"], 20_100);
+    assert_eq!(padding["files"][0]["path"], "a.bin");
+    assert_eq!(padding["files"][0]["padding"], 12_768);
+
+    // Human output goes to stderr, also for a dry run
+    Command::new(env!("CARGO_BIN_EXE_torrite"))
+        .args([
+            "create",
+            "--hybrid",
+            "-l",
+            "14",
+            "--piece-length-report",
+            "--dry-run",
+        ])
+        .arg(&source_dir)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Padding:"))
+        .stderr(predicate::str::contains("+12.47 KiB a.bin"));
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::field_reassign_with_default)]
+
 use std::fs::File;
 use torrite::{TorrentBuilder, TorrentOptions, Mode};
 
@@ -34,6 +36,23 @@ fn test_builder_applies_ptp_defaults() {
     assert_eq!(torrent.info.piece_length, 65536);
 }
 
+#[test]
+fn test_tracker_preset_without_announce() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = create_dummy_file(tmp_dir.path(), "movie.mkv", 50 * 1024 * 1024);
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::V1;
+    options.tracker_preset = Some("ptp".to_string());
+
+    let torrent = TorrentBuilder::new(file_path, options).build().unwrap();
+
+    // PTP's curve puts 50 MiB at 2^16, where the default would be 2^15
+    assert_eq!(torrent.info.piece_length, 1 << 16);
+    assert_eq!(torrent.info.source, Some("PTP".to_string()));
+    assert!(torrent.announce.is_none());
+}
+
 #[test]
 fn test_builder_applies_anthelion_defaults() {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -1,3 +1,5 @@
+#![allow(clippy::field_reassign_with_default)]
+
 use std::fs::File;
 use std::io::Write;
 use torrite::{TorrentBuilder, TorrentOptions, Mode};
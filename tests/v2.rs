@@ -1,6 +1,10 @@
+#![allow(clippy::field_reassign_with_default)]
+
 use std::fs::File;
 use std::io::Write;
 use torrite::{TorrentBuilder, TorrentOptions, Mode};
+use torrite::models::Node;
+use torrite::tree::find_duplicates;
 
 #[test]
 fn test_generate_single_file_torrent_v2() {
@@ -42,3 +46,75 @@ fn test_generate_single_file_torrent_v2() {
     // Cleanup
     std::fs::remove_dir_all(&tmp_dir).unwrap();
 }
+
+#[test]
+fn test_find_duplicates_groups_identical_files() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("dupes");
+    std::fs::create_dir_all(content_dir.join("copy")).unwrap();
+    let data = vec![42u8; 40_000];
+    File::create(content_dir.join("a.bin")).unwrap().write_all(&data).unwrap();
+    File::create(content_dir.join("copy/a.bin")).unwrap().write_all(&data).unwrap();
+    File::create(content_dir.join("unique.bin")).unwrap().write_all(b"unique").unwrap();
+    File::create(content_dir.join("empty1")).unwrap();
+    File::create(content_dir.join("empty2")).unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::V2;
+    let torrent = TorrentBuilder::new(content_dir, options).build().unwrap();
+
+    let groups = find_duplicates(torrent.info.file_tree.as_ref().unwrap());
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].paths, vec!["a.bin", "copy/a.bin"]);
+    assert_eq!(groups[0].length, 40_000);
+    assert_eq!(groups[0].wasted(), 40_000);
+}
+
+#[test]
+fn test_single_file_length_field_and_tree() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let file_path = tmp_dir.path().join("single.bin");
+    File::create(&file_path).unwrap().write_all(&vec![3u8; 50_000]).unwrap();
+
+    let build = |mode: Mode, name: Option<&str>| {
+        let mut options = TorrentOptions::default();
+        options.mode = mode;
+        options.piece_length = Some(15);
+        options.name = name.map(String::from);
+        TorrentBuilder::new(file_path.clone(), options).build().unwrap()
+    };
+
+    // BEP 52: V2-only single-file torrents carry the length in the file tree only
+    let v2 = build(Mode::V2, None);
+    assert!(v2.info.length.is_none());
+    assert!(v2.info.is_single_file());
+    assert_eq!(v2.total_size(), 50_000);
+    let tree = v2.info.file_tree.as_ref().unwrap();
+    assert_eq!(tree.keys().collect::<Vec<_>>(), vec!["single.bin"]);
+    assert!(matches!(&tree["single.bin"], Node::File(f) if f.metadata.length == 50_000));
+
+    // Hybrid keeps the V1 length, and the tree follows a custom name
+    let hybrid = build(Mode::Hybrid, Some("renamed.bin"));
+    assert_eq!(hybrid.info.length, Some(50_000));
+    assert_eq!(hybrid.total_size(), 50_000);
+    let tree = hybrid.info.file_tree.as_ref().unwrap();
+    assert_eq!(tree.keys().collect::<Vec<_>>(), vec!["renamed.bin"]);
+}
+
+#[test]
+fn test_total_size_multi_file_v2() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let content_dir = tmp_dir.path().join("multi");
+    std::fs::create_dir_all(content_dir.join("sub")).unwrap();
+    File::create(content_dir.join("a.bin")).unwrap().write_all(&[1u8; 1000]).unwrap();
+    File::create(content_dir.join("sub/b.bin")).unwrap().write_all(&[2u8; 234]).unwrap();
+
+    let mut options = TorrentOptions::default();
+    options.mode = Mode::V2;
+    let torrent = TorrentBuilder::new(content_dir, options).build().unwrap();
+
+    assert!(torrent.info.length.is_none());
+    assert!(torrent.info.files.is_none());
+    assert!(!torrent.info.is_single_file());
+    assert_eq!(torrent.total_size(), 1234);
+}